@@ -0,0 +1,124 @@
+use rodio::{Decoder, OutputStream, Sink, Source};
+use std::{
+    fs::File,
+    io::{BufReader, Cursor},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+const PREVIEW_LENGTH: Duration = Duration::from_secs(3);
+
+const DEFAULT_CHIME: &[u8] = include_bytes!("../assets/default_chime.ogg");
+
+/// Bumped by `stop_all`; sounds started before the bump stop playing.
+static STOP_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+type BoxedSource = Box<dyn Source<Item = i16> + Send>;
+
+fn open(path: &Path) -> Result<Decoder<BufReader<File>>, String> {
+    let file = File::open(path).map_err(|e| format!("Can't open file: {}", e))?;
+    Decoder::new(BufReader::new(file)).map_err(|e| format!("Can't decode audio: {}", e))
+}
+
+fn default_chime() -> BoxedSource {
+    Box::new(Decoder::new(Cursor::new(DEFAULT_CHIME)).expect("bundled chime should decode"))
+}
+
+/// Decodes `path`, or the bundled chime when there is no path. A file that can't be
+/// played also falls back to the chime, and the reason is returned alongside.
+fn source_for(path: Option<&Path>) -> (BoxedSource, Option<String>) {
+    match path.map(open) {
+        Some(Ok(decoder)) => (Box::new(decoder), None),
+        Some(Err(e)) => (default_chime(), Some(e)),
+        None => (default_chime(), None),
+    }
+}
+
+/// Plays `path` (or the default chime) to the end on its own thread and output
+/// stream, so the UI never waits on it and several sounds can overlap. Returns why
+/// the configured file couldn't be used, if the chime had to stand in for it.
+pub fn play(path: Option<&Path>, volume: f32) -> Option<String> {
+    log::debug!("Trying to play {:?}", path);
+
+    let (source, fallback) = source_for(path);
+    if let Some(e) = &fallback {
+        log::warn!("Falling back to the default chime: {}", e);
+    }
+
+    let generation = STOP_GENERATION.load(Ordering::Relaxed);
+    thread::spawn(move || {
+        if let Ok((_stream, stream_handle)) = OutputStream::try_default() {
+            let sink = Sink::try_new(&stream_handle).unwrap();
+            sink.set_volume(volume);
+            sink.append(source);
+
+            while !sink.empty() && STOP_GENERATION.load(Ordering::Relaxed) == generation {
+                thread::sleep(Duration::from_millis(30));
+            }
+            sink.stop();
+        } else {
+            log::warn!("No audio output stream found");
+        }
+    });
+
+    fallback
+}
+
+/// Stops every sound started by `play` that is still playing.
+pub fn stop_all() {
+    STOP_GENERATION.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Checks that `path` can be opened and decoded, returning a readable error if not.
+pub fn check(path: &Path) -> Result<(), String> {
+    open(path).map(|_| ())
+}
+
+/// A short, stoppable sample of a sound file. Starting a new preview stops the
+/// previous one; real alarms are unaffected since they use their own streams.
+#[derive(Default)]
+pub struct Preview {
+    stop: Option<Arc<AtomicBool>>,
+}
+
+impl Preview {
+    pub fn start(&mut self, path: Option<PathBuf>, volume: f32) {
+        self.stop();
+
+        let stop = Arc::new(AtomicBool::new(false));
+        self.stop = Some(stop.clone());
+
+        let (source, _) = source_for(path.as_deref());
+        thread::spawn(move || {
+            if let Ok((_stream, stream_handle)) = OutputStream::try_default() {
+                if let Ok(sink) = Sink::try_new(&stream_handle) {
+                    sink.set_volume(volume);
+                    sink.append(source.take_duration(PREVIEW_LENGTH));
+
+                    while !sink.empty() && !stop.load(Ordering::Relaxed) {
+                        thread::sleep(Duration::from_millis(30));
+                    }
+                    sink.stop();
+                }
+            } else {
+                log::warn!("No audio output stream found");
+            }
+            stop.store(true, Ordering::Relaxed);
+        });
+    }
+
+    pub fn stop(&mut self) {
+        if let Some(stop) = self.stop.take() {
+            stop.store(true, Ordering::Relaxed);
+        }
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.stop.as_ref().is_some_and(|stop| !stop.load(Ordering::Relaxed))
+    }
+}
@@ -0,0 +1,115 @@
+//! Launching the viewer at login through the current user's Run registry key.
+
+#[cfg(windows)]
+use windows::{
+    core::{w, HSTRING, PCWSTR},
+    Win32::Foundation::ERROR_FILE_NOT_FOUND,
+    Win32::System::Registry::{
+        RegCloseKey, RegDeleteValueW, RegOpenKeyExW, RegQueryValueExW, RegSetValueExW, HKEY, HKEY_CURRENT_USER,
+        KEY_QUERY_VALUE, KEY_SET_VALUE, REG_SAM_FLAGS, REG_SZ,
+    },
+};
+
+#[cfg(windows)]
+const RUN_KEY: PCWSTR = w!(r"Software\Microsoft\Windows\CurrentVersion\Run");
+#[cfg(windows)]
+const VALUE_NAME: PCWSTR = w!("GermiBoard");
+
+/// Passed by the Run entry, so a login launch can be told apart from a manual one.
+#[cfg(windows)]
+const MINIMIZED_FLAG: &str = "--minimized";
+
+/// The command line the Run entry should hold for the exe that's running now.
+#[cfg(windows)]
+fn command_line() -> Result<String, String> {
+    let exe = std::env::current_exe().map_err(|e| e.to_string())?;
+    Ok(format!("\"{}\" {}", exe.display(), MINIMIZED_FLAG))
+}
+
+#[cfg(windows)]
+struct RunKey(HKEY);
+
+#[cfg(windows)]
+impl RunKey {
+    fn open(access: REG_SAM_FLAGS) -> Result<Self, String> {
+        let mut key = HKEY::default();
+        unsafe { RegOpenKeyExW(HKEY_CURRENT_USER, RUN_KEY, 0, access, &mut key) }
+            .ok()
+            .map_err(|e| e.message().to_string())?;
+        Ok(Self(key))
+    }
+
+    fn read(&self) -> Option<String> {
+        let mut buffer = [0u16; 1024];
+        let mut size = std::mem::size_of_val(&buffer) as u32;
+        unsafe {
+            RegQueryValueExW(self.0, VALUE_NAME, None, None, Some(buffer.as_mut_ptr().cast()), Some(&mut size))
+        }
+        .ok()
+        .ok()?;
+        let len = (size as usize / 2).min(buffer.len());
+        Some(String::from_utf16_lossy(&buffer[..len]).trim_end_matches('\0').to_string())
+    }
+
+    fn write(&self, command: &str) -> Result<(), String> {
+        let data: Vec<u8> = HSTRING::from(command)
+            .as_wide()
+            .iter()
+            .chain(Some(&0))
+            .flat_map(|c| c.to_le_bytes())
+            .collect();
+        unsafe { RegSetValueExW(self.0, VALUE_NAME, 0, REG_SZ, Some(&data)) }
+            .ok()
+            .map_err(|e| e.message().to_string())
+    }
+}
+
+#[cfg(windows)]
+impl Drop for RunKey {
+    fn drop(&mut self) {
+        let _ = unsafe { RegCloseKey(self.0) };
+    }
+}
+
+/// Whether a Run entry for the viewer exists, wherever it points.
+#[cfg(windows)]
+pub fn is_enabled() -> bool {
+    RunKey::open(KEY_QUERY_VALUE).is_ok_and(|key| key.read().is_some())
+}
+
+#[cfg(windows)]
+pub fn set_enabled(enabled: bool) -> Result<(), String> {
+    let key = RunKey::open(KEY_QUERY_VALUE | KEY_SET_VALUE)?;
+    if enabled {
+        return key.write(&command_line()?);
+    }
+    match unsafe { RegDeleteValueW(key.0, VALUE_NAME) } {
+        e if e.is_ok() || e == ERROR_FILE_NOT_FOUND => Ok(()),
+        e => Err(e.to_hresult().message().to_string()),
+    }
+}
+
+/// Points an existing Run entry at the current exe, in case it was moved since
+/// the entry was written. Does nothing when there is no entry.
+#[cfg(windows)]
+pub fn refresh() {
+    let Ok(key) = RunKey::open(KEY_QUERY_VALUE | KEY_SET_VALUE) else { return };
+    let (Some(current), Ok(wanted)) = (key.read(), command_line()) else { return };
+    if !current.eq_ignore_ascii_case(&wanted) {
+        log::info!("Updating the startup entry to {}", wanted);
+        let _ = key.write(&wanted);
+    }
+}
+
+#[cfg(not(windows))]
+pub fn is_enabled() -> bool {
+    false
+}
+
+#[cfg(not(windows))]
+pub fn set_enabled(_enabled: bool) -> Result<(), String> {
+    Err("Starting at login is only supported on Windows".to_string())
+}
+
+#[cfg(not(windows))]
+pub fn refresh() {}
@@ -0,0 +1,75 @@
+//! What shows through the transparent parts of an image, so the edges of cutouts
+//! can be judged.
+
+use eframe::egui::{vec2, Color32, Mesh, Painter, Rect, Shape};
+use serde::{Deserialize, Serialize};
+
+use crate::i18n::tr;
+
+#[derive(Serialize, Deserialize, Default, Clone, Copy, PartialEq)]
+pub enum BackdropKind {
+    /// The window's own background.
+    #[default]
+    Panel,
+    Checkerboard,
+    Solid,
+}
+
+impl BackdropKind {
+    pub const ALL: [BackdropKind; 3] = [Self::Panel, Self::Checkerboard, Self::Solid];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Panel => tr("None"),
+            Self::Checkerboard => tr("Checkerboard"),
+            Self::Solid => tr("Solid Color"),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct Backdrop {
+    pub kind: BackdropKind,
+    /// The size of a checkerboard square on screen, in points.
+    pub cell_size: f32,
+    pub color: [u8; 3],
+}
+
+impl Default for Backdrop {
+    fn default() -> Self {
+        Self {
+            kind: BackdropKind::Panel,
+            cell_size: 10.0,
+            color: [255, 255, 255],
+        }
+    }
+}
+
+impl Backdrop {
+    /// Fills `rect`, where the image is about to be drawn. The squares stay the
+    /// same size on screen and in place as the image is zoomed or panned.
+    pub fn paint(&self, painter: &Painter, rect: Rect) {
+        match self.kind {
+            BackdropKind::Panel => {}
+            BackdropKind::Solid => {
+                let [r, g, b] = self.color;
+                painter.rect_filled(rect, 0.0, Color32::from_rgb(r, g, b));
+            }
+            BackdropKind::Checkerboard => {
+                painter.rect_filled(rect, 0.0, Color32::WHITE);
+                let cell = self.cell_size.max(2.0);
+                let (columns, rows) = ((rect.width() / cell).ceil() as usize, (rect.height() / cell).ceil() as usize);
+                let mut mesh = Mesh::default();
+                for row in 0..rows {
+                    for column in (row % 2..columns).step_by(2) {
+                        let min = rect.min + vec2(column as f32, row as f32) * cell;
+                        let square = Rect::from_min_size(min, vec2(cell, cell)).intersect(rect);
+                        mesh.add_colored_rect(square, Color32::from_gray(204));
+                    }
+                }
+                painter.add(Shape::mesh(mesh));
+            }
+        }
+    }
+}
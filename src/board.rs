@@ -0,0 +1,116 @@
+//! Showing two or four images at once for comparative studies, such as a skeleton
+//! beside the surface anatomy over it. The first slot is the viewer's usual image,
+//! with its zoom, grid and timer; the others are dealt from the same deck.
+
+use eframe::egui::{vec2, Rect, TextureHandle, Vec2};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use crate::i18n::tr;
+
+#[derive(Serialize, Deserialize, Default, Clone, Copy, PartialEq)]
+pub enum BoardLayout {
+    #[default]
+    Single,
+    SideBySide,
+    Quad,
+}
+
+impl BoardLayout {
+    pub const ALL: [BoardLayout; 3] = [Self::Single, Self::SideBySide, Self::Quad];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Single => tr("One Image"),
+            Self::SideBySide => tr("Two Side by Side"),
+            Self::Quad => tr("Four in a Grid"),
+        }
+    }
+
+    /// Columns and rows of slots.
+    pub fn grid(self) -> Vec2 {
+        match self {
+            Self::Single => vec2(1.0, 1.0),
+            Self::SideBySide => vec2(2.0, 1.0),
+            Self::Quad => vec2(2.0, 2.0),
+        }
+    }
+
+    pub fn slot_count(self) -> usize {
+        let grid = self.grid();
+        (grid.x * grid.y) as usize
+    }
+
+    /// `rect` split into the slots, left to right then top to bottom, with a
+    /// small gap between them.
+    pub fn cells(self, rect: Rect) -> Vec<Rect> {
+        const GAP: f32 = 4.0;
+        let grid = self.grid();
+        let size = (rect.size() - (grid - Vec2::splat(1.0)) * GAP) / grid;
+        (0..self.slot_count())
+            .map(|i| {
+                let (column, row) = ((i % grid.x as usize) as f32, (i / grid.x as usize) as f32);
+                Rect::from_min_size(rect.min + vec2(column, row) * (size + Vec2::splat(GAP)), size)
+            })
+            .collect()
+    }
+}
+
+/// What the next-image hotkey and shortcut move on.
+#[derive(Serialize, Deserialize, Default, Clone, Copy, PartialEq)]
+pub enum BoardAdvance {
+    /// The slot under the pointer, or every slot if it isn't over one.
+    #[default]
+    UnderPointer,
+    All,
+}
+
+impl BoardAdvance {
+    pub const ALL: [BoardAdvance; 2] = [Self::UnderPointer, Self::All];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::UnderPointer => tr("The Image Under the Pointer"),
+            Self::All => tr("Every Image"),
+        }
+    }
+}
+
+/// How the images on the board are timed.
+#[derive(Serialize, Deserialize, Default, Clone, Copy, PartialEq)]
+pub enum BoardTimer {
+    /// One timer for the board; an alarm that moves on moves every slot on.
+    #[default]
+    Shared,
+    /// The timer follows the first slot, and the others each count their own
+    /// tracked time, moving on by themselves at an alarm that moves on.
+    PerSlot,
+}
+
+impl BoardTimer {
+    pub const ALL: [BoardTimer; 2] = [Self::Shared, Self::PerSlot];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Shared => tr("One Timer for All"),
+            Self::PerSlot => tr("A Timer per Image"),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Default, Clone)]
+#[serde(default)]
+pub struct BoardSettings {
+    pub layout: BoardLayout,
+    pub advance: BoardAdvance,
+    pub timer: BoardTimer,
+}
+
+/// An image shown beside the first one.
+pub struct Slot {
+    /// In pixels, as loaded.
+    pub size: Vec2,
+    pub texture: TextureHandle,
+    /// Tracked time since the image came up, for timing each slot on its own.
+    pub tracked: Duration,
+}
@@ -0,0 +1,24 @@
+//! The command line.
+
+use clap::Parser;
+use std::path::PathBuf;
+
+#[derive(Parser)]
+#[command(name = "GermiBoard", version, about = "Reference image viewer with a drawing timer")]
+pub struct Args {
+    /// Folders to add and an image to show.
+    pub paths: Vec<PathBuf>,
+    /// Start a separate viewer even when one is already running.
+    #[arg(long)]
+    pub new_instance: bool,
+    /// Start hidden in the tray, as when launched at login.
+    #[arg(long)]
+    pub minimized: bool,
+    /// Read and save the settings in this file instead of the usual one. It's
+    /// created on the first save if it doesn't exist.
+    #[arg(long, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+    /// Keep the settings next to the exe.
+    #[arg(long, conflicts_with = "config")]
+    pub portable: bool,
+}
@@ -0,0 +1,101 @@
+//! Showing only part of an image, such as the hands of a full figure, for the
+//! whole pose. The region is dragged out on the image and kept in the image's own
+//! pixels, so it stays put whichever way the image is turned or mirrored and is
+//! remembered for that image the next time it comes up.
+
+use eframe::egui::{self, pos2, Color32, Painter, Pos2, Rect, Stroke};
+use serde::{Deserialize, Serialize};
+
+/// Regions smaller than this many pixels either way are taken for a slip.
+const MIN_SIDE: u32 = 4;
+
+/// A region of an image in its pixels, from the top left as stored on disk.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct Crop {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Crop {
+    /// The region with corners at texture points `a` and `b`, in 0..1, of an
+    /// image `width` by `height` pixels, or `None` if it's too small.
+    pub fn between(a: Pos2, b: Pos2, width: u32, height: u32) -> Option<Self> {
+        let pixel = |t: f32, side: u32| (t.clamp(0.0, 1.0) * side as f32).round() as u32;
+        let (left, right) = (pixel(a.x.min(b.x), width), pixel(a.x.max(b.x), width));
+        let (top, bottom) = (pixel(a.y.min(b.y), height), pixel(a.y.max(b.y), height));
+        (right - left >= MIN_SIDE && bottom - top >= MIN_SIDE).then_some(Self {
+            x: left,
+            y: top,
+            width: right - left,
+            height: bottom - top,
+        })
+    }
+
+    /// The region in texture coordinates, 0..1, of an image `width` by `height`
+    /// pixels, kept within it in case the file has since shrunk.
+    pub fn uv(&self, width: u32, height: u32) -> Option<Rect> {
+        let (width, height) = (width.max(1) as f32, height.max(1) as f32);
+        let min = pos2(self.x as f32 / width, self.y as f32 / height);
+        let max = pos2((self.x + self.width) as f32 / width, (self.y + self.height) as f32 / height);
+        let rect = Rect::from_min_max(min, max).intersect(Rect::from_min_max(Pos2::ZERO, pos2(1.0, 1.0)));
+        rect.is_positive().then_some(rect)
+    }
+}
+
+/// Dragging out a region on screen.
+#[derive(Default)]
+pub struct CropTool {
+    pub active: bool,
+    start: Option<Pos2>,
+}
+
+impl CropTool {
+    /// Follows a drag over the image, returning the rectangle on screen once
+    /// it's let go.
+    pub fn update(&mut self, response: &egui::Response) -> Option<Rect> {
+        if response.drag_started_by(egui::PointerButton::Primary) {
+            self.start = response.interact_pointer_pos();
+        }
+        if response.drag_released() {
+            let start = self.start.take()?;
+            return Some(Rect::from_two_pos(start, response.interact_pointer_pos()?));
+        }
+        None
+    }
+
+    /// Darkens what's left out of the region being dragged out to `pointer`.
+    pub fn paint(&self, painter: &Painter, image_rect: Rect, pointer: Option<Pos2>) {
+        let (Some(start), Some(pointer)) = (self.start, pointer) else {
+            return;
+        };
+        let region = Rect::from_two_pos(start, pointer).intersect(image_rect);
+        let shade = Color32::from_black_alpha(140);
+        let sides = [
+            Rect::from_min_max(image_rect.min, pos2(image_rect.max.x, region.min.y)),
+            Rect::from_min_max(pos2(image_rect.min.x, region.max.y), image_rect.max),
+            Rect::from_min_max(pos2(image_rect.min.x, region.min.y), pos2(region.min.x, region.max.y)),
+            Rect::from_min_max(pos2(region.max.x, region.min.y), pos2(image_rect.max.x, region.max.y)),
+        ];
+        for side in sides.into_iter().filter(|side| side.is_positive()) {
+            painter.rect_filled(side, 0.0, shade);
+        }
+        painter.rect_stroke(region, 0.0, Stroke::new(1.0, Color32::WHITE));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn regions() {
+        let crop = Crop::between(pos2(0.75, 0.5), pos2(0.25, 0.0), 200, 100).unwrap();
+        assert_eq!(crop, Crop { x: 50, y: 0, width: 100, height: 50 });
+        assert_eq!(crop.uv(200, 100), Some(Rect::from_min_max(pos2(0.25, 0.0), pos2(0.75, 0.5))));
+        // Dragged past the edge, and too thin to keep.
+        assert_eq!(Crop::between(pos2(0.9, 0.9), pos2(1.5, 1.2), 200, 100).unwrap().width, 20);
+        assert_eq!(Crop::between(pos2(0.5, 0.5), pos2(0.51, 0.9), 200, 100), None);
+    }
+}
@@ -0,0 +1,114 @@
+//! The shuffled order of the images and how far through it the viewer got, kept
+//! across restarts. It lives in its own file as a large library makes it big.
+
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashSet,
+    fs,
+    io,
+    path::PathBuf,
+};
+
+use crate::{paths, settings_file};
+
+const FILE_NAME: &str = "deck_state.json";
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct DeckState {
+    pub paths: Vec<PathBuf>,
+    /// The index of the image that was showing.
+    pub position: usize,
+}
+
+/// Beside the settings file, named after it if it was given with `--config`, so
+/// two settings files in one folder don't share a deck.
+fn path() -> PathBuf {
+    let config = paths::config_path();
+    match paths::config_origin() {
+        paths::Origin::CommandLine => {
+            let stem = config.file_stem().unwrap_or_default().to_string_lossy();
+            config.with_file_name(format!("{}.{}", stem, FILE_NAME))
+        }
+        _ => config.with_file_name(FILE_NAME),
+    }
+}
+
+pub fn save(paths: &[PathBuf], position: usize) -> io::Result<()> {
+    let state = DeckState {
+        paths: paths.to_vec(),
+        position,
+    };
+    let json = serde_json::to_vec(&state).map_err(io::Error::other)?;
+    settings_file::write(&path(), &json)
+}
+
+/// Deletes the saved deck, which lists every image path.
+pub fn forget() {
+    let _ = fs::remove_file(path());
+}
+
+pub fn load() -> Option<DeckState> {
+    let json = fs::read_to_string(path()).ok()?;
+    serde_json::from_str(&json).ok()
+}
+
+/// Continues the saved order with the images that are still in `current`, which
+/// holds every image of the enabled folders. Images that are new since the deck
+/// was saved are shuffled in after the rest. The position stays on the image that
+/// was showing, or the next one still there if it's gone.
+pub fn reconcile(saved: DeckState, current: Vec<PathBuf>) -> (Vec<PathBuf>, usize) {
+    let mut remaining: HashSet<PathBuf> = current.iter().cloned().collect();
+    let mut position = 0;
+    let mut paths = Vec::with_capacity(current.len());
+    for (i, path) in saved.paths.into_iter().enumerate() {
+        if remaining.remove(&path) {
+            if i < saved.position {
+                position += 1;
+            }
+            paths.push(path);
+        }
+    }
+
+    let mut new_images: Vec<PathBuf> = current.into_iter().filter(|p| remaining.contains(p)).collect();
+    new_images.shuffle(&mut rand::thread_rng());
+    paths.extend(new_images);
+
+    if position >= paths.len() {
+        position = 0;
+    }
+    (paths, position)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paths(names: &[&str]) -> Vec<PathBuf> {
+        names.iter().map(PathBuf::from).collect()
+    }
+
+    #[test]
+    fn reconcile_keeps_order_and_position() {
+        // The image showing was removed: the next one still there takes its place.
+        let saved = DeckState { paths: paths(&["a", "b", "c", "d"]), position: 1 };
+        let (order, position) = reconcile(saved, paths(&["d", "c", "a"]));
+        assert_eq!(order, paths(&["a", "c", "d"]));
+        assert_eq!(order[position], PathBuf::from("c"));
+
+        // New images come after the saved order.
+        let saved = DeckState { paths: paths(&["b", "a"]), position: 1 };
+        let (order, position) = reconcile(saved, paths(&["a", "x", "b", "y"]));
+        assert_eq!(order[..2], paths(&["b", "a"]));
+        let mut new: Vec<PathBuf> = order[2..].to_vec();
+        new.sort();
+        assert_eq!(new, paths(&["x", "y"]));
+        assert_eq!(position, 1);
+
+        // Past the end, it starts over.
+        let saved = DeckState { paths: paths(&["a", "b"]), position: 5 };
+        assert_eq!(reconcile(saved, paths(&["a", "b"])).1, 0);
+        let saved = DeckState { paths: paths(&["a", "b", "c"]), position: 2 };
+        assert_eq!(reconcile(saved, paths(&["a", "b"])).1, 0);
+    }
+}
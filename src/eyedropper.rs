@@ -0,0 +1,163 @@
+//! Picking colors off the reference to mix in a painting app. While picking, a
+//! loupe follows the pointer with the pixels around it blown up, and a click
+//! copies the color's hex code. Colors are read from the image as decoded, found
+//! back through however it's zoomed, turned and mirrored on screen.
+
+use eframe::egui::{self, vec2, Align2, Color32, FontId, Painter, Pos2, Rect, Sense, Stroke};
+use image::{DynamicImage, GenericImageView};
+use std::collections::VecDeque;
+
+use crate::{overlay, zoom::View};
+
+/// How many picks the swatch strip keeps.
+const MAX_PICKS: usize = 8;
+/// Pixels shown either side of the one under the pointer.
+const LOUPE_RADIUS: i32 = 5;
+const LOUPE_CELL: f32 = 10.0;
+const SWATCH_SIZE: f32 = 22.0;
+
+#[derive(Default)]
+pub struct Eyedropper {
+    pub active: bool,
+    /// Newest first.
+    picks: VecDeque<[u8; 3]>,
+}
+
+impl Eyedropper {
+    /// Puts `color` at the front of the swatch strip, taking it out of wherever
+    /// it was already.
+    pub fn remember(&mut self, color: [u8; 3]) {
+        self.picks.retain(|&c| c != color);
+        self.picks.push_front(color);
+        self.picks.truncate(MAX_PICKS);
+    }
+
+    /// The strip of recent picks along the bottom of the window. Returns the one
+    /// clicked, to copy again.
+    pub fn swatches(&self, ctx: &egui::Context) -> Option<[u8; 3]> {
+        if self.picks.is_empty() {
+            return None;
+        }
+        let mut clicked = None;
+        egui::Area::new("swatches").anchor(Align2::CENTER_BOTTOM, [0.0, -10.0]).show(ctx, |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    for &color in &self.picks {
+                        let (rect, response) = ui.allocate_exact_size(vec2(SWATCH_SIZE, SWATCH_SIZE), Sense::click());
+                        let stroke = if response.hovered() { ui.visuals().widgets.hovered.fg_stroke } else { Stroke::new(1.0, Color32::GRAY) };
+                        ui.painter().rect(rect, 3.0, rgb(color), stroke);
+                        if response.on_hover_text(hex(color)).clicked() {
+                            clicked = Some(color);
+                        }
+                    }
+                });
+            });
+        });
+        clicked
+    }
+}
+
+/// `color` as web colors are written, e.g. "#1E90FF".
+pub fn hex([r, g, b]: [u8; 3]) -> String {
+    format!("#{:02X}{:02X}{:02X}", r, g, b)
+}
+
+fn rgb([r, g, b]: [u8; 3]) -> Color32 {
+    Color32::from_rgb(r, g, b)
+}
+
+/// The pixel of `img` at `p` on screen, where `view` of it fills `rect`.
+fn pixel_at(img: &DynamicImage, view: &View, rect: Rect, p: Pos2) -> Option<(u32, u32)> {
+    if !rect.contains(p) {
+        return None;
+    }
+    let t = view.texture_at(rect, p);
+    let x = (t.x * img.width() as f32).floor().clamp(0.0, img.width().saturating_sub(1) as f32);
+    let y = (t.y * img.height() as f32).floor().clamp(0.0, img.height().saturating_sub(1) as f32);
+    Some((x as u32, y as u32))
+}
+
+fn color(img: &DynamicImage, x: u32, y: u32) -> [u8; 3] {
+    let [r, g, b, _] = img.get_pixel(x, y).0;
+    [r, g, b]
+}
+
+/// The color of the image under `p`, if it's over it.
+pub fn color_at(img: &DynamicImage, view: &View, rect: Rect, p: Pos2) -> Option<[u8; 3]> {
+    let (x, y) = pixel_at(img, view, rect, p)?;
+    Some(color(img, x, y))
+}
+
+/// Draws the loupe beside `pointer`: the pixels around the one under it as
+/// they're shown, blown up, with that one's color written below.
+pub fn paint_loupe(painter: &Painter, img: &DynamicImage, view: &View, rect: Rect, pointer: Pos2) {
+    let Some((x, y)) = pixel_at(img, view, rect, pointer) else {
+        return;
+    };
+    let cells = (2 * LOUPE_RADIUS + 1) as f32;
+    let grid_size = vec2(cells, cells) * LOUPE_CELL;
+    let size = grid_size + vec2(0.0, 20.0);
+    // Below right of the pointer, or wherever it fits if that's off screen.
+    let screen = painter.clip_rect();
+    let mut top_left = pointer + vec2(24.0, 24.0);
+    if top_left.x + size.x > screen.right() {
+        top_left.x = pointer.x - 24.0 - size.x;
+    }
+    if top_left.y + size.y > screen.bottom() {
+        top_left.y = pointer.y - 24.0 - size.y;
+    }
+    let loupe = Rect::from_min_size(overlay::clamp_into(top_left, size, screen), size);
+    painter.rect_filled(loupe.expand(3.0), 4.0, Color32::from_black_alpha(200));
+
+    let grid = Rect::from_min_size(loupe.min, grid_size);
+    for row in -LOUPE_RADIUS..=LOUPE_RADIUS {
+        for column in -LOUPE_RADIUS..=LOUPE_RADIUS {
+            let step = view.texture_step(vec2(column as f32, row as f32));
+            let (px, py) = (x as i64 + step.x.round() as i64, y as i64 + step.y.round() as i64);
+            let inside = (0..img.width() as i64).contains(&px) && (0..img.height() as i64).contains(&py);
+            let fill = if inside { rgb(color(img, px as u32, py as u32)) } else { Color32::from_gray(40) };
+            let offset = vec2((column + LOUPE_RADIUS) as f32, (row + LOUPE_RADIUS) as f32) * LOUPE_CELL;
+            painter.rect_filled(Rect::from_min_size(grid.min + offset, vec2(LOUPE_CELL, LOUPE_CELL)), 0.0, fill);
+        }
+    }
+    let middle = Rect::from_center_size(grid.center(), vec2(LOUPE_CELL, LOUPE_CELL));
+    painter.rect_stroke(middle.expand(1.0), 0.0, Stroke::new(1.0, Color32::BLACK));
+    painter.rect_stroke(middle, 0.0, Stroke::new(1.0, Color32::WHITE));
+
+    let picked = color(img, x, y);
+    let [r, g, b] = picked;
+    let text = format!("{}  {} {} {}", hex(picked), r, g, b);
+    painter.text(grid.left_bottom() + vec2(2.0, 10.0), Align2::LEFT_CENTER, text, FontId::monospace(12.0), Color32::WHITE);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use eframe::egui::pos2;
+    use image::{Rgba, RgbaImage};
+
+    #[test]
+    fn samples_through_the_view() {
+        // 2×1: red on the left, blue on the right.
+        let mut img = RgbaImage::from_pixel(2, 1, Rgba([255, 0, 0, 255]));
+        img.put_pixel(1, 0, Rgba([0, 0, 255, 255]));
+        let img = DynamicImage::ImageRgba8(img);
+        let red = [255, 0, 0];
+        let blue = [0, 0, 255];
+
+        let rect = Rect::from_min_size(pos2(0.0, 0.0), vec2(200.0, 100.0));
+        let left = pos2(10.0, 50.0);
+        assert_eq!(color_at(&img, &View::default(), rect, left), Some(red));
+        let flipped = View::default().with_flips((true, false));
+        assert_eq!(color_at(&img, &flipped, rect, left), Some(blue));
+
+        // Turned clockwise, the left pixel is on top.
+        let turned = View::turned(1);
+        let rect = Rect::from_min_size(pos2(0.0, 0.0), vec2(100.0, 200.0));
+        assert_eq!(color_at(&img, &turned, rect, pos2(50.0, 10.0)), Some(red));
+        assert_eq!(color_at(&img, &turned, rect, pos2(50.0, 190.0)), Some(blue));
+        assert_eq!(color_at(&img, &turned, rect, pos2(150.0, 10.0)), None);
+
+        assert_eq!(hex([30, 144, 255]), "#1E90FF");
+    }
+}
@@ -0,0 +1,42 @@
+//! Fading from one image into the next instead of cutting straight to it.
+
+use eframe::egui::{Painter, Rect, TextureHandle};
+use std::time::{Duration, Instant};
+
+use crate::zoom::View;
+
+/// The image being moved on from, drawn as it was while it fades out.
+pub struct Crossfade {
+    /// Kept alive only until the fade is over, then dropped with the fade.
+    texture: TextureHandle,
+    view: View,
+    rect: Rect,
+    started: Instant,
+    duration: Duration,
+}
+
+impl Crossfade {
+    pub fn new(texture: TextureHandle, view: View, rect: Rect, duration: Duration) -> Self {
+        Self {
+            texture,
+            view,
+            rect,
+            started: Instant::now(),
+            duration,
+        }
+    }
+
+    /// How far the fade has got, from 0 to 1.
+    pub fn progress(&self) -> f32 {
+        (self.started.elapsed().as_secs_f32() / self.duration.as_secs_f32()).min(1.0)
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.started.elapsed() >= self.duration
+    }
+
+    /// Draws the last image where it was, fading out as the next fades in.
+    pub fn paint(&self, painter: &Painter) {
+        self.view.paint_faded(painter, self.rect, self.texture.id(), 1.0 - self.progress());
+    }
+}
@@ -0,0 +1,203 @@
+//! Swipes and taps on a touch screen, for moving through images on a tablet
+//! propped up beside paper. Pinching to zoom is left to egui, which turns it into
+//! a zoom factor along with Ctrl+scroll.
+//!
+//! Only touches count, so a pen hovering over the screen does nothing. A hand
+//! resting on the screen touches it in more places than any gesture uses, which
+//! cancels whatever was under way until every finger is lifted.
+
+use eframe::egui::{self, Pos2, TouchId, TouchPhase};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    time::{Duration, Instant},
+};
+
+use crate::{i18n::tr, shortcut::Action};
+
+/// How far a finger has to travel, in points, for a swipe.
+const SWIPE_DISTANCE: f32 = 80.0;
+/// A slower stroke is taken for something else, such as reading with a finger.
+const SWIPE_TIME: Duration = Duration::from_millis(700);
+/// How far fingers may wander during a tap.
+const TAP_SLOP: f32 = 20.0;
+const TAP_TIME: Duration = Duration::from_millis(300);
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Gesture {
+    SwipeLeft,
+    SwipeRight,
+    SwipeUp,
+    SwipeDown,
+    TwoFingerTap,
+}
+
+impl Gesture {
+    pub const ALL: [Gesture; 5] = [Self::SwipeLeft, Self::SwipeRight, Self::SwipeUp, Self::SwipeDown, Self::TwoFingerTap];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::SwipeLeft => tr("Swipe left"),
+            Self::SwipeRight => tr("Swipe right"),
+            Self::SwipeUp => tr("Swipe up"),
+            Self::SwipeDown => tr("Swipe down"),
+            Self::TwoFingerTap => tr("Two-finger tap"),
+        }
+    }
+}
+
+/// Gestures left out do nothing.
+pub fn default_gestures() -> BTreeMap<Gesture, Action> {
+    BTreeMap::from([
+        (Gesture::SwipeLeft, Action::NextImage),
+        (Gesture::SwipeRight, Action::PreviousImage),
+        (Gesture::TwoFingerTap, Action::TogglePause),
+    ])
+}
+
+struct Touch {
+    start: Pos2,
+    pos: Pos2,
+    down: bool,
+}
+
+impl Touch {
+    fn travel(&self) -> egui::Vec2 {
+        self.pos - self.start
+    }
+}
+
+/// Follows the touches from the first finger down to the last one up, and
+/// makes out what gesture they were.
+#[derive(Default)]
+pub struct TouchGestures {
+    touches: BTreeMap<TouchId, Touch>,
+    started: Option<Instant>,
+    /// Too many fingers, a touch that began away from the image, or one the
+    /// system took back: nothing comes of these touches.
+    cancelled: bool,
+}
+
+impl TouchGestures {
+    /// Takes in this frame's touches and returns a gesture once the last finger
+    /// is lifted from one. Touches starting where `on_image` says no are ignored,
+    /// and swipes only count with `swipes`, as the finger pans a zoomed image.
+    pub fn update(&mut self, events: &[egui::Event], swipes: bool, on_image: impl Fn(Pos2) -> bool) -> Option<Gesture> {
+        let mut gesture = None;
+        for event in events {
+            let egui::Event::Touch { id, phase, pos, .. } = *event else {
+                continue;
+            };
+            match phase {
+                TouchPhase::Start => {
+                    if !self.touches.values().any(|t| t.down) {
+                        self.touches.clear();
+                        self.started = Some(Instant::now());
+                        self.cancelled = false;
+                    }
+                    self.cancelled |= !on_image(pos) || self.touches.len() >= 2;
+                    self.touches.insert(id, Touch { start: pos, pos, down: true });
+                }
+                TouchPhase::Move => {
+                    if let Some(touch) = self.touches.get_mut(&id) {
+                        touch.pos = pos;
+                    }
+                }
+                TouchPhase::End | TouchPhase::Cancel => {
+                    self.cancelled |= phase == TouchPhase::Cancel;
+                    if let Some(touch) = self.touches.get_mut(&id) {
+                        touch.pos = pos;
+                        touch.down = false;
+                    }
+                    if !self.touches.values().any(|t| t.down) {
+                        gesture = self.finished(swipes);
+                    }
+                }
+            }
+        }
+        gesture
+    }
+
+    /// Whether a finger is on the screen.
+    pub fn touching(&self) -> bool {
+        self.touches.values().any(|t| t.down)
+    }
+
+    fn finished(&mut self, swipes: bool) -> Option<Gesture> {
+        let took = self.started.take()?.elapsed();
+        if self.cancelled {
+            return None;
+        }
+        let touches: Vec<&Touch> = self.touches.values().collect();
+        match touches.as_slice() {
+            [a, b] if took <= TAP_TIME && a.travel().length() <= TAP_SLOP && b.travel().length() <= TAP_SLOP => Some(Gesture::TwoFingerTap),
+            [touch] if swipes && took <= SWIPE_TIME => {
+                let travel = touch.travel();
+                // Mostly one way, so a diagonal stroke is neither.
+                if travel.x.abs() >= SWIPE_DISTANCE && travel.x.abs() >= 2.0 * travel.y.abs() {
+                    Some(if travel.x < 0.0 { Gesture::SwipeLeft } else { Gesture::SwipeRight })
+                } else if travel.y.abs() >= SWIPE_DISTANCE && travel.y.abs() >= 2.0 * travel.x.abs() {
+                    Some(if travel.y < 0.0 { Gesture::SwipeUp } else { Gesture::SwipeDown })
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use eframe::egui::{pos2, TouchDeviceId};
+
+    fn touch(id: u64, phase: TouchPhase, x: f32, y: f32) -> egui::Event {
+        egui::Event::Touch {
+            device_id: TouchDeviceId(0),
+            id: TouchId(id),
+            phase,
+            pos: pos2(x, y),
+            force: None,
+        }
+    }
+
+    fn run(events: &[egui::Event], swipes: bool) -> Option<Gesture> {
+        TouchGestures::default().update(events, swipes, |_| true)
+    }
+
+    #[test]
+    fn swipes() {
+        let left = [touch(1, TouchPhase::Start, 300.0, 100.0), touch(1, TouchPhase::Move, 200.0, 110.0), touch(1, TouchPhase::End, 150.0, 110.0)];
+        assert_eq!(run(&left, true), Some(Gesture::SwipeLeft));
+        // Panning a zoomed image.
+        assert_eq!(run(&left, false), None);
+
+        let diagonal = [touch(1, TouchPhase::Start, 300.0, 100.0), touch(1, TouchPhase::End, 200.0, 200.0)];
+        assert_eq!(run(&diagonal, true), None);
+    }
+
+    #[test]
+    fn two_finger_tap() {
+        let tap = [
+            touch(1, TouchPhase::Start, 100.0, 100.0),
+            touch(2, TouchPhase::Start, 160.0, 100.0),
+            touch(1, TouchPhase::End, 102.0, 101.0),
+            touch(2, TouchPhase::End, 160.0, 98.0),
+        ];
+        assert_eq!(run(&tap, true), Some(Gesture::TwoFingerTap));
+    }
+
+    #[test]
+    fn palm_cancels() {
+        let palm = [
+            touch(1, TouchPhase::Start, 300.0, 100.0),
+            touch(2, TouchPhase::Start, 320.0, 300.0),
+            touch(3, TouchPhase::Start, 340.0, 310.0),
+            touch(2, TouchPhase::End, 320.0, 300.0),
+            touch(3, TouchPhase::End, 340.0, 310.0),
+            touch(1, TouchPhase::End, 100.0, 100.0),
+        ];
+        assert_eq!(run(&palm, true), None);
+    }
+}
@@ -0,0 +1,109 @@
+//! Lines drawn over the image for transferring it to paper by grid. They're
+//! placed in image pixels, so they stay on the same spots while zooming, panning,
+//! turning and flipping.
+
+use eframe::egui::{pos2, Color32, Painter, Pos2, Rect, Stroke, Vec2};
+use serde::{Deserialize, Serialize};
+
+use crate::{i18n::tr, zoom::View};
+
+/// More lines than this would only make a solid block.
+const MAX_LINES: u32 = 500;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub enum Spacing {
+    /// The image is split into the given number of rows and columns.
+    Divisions,
+    /// Square cells of a fixed number of image pixels.
+    CellSize,
+}
+
+impl Spacing {
+    pub const ALL: [Spacing; 2] = [Self::Divisions, Self::CellSize];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Divisions => tr("Rows and columns"),
+            Self::CellSize => tr("Square cells"),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct GridSettings {
+    pub enabled: bool,
+    pub spacing: Spacing,
+    pub rows: u32,
+    pub columns: u32,
+    /// The side of a cell in image pixels.
+    pub cell_size: u32,
+    pub color: [u8; 3],
+    pub opacity: f32,
+    pub thirds: bool,
+    pub diagonals: bool,
+}
+
+impl Default for GridSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            spacing: Spacing::Divisions,
+            rows: 4,
+            columns: 4,
+            cell_size: 100,
+            color: [255, 255, 255],
+            opacity: 0.6,
+            thirds: false,
+            diagonals: false,
+        }
+    }
+}
+
+impl GridSettings {
+    /// Draws the grid over an image `image_size` pixels large, shown through
+    /// `view` in `rect`.
+    pub fn paint(&self, painter: &Painter, rect: Rect, view: &View, image_size: Vec2) {
+        let painter = painter.with_clip_rect(rect);
+        let [r, g, b] = self.color;
+        let color = Color32::from_rgba_unmultiplied(r, g, b, (self.opacity.clamp(0.0, 1.0) * 255.0) as u8);
+        let line = |from: Pos2, to: Pos2, width: f32| {
+            painter.line_segment([view.screen_point(rect, from), view.screen_point(rect, to)], Stroke::new(width, color));
+        };
+
+        let (xs, ys) = match self.spacing {
+            Spacing::Divisions => (divisions(self.columns), divisions(self.rows)),
+            Spacing::CellSize => (cells(self.cell_size, image_size.x), cells(self.cell_size, image_size.y)),
+        };
+        for x in xs {
+            line(pos2(x, 0.0), pos2(x, 1.0), 1.0);
+        }
+        for y in ys {
+            line(pos2(0.0, y), pos2(1.0, y), 1.0);
+        }
+
+        if self.thirds {
+            for third in [1.0 / 3.0, 2.0 / 3.0] {
+                line(pos2(third, 0.0), pos2(third, 1.0), 2.0);
+                line(pos2(0.0, third), pos2(1.0, third), 2.0);
+            }
+        }
+        if self.diagonals {
+            line(pos2(0.0, 0.0), pos2(1.0, 1.0), 1.0);
+            line(pos2(1.0, 0.0), pos2(0.0, 1.0), 1.0);
+        }
+    }
+}
+
+/// Where the lines go, as fractions of the side, to split it into `count` parts.
+fn divisions(count: u32) -> Vec<f32> {
+    let count = count.clamp(1, MAX_LINES);
+    (1..count).map(|i| i as f32 / count as f32).collect()
+}
+
+/// Where the lines go, as fractions of a side `length` pixels long, for cells of
+/// `size` pixels starting from the top left.
+fn cells(size: u32, length: f32) -> Vec<f32> {
+    let size = (size as f32).max(length / MAX_LINES as f32).max(1.0);
+    (1..).map(|i| i as f32 * size).take_while(|&x| x < length).map(|x| x / length).collect()
+}
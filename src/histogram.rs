@@ -0,0 +1,131 @@
+//! How the image's tones are spread, for checking values during a value study.
+//! It's counted from the image as decoded, so it's the same whichever way the
+//! image is turned or flipped and whether or not it's shown in grayscale.
+
+use eframe::egui::{pos2, vec2, Color32, Painter, Rect, Shape, Stroke};
+use image::{DynamicImage, GenericImageView};
+use serde::{Deserialize, Serialize};
+
+use crate::overlay::Corner;
+
+const BINS: usize = 256;
+/// Large images are sampled down to about this many pixels, which is plenty for
+/// the shape of the curve and keeps counting quick.
+const MAX_SAMPLES: u64 = 1 << 20;
+const WIDTH: f32 = 200.0;
+const HEIGHT: f32 = 80.0;
+/// Below this width there's no telling one tone from another.
+const MIN_WIDTH: f32 = 64.0;
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct HistogramSettings {
+    pub enabled: bool,
+    /// Draws the red, green and blue channels over the luminance.
+    pub channels: bool,
+    pub corner: Corner,
+}
+
+impl Default for HistogramSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            channels: false,
+            corner: Corner::BottomRight,
+        }
+    }
+}
+
+impl HistogramSettings {
+    /// Draws `histogram` in the set corner of `image_rect`, unless the image is
+    /// shown too small for it.
+    pub fn paint(&self, painter: &Painter, image_rect: Rect, histogram: &Histogram) {
+        let width = WIDTH.min(image_rect.width() - 40.0);
+        if width < MIN_WIDTH {
+            return;
+        }
+        let rect = self.corner.place(vec2(width, width * HEIGHT / WIDTH), image_rect);
+        painter.rect_filled(rect.expand(4.0), 4.0, Color32::from_black_alpha(170));
+
+        let tallest = histogram.tallest(self.channels).max(1) as f32;
+        let point = |bin: usize, count: u32| {
+            let x = rect.left() + rect.width() * (bin as f32 + 0.5) / BINS as f32;
+            pos2(x, rect.bottom() - rect.height() * count as f32 / tallest)
+        };
+        let bar = rect.width() / BINS as f32;
+        let fill = Color32::from_gray(200);
+        for (bin, &count) in histogram.luma.iter().enumerate().filter(|(_, &count)| count > 0) {
+            let top = point(bin, count);
+            painter.line_segment([top, pos2(top.x, rect.bottom())], Stroke::new(bar.max(1.0), fill));
+        }
+        if self.channels {
+            let colors = [Color32::from_rgb(255, 80, 80), Color32::from_rgb(80, 220, 80), Color32::from_rgb(90, 140, 255)];
+            for (counts, color) in histogram.channels.iter().zip(colors) {
+                let line = counts.iter().enumerate().map(|(bin, &count)| point(bin, count)).collect();
+                painter.add(Shape::line(line, Stroke::new(1.0, color)));
+            }
+        }
+    }
+}
+
+/// Pixel counts for each of the 256 tones of luminance and of each channel.
+/// Fully transparent pixels aren't counted, as nothing of them shows.
+pub struct Histogram {
+    luma: [u32; BINS],
+    channels: [[u32; BINS]; 3],
+}
+
+impl Histogram {
+    /// Counts `img`'s pixels, skipping evenly through large images.
+    pub fn of(img: &DynamicImage) -> Self {
+        let mut histogram = Self {
+            luma: [0; BINS],
+            channels: [[0; BINS]; 3],
+        };
+        let pixels = img.width() as u64 * img.height() as u64;
+        let step = pixels.div_ceil(MAX_SAMPLES).max(1) as usize;
+        for (_, _, pixel) in img.pixels().step_by(step) {
+            let [r, g, b, a] = pixel.0;
+            if a == 0 {
+                continue;
+            }
+            // Rec. 709, as the grayscale view uses.
+            let luma = (2126 * r as u32 + 7152 * g as u32 + 722 * b as u32) / 10000;
+            histogram.luma[luma as usize] += 1;
+            for (counts, value) in histogram.channels.iter_mut().zip([r, g, b]) {
+                counts[value as usize] += 1;
+            }
+        }
+        histogram
+    }
+
+    /// The largest count drawn, which the curves are scaled to.
+    fn tallest(&self, channels: bool) -> u32 {
+        let mut counts: Vec<&[u32; BINS]> = vec![&self.luma];
+        if channels {
+            counts.extend(&self.channels);
+        }
+        counts.iter().flat_map(|c| c.iter()).copied().max().unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgba, RgbaImage};
+
+    #[test]
+    fn counts_tones() {
+        let mut img = RgbaImage::from_pixel(4, 2, Rgba([255, 255, 255, 255]));
+        img.put_pixel(0, 0, Rgba([0, 0, 0, 255]));
+        img.put_pixel(1, 0, Rgba([255, 0, 0, 255]));
+        img.put_pixel(2, 0, Rgba([10, 10, 10, 0]));
+        let histogram = Histogram::of(&DynamicImage::ImageRgba8(img));
+        assert_eq!(histogram.luma[0], 1);
+        assert_eq!(histogram.luma[54], 1);
+        assert_eq!(histogram.luma[255], 5);
+        assert_eq!(histogram.channels[0][255], 6);
+        assert_eq!(histogram.channels[1][0], 2);
+        assert_eq!(histogram.luma.iter().sum::<u32>(), 7);
+    }
+}
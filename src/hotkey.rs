@@ -0,0 +1,429 @@
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        mpsc::{self, Receiver, Sender},
+        Arc, Mutex,
+    },
+    thread,
+};
+
+use crate::{i18n::tr, snap::Spot};
+
+#[cfg(windows)]
+use windows::Win32::{
+    Foundation::{ERROR_HOTKEY_ALREADY_REGISTERED, HWND, LPARAM, WPARAM},
+    System::Threading::GetCurrentThreadId,
+    UI::Input::KeyboardAndMouse::{
+        RegisterHotKey, UnregisterHotKey, HOT_KEY_MODIFIERS, MOD_ALT, MOD_CONTROL, MOD_NOREPEAT, MOD_SHIFT,
+    },
+    UI::WindowsAndMessaging::{GetMessageW, PeekMessageW, PostThreadMessageW, MSG, PM_NOREMOVE, WM_APP, WM_HOTKEY},
+};
+
+/// Something a global hotkey asks the app to do.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Command {
+    NextImage,
+    TogglePause,
+    DismissAlarm,
+    ToggleClickThrough,
+    ThrowTopLeft,
+    ThrowTop,
+    ThrowTopRight,
+    ThrowLeft,
+    ThrowCenter,
+    ThrowRight,
+    ThrowBottomLeft,
+    ThrowBottom,
+    ThrowBottomRight,
+}
+
+impl Command {
+    pub const ALL: [Command; 13] = [
+        Self::NextImage,
+        Self::TogglePause,
+        Self::DismissAlarm,
+        Self::ToggleClickThrough,
+        Self::ThrowTopLeft,
+        Self::ThrowTop,
+        Self::ThrowTopRight,
+        Self::ThrowLeft,
+        Self::ThrowCenter,
+        Self::ThrowRight,
+        Self::ThrowBottomLeft,
+        Self::ThrowBottom,
+        Self::ThrowBottomRight,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::NextImage => tr("Next image"),
+            Self::TogglePause => tr("Pause / resume timer"),
+            Self::DismissAlarm => tr("Dismiss alarm"),
+            Self::ToggleClickThrough => tr("Toggle click-through"),
+            Self::ThrowTopLeft => tr("Move to top left"),
+            Self::ThrowTop => tr("Move to top"),
+            Self::ThrowTopRight => tr("Move to top right"),
+            Self::ThrowLeft => tr("Move to left"),
+            Self::ThrowCenter => tr("Move to center"),
+            Self::ThrowRight => tr("Move to right"),
+            Self::ThrowBottomLeft => tr("Move to bottom left"),
+            Self::ThrowBottom => tr("Move to bottom"),
+            Self::ThrowBottomRight => tr("Move to bottom right"),
+        }
+    }
+
+    /// Where on the screen the throw commands send the window.
+    pub fn spot(self) -> Option<Spot> {
+        match self {
+            Self::ThrowTopLeft => Some(Spot::TopLeft),
+            Self::ThrowTop => Some(Spot::Top),
+            Self::ThrowTopRight => Some(Spot::TopRight),
+            Self::ThrowLeft => Some(Spot::Left),
+            Self::ThrowCenter => Some(Spot::Center),
+            Self::ThrowRight => Some(Spot::Right),
+            Self::ThrowBottomLeft => Some(Spot::BottomLeft),
+            Self::ThrowBottom => Some(Spot::Bottom),
+            Self::ThrowBottomRight => Some(Spot::BottomRight),
+            _ => None,
+        }
+    }
+
+    #[cfg(windows)]
+    fn id(self) -> i32 {
+        Self::ALL.iter().position(|&c| c == self).unwrap_or_default() as i32 + 1
+    }
+
+    #[cfg(windows)]
+    fn from_id(id: usize) -> Option<Self> {
+        id.checked_sub(1).and_then(|i| Self::ALL.get(i).copied())
+    }
+}
+
+/// A key combination. The key is stored by its egui name ("Right", "P", "F5") so
+/// the config stays readable. egui doesn't tell the number pad apart, so its keys
+/// ("Numpad7") only come from the defaults and only work as global hotkeys.
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub struct Binding {
+    pub ctrl: bool,
+    pub alt: bool,
+    pub shift: bool,
+    pub key: String,
+}
+
+impl Binding {
+    pub fn new(modifiers: egui::Modifiers, key: egui::Key) -> Self {
+        Self {
+            ctrl: modifiers.ctrl,
+            alt: modifiers.alt,
+            shift: modifiers.shift,
+            key: key.name().to_string(),
+        }
+    }
+
+    pub fn label(&self) -> String {
+        let mut parts = Vec::new();
+        if self.ctrl {
+            parts.push("Ctrl");
+        }
+        if self.alt {
+            parts.push("Alt");
+        }
+        if self.shift {
+            parts.push("Shift");
+        }
+        parts.push(&self.key);
+        parts.join("+")
+    }
+
+    /// Whether the combination was pressed this frame, with no other modifiers
+    /// held. Plus and Equals share a key on most layouts, where Plus needs Shift,
+    /// so either one with or without Shift counts as Plus.
+    pub fn pressed(&self, input: &egui::InputState) -> bool {
+        let Some(key) = egui::Key::from_name(&self.key) else {
+            return false;
+        };
+        let modifiers = input.modifiers;
+        let plus = key == egui::Key::Plus;
+        if modifiers.ctrl != self.ctrl || modifiers.alt != self.alt || (modifiers.shift != self.shift && !plus) {
+            return false;
+        }
+        input.key_pressed(key) || (plus && input.key_pressed(egui::Key::Equals))
+    }
+
+    #[cfg(windows)]
+    fn virtual_key(&self) -> Option<u32> {
+        let key = self.key.as_str();
+        let code = match key {
+            "Left" => 0x25,
+            "Up" => 0x26,
+            "Right" => 0x27,
+            "Down" => 0x28,
+            "Space" => 0x20,
+            "PageUp" => 0x21,
+            "PageDown" => 0x22,
+            "End" => 0x23,
+            "Home" => 0x24,
+            "Insert" => 0x2d,
+            "Delete" => 0x2e,
+            "Plus" | "Equals" => 0xbb,
+            "Comma" => 0xbc,
+            "Minus" => 0xbd,
+            "Period" => 0xbe,
+            _ if key.len() == 1 && key.bytes().all(|b| b.is_ascii_uppercase() || b.is_ascii_digit()) => {
+                key.as_bytes()[0] as u32
+            }
+            _ if key.starts_with("Numpad") => match key["Numpad".len()..].parse::<u32>() {
+                Ok(n @ 0..=9) => 0x60 + n,
+                _ => return None,
+            },
+            _ => match key.strip_prefix('F').and_then(|n| n.parse::<u32>().ok()) {
+                Some(n @ 1..=24) => 0x6f + n,
+                _ => return None,
+            },
+        };
+        Some(code)
+    }
+
+    #[cfg(windows)]
+    fn modifiers(&self) -> HOT_KEY_MODIFIERS {
+        let mut modifiers = MOD_NOREPEAT;
+        if self.ctrl {
+            modifiers |= MOD_CONTROL;
+        }
+        if self.alt {
+            modifiers |= MOD_ALT;
+        }
+        if self.shift {
+            modifiers |= MOD_SHIFT;
+        }
+        modifiers
+    }
+}
+
+pub fn default_bindings() -> BTreeMap<Command, Binding> {
+    let ctrl_alt = egui::Modifiers {
+        ctrl: true,
+        alt: true,
+        ..Default::default()
+    };
+    let mut bindings = BTreeMap::from([
+        (Command::NextImage, Binding::new(ctrl_alt, egui::Key::ArrowRight)),
+        (Command::TogglePause, Binding::new(ctrl_alt, egui::Key::P)),
+        (Command::DismissAlarm, Binding::new(ctrl_alt, egui::Key::D)),
+        (Command::ToggleClickThrough, Binding::new(ctrl_alt, egui::Key::C)),
+    ]);
+    // Laid out like the number pad. Ctrl+Alt with the top-row digits is AltGr on
+    // many keyboards, so only the number pad's own keys are used.
+    let numpad = [
+        (Command::ThrowBottomLeft, 1),
+        (Command::ThrowBottom, 2),
+        (Command::ThrowBottomRight, 3),
+        (Command::ThrowLeft, 4),
+        (Command::ThrowCenter, 5),
+        (Command::ThrowRight, 6),
+        (Command::ThrowTopLeft, 7),
+        (Command::ThrowTop, 8),
+        (Command::ThrowTopRight, 9),
+    ];
+    for (command, n) in numpad {
+        let binding = Binding {
+            ctrl: true,
+            alt: true,
+            shift: false,
+            key: format!("Numpad{}", n),
+        };
+        bindings.insert(command, binding);
+    }
+    bindings
+}
+
+#[derive(Default)]
+struct Shared {
+    /// Why each command that isn't registered couldn't be; commands missing here
+    /// are registered.
+    errors: BTreeMap<Command, String>,
+    /// False until the listener has processed a set of bindings.
+    ready: bool,
+    /// Set where there are no global hotkeys at all, until it's been reported.
+    unavailable: bool,
+}
+
+/// Listens for system-wide hotkeys on a dedicated thread, so they work while
+/// another app has focus. Presses are queued until the UI drains them.
+pub struct Hotkeys {
+    rx: Receiver<Command>,
+    rebind_tx: Sender<BTreeMap<Command, Binding>>,
+    /// The listener's thread id, used to wake it up; zero until it has started.
+    thread_id: Arc<AtomicU32>,
+    shared: Arc<Mutex<Shared>>,
+}
+
+impl Hotkeys {
+    pub fn spawn(ctx: egui::Context, bindings: &BTreeMap<Command, Binding>) -> Self {
+        let (tx, rx) = mpsc::channel::<Command>();
+        let (rebind_tx, rebind_rx) = mpsc::channel();
+        let thread_id = Arc::new(AtomicU32::new(0));
+        let shared = Arc::new(Mutex::new(Shared::default()));
+
+        let listener_id = Arc::clone(&thread_id);
+        let listener_shared = Arc::clone(&shared);
+        thread::spawn(move || run(tx, rebind_rx, listener_id, listener_shared, ctx));
+
+        let hotkeys = Self {
+            rx,
+            rebind_tx,
+            thread_id,
+            shared,
+        };
+        hotkeys.rebind(bindings);
+        hotkeys
+    }
+
+    /// Replaces every registration with `bindings`. An empty map suspends the
+    /// hotkeys, e.g. while a new combination is being recorded.
+    pub fn rebind(&self, bindings: &BTreeMap<Command, Binding>) {
+        let _ = self.rebind_tx.send(bindings.clone());
+        wake(self.thread_id.load(Ordering::SeqCst));
+    }
+
+    /// Takes the commands pressed since the last call.
+    pub fn drain(&self) -> Vec<Command> {
+        self.rx.try_iter().collect()
+    }
+
+    /// Why `command` couldn't be registered, if it couldn't.
+    pub fn error(&self, command: Command) -> Option<String> {
+        self.shared.lock().ok()?.errors.get(&command).cloned()
+    }
+
+    /// Whether it turned out there are no global hotkeys here, the first time
+    /// it's asked after that.
+    pub fn take_unavailable(&self) -> bool {
+        self.shared.lock().is_ok_and(|mut shared| std::mem::take(&mut shared.unavailable))
+    }
+
+    /// Whether `command`'s hotkey is currently registered.
+    pub fn is_registered(&self, command: Command) -> bool {
+        self.shared
+            .lock()
+            .is_ok_and(|shared| shared.ready && !shared.errors.contains_key(&command))
+    }
+}
+
+/// Checks a set of bindings for combinations used twice.
+fn duplicates(bindings: &BTreeMap<Command, Binding>) -> BTreeMap<Command, String> {
+    let mut errors = BTreeMap::new();
+    for (command, binding) in bindings {
+        if let Some((other, _)) = bindings.iter().find(|(other, b)| *other < command && *b == binding) {
+            errors.insert(*command, format!("Also used for \"{}\"", other.label()));
+        }
+    }
+    errors
+}
+
+/// Hotkeys registered without a window are posted to the registering thread's
+/// message queue, so registering and listening both happen here.
+#[cfg(windows)]
+fn run(
+    tx: Sender<Command>,
+    rebind_rx: Receiver<BTreeMap<Command, Binding>>,
+    thread_id: Arc<AtomicU32>,
+    shared: Arc<Mutex<Shared>>,
+    ctx: egui::Context,
+) {
+    let mut msg = MSG::default();
+    // Make sure the thread has a message queue before anyone posts to it.
+    unsafe {
+        let _ = PeekMessageW(&mut msg, HWND::default(), 0, 0, PM_NOREMOVE);
+        thread_id.store(GetCurrentThreadId(), Ordering::SeqCst);
+    }
+
+    let mut registered = Vec::new();
+    loop {
+        if let Some(bindings) = rebind_rx.try_iter().last() {
+            for command in registered.drain(..) {
+                let _ = unsafe { UnregisterHotKey(HWND::default(), Command::id(command)) };
+            }
+
+            let mut errors = duplicates(&bindings);
+            for (command, binding) in &bindings {
+                if errors.contains_key(command) {
+                    continue;
+                }
+                let Some(vk) = binding.virtual_key() else {
+                    errors.insert(*command, format!("{} can't be used as a global hotkey", binding.key));
+                    continue;
+                };
+                match unsafe { RegisterHotKey(HWND::default(), command.id(), binding.modifiers(), vk) } {
+                    Ok(()) => registered.push(*command),
+                    Err(e) if e.code() == ERROR_HOTKEY_ALREADY_REGISTERED.to_hresult() => {
+                        errors.insert(*command, "Already used by another app".to_string());
+                    }
+                    Err(e) => {
+                        errors.insert(*command, e.message().to_string());
+                    }
+                }
+            }
+
+            if let Ok(mut shared) = shared.lock() {
+                shared.errors = errors;
+                shared.ready = true;
+            }
+            ctx.request_repaint();
+        }
+
+        if !unsafe { GetMessageW(&mut msg, HWND::default(), 0, 0) }.as_bool() {
+            return;
+        }
+        if msg.message == WM_HOTKEY {
+            if let Some(command) = Command::from_id(msg.wParam.0) {
+                if tx.send(command).is_err() {
+                    return;
+                }
+                ctx.request_repaint();
+            }
+        }
+    }
+}
+
+#[cfg(not(windows))]
+fn run(
+    _tx: Sender<Command>,
+    rebind_rx: Receiver<BTreeMap<Command, Binding>>,
+    _thread_id: Arc<AtomicU32>,
+    shared: Arc<Mutex<Shared>>,
+    ctx: egui::Context,
+) {
+    log::warn!("Global hotkeys unavailable");
+    if let Ok(mut shared) = shared.lock() {
+        shared.unavailable = true;
+    }
+    for bindings in rebind_rx {
+        let mut errors = duplicates(&bindings);
+        for command in bindings.keys() {
+            errors
+                .entry(*command)
+                .or_insert_with(|| "Global hotkeys are only available on Windows".to_string());
+        }
+        if let Ok(mut shared) = shared.lock() {
+            shared.errors = errors;
+            shared.ready = true;
+        }
+        ctx.request_repaint();
+    }
+}
+
+#[cfg(windows)]
+fn wake(thread_id: u32) {
+    // Before the listener has started there's nothing to wake; it checks for new
+    // bindings first thing anyway.
+    if thread_id != 0 {
+        let _ = unsafe { PostThreadMessageW(thread_id, WM_APP, WPARAM(0), LPARAM(0)) };
+    }
+}
+
+#[cfg(not(windows))]
+fn wake(_thread_id: u32) {}
@@ -0,0 +1,205 @@
+//! Translations of what the viewer says. Strings are looked up by their English
+//! text, so anything not translated yet still shows in English. The catalogs are
+//! JSON files built into the program, one per language, mapping the English text
+//! to the translation, or to its plural forms for text with a count in it.
+
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    sync::{OnceLock, RwLock},
+};
+
+#[derive(Serialize, Deserialize, Default, Clone, Copy, PartialEq)]
+pub enum Language {
+    /// Whatever the system is set to, or English if it isn't translated.
+    #[default]
+    System,
+    English,
+    German,
+}
+
+impl Language {
+    pub const ALL: [Language; 3] = [Self::System, Self::English, Self::German];
+
+    /// Each language is named in itself, so it can be found by someone who can't
+    /// read the current one.
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::System => tr("System Language"),
+            Self::English => "English",
+            Self::German => "Deutsch",
+        }
+    }
+
+    /// The language meant by a locale such as "de-AT", if there's a catalog for it.
+    fn from_locale(locale: &str) -> Option<Self> {
+        match locale.split(['-', '_']).next()?.to_ascii_lowercase().as_str() {
+            "en" => Some(Self::English),
+            "de" => Some(Self::German),
+            _ => None,
+        }
+    }
+
+    fn resolve(self) -> Self {
+        match self {
+            Self::System => sys_locale::get_locale().and_then(|l| Self::from_locale(&l)).unwrap_or(Self::English),
+            language => language,
+        }
+    }
+
+    fn catalog(self) -> &'static Catalog {
+        static ENGLISH: OnceLock<Catalog> = OnceLock::new();
+        static GERMAN: OnceLock<Catalog> = OnceLock::new();
+        match self {
+            Self::German => GERMAN.get_or_init(|| Catalog::parse(include_str!("../assets/i18n/de.json"))),
+            _ => ENGLISH.get_or_init(|| Catalog::parse(include_str!("../assets/i18n/en.json"))),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Entry {
+    Text(String),
+    Plural { one: String, other: String },
+}
+
+struct Catalog(HashMap<String, Entry>);
+
+impl Catalog {
+    fn parse(json: &str) -> Self {
+        // The catalogs are built in, so a broken one is caught by the tests.
+        Self(serde_json::from_str(json).unwrap_or_default())
+    }
+}
+
+/// The language strings are shown in, already resolved from `System`.
+static CURRENT: RwLock<Language> = RwLock::new(Language::English);
+
+/// Switches everything shown from the next frame on.
+pub fn set_language(language: Language) {
+    *CURRENT.write().unwrap() = language.resolve();
+}
+
+fn catalog() -> &'static Catalog {
+    CURRENT.read().unwrap().catalog()
+}
+
+/// `text` in the current language.
+pub fn tr(text: &'static str) -> &'static str {
+    match catalog().0.get(text) {
+        Some(Entry::Text(translated)) => translated,
+        _ => text,
+    }
+}
+
+/// `text` in the current language with each `{name}` in it filled in.
+pub fn tr_args(text: &'static str, args: &[(&str, &dyn Display)]) -> String {
+    fill(tr(text), args)
+}
+
+/// `text`, which has a `{count}` in it, in the current language and in the
+/// plural form that goes with `count`.
+pub fn tr_count(text: &'static str, count: usize) -> String {
+    count_in(*CURRENT.read().unwrap(), text, count)
+}
+
+/// `tr_count` in `language`, which is already resolved.
+fn count_in(language: Language, text: &'static str, count: usize) -> String {
+    let plural = |catalog: &'static Catalog| match catalog.0.get(text) {
+        Some(Entry::Plural { one, other }) => Some(if count == 1 { one } else { other }),
+        _ => None,
+    };
+    // English and German both use the singular for one only.
+    let form = plural(language.catalog()).or_else(|| plural(Language::English.catalog())).map_or(text, |s| s.as_str());
+    fill(form, &[("count", &count)])
+}
+
+fn fill(text: &str, args: &[(&str, &dyn Display)]) -> String {
+    let mut text = text.to_string();
+    for (name, value) in args {
+        text = text.replace(&format!("{{{}}}", name), &value.to_string());
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn keys(json: &str) -> Vec<String> {
+        let catalog: HashMap<String, serde_json::Value> = serde_json::from_str(json).unwrap();
+        catalog.into_keys().collect()
+    }
+
+    #[test]
+    fn catalogs_parse() {
+        for json in [include_str!("../assets/i18n/en.json"), include_str!("../assets/i18n/de.json")] {
+            let entries: HashMap<String, Entry> = serde_json::from_str(json).unwrap();
+            assert!(!entries.is_empty());
+        }
+    }
+
+    /// Every Rust file under `src`, read from disk so new modules are covered too.
+    fn sources() -> Vec<(PathBuf, String)> {
+        let mut dirs = vec![PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/src"))];
+        let mut sources = Vec::new();
+        while let Some(dir) = dirs.pop() {
+            for entry in std::fs::read_dir(dir).unwrap() {
+                let path = entry.unwrap().path();
+                if path.is_dir() {
+                    dirs.push(path);
+                } else if path.extension().is_some_and(|e| e == "rs") {
+                    sources.push((path.clone(), std::fs::read_to_string(&path).unwrap()));
+                }
+            }
+        }
+        sources
+    }
+
+    #[test]
+    fn german_covers_every_string() {
+        let german = keys(include_str!("../assets/i18n/de.json"));
+        let used = regex::Regex::new(r#"\btr(?:_args|_count)?\(\s*"([^"]+)""#).unwrap();
+        for (path, source) in sources() {
+            for text in used.captures_iter(&source).map(|c| c[1].to_string()) {
+                assert!(german.contains(&text), "no German for {:?} in {}", text, path.display());
+            }
+        }
+    }
+
+    /// The `label` functions name things for the UI, so each of their strings
+    /// should go through `tr`. Language names are the exception, see `Language::label`.
+    #[test]
+    fn labels_are_translated() {
+        let label = regex::Regex::new(r"(?m)^( *)(?:pub )?fn \w*label\(.*-> (?:Option<)?&'static str>? \{$").unwrap();
+        let bare = regex::Regex::new(r#"(?:=>|Some\()\s*"([^"]*)""#).unwrap();
+        for (path, source) in sources() {
+            for found in label.captures_iter(&source) {
+                let body = &source[found.get(0).unwrap().end()..];
+                let end = body.find(&format!("\n{}}}", &found[1])).unwrap();
+                for text in bare.captures_iter(&body[..end]).map(|c| c[1].to_string()) {
+                    assert!(["English", "Deutsch"].contains(&text.as_str()), "{:?} in {} isn't translated", text, path.display());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn plural_forms() {
+        // Not through `set_language`, which would change the language for tests running alongside.
+        assert_eq!(count_in(Language::German, "{count} images", 1), "1 Bild");
+        assert_eq!(count_in(Language::German, "{count} images", 3), "3 Bilder");
+        assert_eq!(count_in(Language::English, "{count} images", 1), "1 image");
+        assert_eq!(count_in(Language::English, "{count} images", 0), "0 images");
+    }
+
+    #[test]
+    fn locales() {
+        assert!(Language::from_locale("de-AT") == Some(Language::German));
+        assert!(Language::from_locale("en_US") == Some(Language::English));
+        assert!(Language::from_locale("fr-FR").is_none());
+    }
+}
@@ -0,0 +1,132 @@
+//! Details of the image showing, for the Image Info window. They're gathered
+//! while the image is opened, so the file is read only once.
+
+use exif::{In, Tag};
+use image::{ColorType, ImageFormat};
+
+use crate::i18n::tr;
+
+pub struct ImageInfo {
+    pub width: u32,
+    pub height: u32,
+    pub file_size: Option<u64>,
+    pub format: Option<ImageFormat>,
+    pub color: ColorType,
+    /// Capture details from the EXIF data, empty if there is none or it couldn't be read.
+    pub exif: Vec<(&'static str, String)>,
+}
+
+impl ImageInfo {
+    pub fn new(width: u32, height: u32, file_size: Option<u64>, format: Option<ImageFormat>, color: ColorType, raw_exif: Option<Vec<u8>>) -> Self {
+        Self {
+            width,
+            height,
+            file_size,
+            format,
+            color,
+            exif: raw_exif.map(parse_exif).unwrap_or_default(),
+        }
+    }
+
+    /// Every detail as a name and a value, the basic ones first.
+    pub fn rows(&self) -> Vec<(&'static str, String)> {
+        let mut rows = vec![("Dimensions", format!("{} × {} px", self.width, self.height))];
+        if let Some(bytes) = self.file_size {
+            rows.push(("File size", file_size(bytes)));
+        }
+        if let Some(format) = self.format {
+            rows.push(("Format", format!("{:?}", format).to_uppercase()));
+        }
+        rows.push(("Color", color_label(self.color).to_string()));
+        rows.extend(self.exif.iter().cloned());
+        rows
+    }
+
+    /// The details as text, one per line, for the clipboard.
+    pub fn to_text(&self) -> String {
+        self.rows().iter().map(|(name, value)| format!("{}: {}\n", name, value)).collect()
+    }
+}
+
+/// Reads the camera, lens and exposure out of a raw EXIF chunk, skipping what
+/// isn't there.
+fn parse_exif(raw: Vec<u8>) -> Vec<(&'static str, String)> {
+    let exif = match exif::Reader::new().read_raw(raw) {
+        Ok(exif) => exif,
+        Err(e) => {
+            log::warn!("Couldn't read EXIF data: {}", e);
+            return Vec::new();
+        }
+    };
+    let text = |tag: Tag| {
+        let field = exif.get_field(tag, In::PRIMARY)?;
+        match &field.value {
+            exif::Value::Ascii(values) => {
+                let value = String::from_utf8_lossy(values.first()?).trim_matches(|c: char| c == '\0' || c.is_whitespace()).to_string();
+                (!value.is_empty()).then_some(value)
+            }
+            _ => None,
+        }
+    };
+    let with_unit = |tag: Tag| {
+        exif.get_field(tag, In::PRIMARY)
+            .map(|field| field.display_value().with_unit(&exif).to_string())
+    };
+
+    let mut rows = Vec::new();
+    // The model usually starts with the make already.
+    let camera = match (text(Tag::Make), text(Tag::Model)) {
+        (Some(make), Some(model)) if !model.starts_with(&make) => Some(format!("{} {}", make, model)),
+        (_, Some(model)) => Some(model),
+        (make, None) => make,
+    };
+    if let Some(camera) = camera {
+        rows.push(("Camera", camera));
+    }
+    if let Some(lens) = text(Tag::LensModel) {
+        rows.push(("Lens", lens));
+    }
+    if let Some(focal_length) = with_unit(Tag::FocalLength) {
+        rows.push(("Focal length", focal_length));
+    }
+    let exposure: Vec<String> = [
+        with_unit(Tag::ExposureTime),
+        with_unit(Tag::FNumber),
+        with_unit(Tag::PhotographicSensitivity).map(|iso| format!("ISO {}", iso)),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+    if !exposure.is_empty() {
+        rows.push(("Exposure", exposure.join(", ")));
+    }
+    if let Some(taken) = text(Tag::DateTimeOriginal) {
+        let taken = exif::DateTime::from_ascii(taken.as_bytes()).map_or(taken, |date| date.to_string());
+        rows.push(("Date taken", taken));
+    }
+    rows
+}
+
+fn color_label(color: ColorType) -> &'static str {
+    match color {
+        ColorType::L8 => tr("Grayscale, 8-bit"),
+        ColorType::La8 => tr("Grayscale with alpha, 8-bit"),
+        ColorType::Rgb8 => tr("RGB, 8-bit"),
+        ColorType::Rgba8 => tr("RGBA, 8-bit"),
+        ColorType::L16 => tr("Grayscale, 16-bit"),
+        ColorType::La16 => tr("Grayscale with alpha, 16-bit"),
+        ColorType::Rgb16 => tr("RGB, 16-bit"),
+        ColorType::Rgba16 => tr("RGBA, 16-bit"),
+        ColorType::Rgb32F => tr("RGB, 32-bit float"),
+        ColorType::Rgba32F => tr("RGBA, 32-bit float"),
+        _ => tr("Other"),
+    }
+}
+
+pub fn file_size(bytes: u64) -> String {
+    match bytes {
+        0..=1023 => format!("{} B", bytes),
+        1024..=1_048_575 => format!("{:.0} KB", bytes as f64 / 1024.0),
+        _ => format!("{:.1} MB", bytes as f64 / 1_048_576.0),
+    }
+}
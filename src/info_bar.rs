@@ -0,0 +1,121 @@
+//! A bar over the image naming the file, for saying which one is up while going
+//! over drawings with others. It's only painted, so it takes no room from the
+//! image and clicks go through to it.
+
+use eframe::egui::{pos2, vec2, Align2, Color32, FontId, Painter, Rect};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::{i18n::tr, image_info::file_size};
+
+const FONT_SIZE: f32 = 13.0;
+const PADDING: f32 = 6.0;
+/// How long the bar takes to fade out once its time is up, in seconds.
+const FADE_SECONDS: f32 = 0.5;
+
+#[derive(Serialize, Deserialize, Default, Clone, Copy, PartialEq)]
+pub enum Position {
+    Top,
+    #[default]
+    Bottom,
+}
+
+impl Position {
+    pub const ALL: [Position; 2] = [Self::Top, Self::Bottom];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Top => tr("Top"),
+            Self::Bottom => tr("Bottom"),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct InfoBarSettings {
+    pub enabled: bool,
+    pub position: Position,
+    pub auto_hide: bool,
+    pub hide_after_seconds: u32,
+}
+
+impl Default for InfoBarSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            position: Position::Bottom,
+            auto_hide: false,
+            hide_after_seconds: 4,
+        }
+    }
+}
+
+impl InfoBarSettings {
+    /// How strongly the bar shows `shown_for` seconds after it came up, from 1
+    /// down to 0 once it has hidden.
+    pub fn visibility(&self, shown_for: f32) -> f32 {
+        if !self.enabled {
+            return 0.0;
+        }
+        if !self.auto_hide {
+            return 1.0;
+        }
+        let left = self.hide_after_seconds as f32 + FADE_SECONDS - shown_for;
+        (left / FADE_SECONDS).clamp(0.0, 1.0)
+    }
+
+    /// Draws the bar across the top or bottom of `image_rect` for the image at
+    /// `path`, `pixels` wide and high and `bytes` large on disk.
+    pub fn paint(&self, painter: &Painter, image_rect: Rect, path: &Path, pixels: [u32; 2], bytes: Option<u64>, visibility: f32) {
+        let painter = painter.with_clip_rect(image_rect);
+        let font = FontId::proportional(FONT_SIZE);
+        let text_color = Color32::WHITE.gamma_multiply(visibility);
+        let height = painter.layout_no_wrap("Ag".into(), font.clone(), text_color).size().y + 2.0 * PADDING;
+        let bar = match self.position {
+            Position::Top => Rect::from_min_size(image_rect.min, vec2(image_rect.width(), height)),
+            Position::Bottom => Rect::from_min_size(pos2(image_rect.min.x, image_rect.max.y - height), vec2(image_rect.width(), height)),
+        };
+        painter.rect_filled(bar, 0.0, Color32::from_black_alpha((150.0 * visibility) as u8));
+
+        let mut details = format!("{} × {}", pixels[0], pixels[1]);
+        if let Some(bytes) = bytes {
+            details += &format!("   {}", file_size(bytes));
+        }
+        let details = painter.text(bar.right_center() - vec2(PADDING, 0.0), Align2::RIGHT_CENTER, details, font.clone(), text_color);
+
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        let folder = path.parent().map(|p| p.display().to_string()).unwrap_or_default();
+        let room = details.left() - bar.left() - 3.0 * PADDING;
+        let width = |text: &str| painter.layout_no_wrap(text.to_string(), font.clone(), text_color).size().x;
+        let full = format!("{}   {}", name, folder);
+        let mut shown = full.clone();
+        let full_width = width(&full);
+        if full_width > room {
+            // Starts from about the right length, then trims until it fits.
+            let mut keep = (full.chars().count() as f32 * room.max(0.0) / full_width) as usize;
+            shown = middle_truncated(&full, keep);
+            while keep > 0 && width(&shown) > room {
+                keep -= 1;
+                shown = middle_truncated(&full, keep);
+            }
+        }
+        painter.text(bar.left_center() + vec2(PADDING, 0.0), Align2::LEFT_CENTER, shown, font, text_color);
+    }
+}
+
+/// `text` cut to `keep` characters by taking some out of the middle, so both the
+/// start of a long path and the file at its end stay readable.
+fn middle_truncated(text: &str, keep: usize) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    if chars.len() <= keep {
+        return text.to_string();
+    }
+    let keep = keep.saturating_sub(1);
+    let tail = keep / 2;
+    let head = keep - tail;
+    let mut shown: String = chars[..head].iter().collect();
+    shown.push('…');
+    shown.extend(&chars[chars.len() - tail..]);
+    shown
+}
@@ -0,0 +1,168 @@
+//! Keeping to one viewer at a time. A second launch hands its command line to the
+//! running viewer over a named pipe and exits.
+
+use eframe::egui;
+use std::{
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver},
+};
+
+#[cfg(windows)]
+use std::{
+    fs::{File, OpenOptions},
+    io::{Read, Write},
+    os::windows::io::FromRawHandle,
+    thread,
+    time::Duration,
+};
+
+#[cfg(windows)]
+use windows::{
+    core::HSTRING,
+    Win32::Foundation::{CloseHandle, GetLastError, ERROR_ALREADY_EXISTS, ERROR_PIPE_CONNECTED, HANDLE},
+    Win32::Storage::FileSystem::PIPE_ACCESS_INBOUND,
+    Win32::System::Pipes::{
+        ConnectNamedPipe, CreateNamedPipeW, PIPE_READMODE_BYTE, PIPE_TYPE_BYTE, PIPE_UNLIMITED_INSTANCES, PIPE_WAIT,
+    },
+    Win32::System::Threading::CreateMutexW,
+    Win32::UI::WindowsAndMessaging::{AllowSetForegroundWindow, ASFW_ANY},
+};
+
+/// Proof that this is the viewer other launches forward to, held until exit.
+pub struct Lock {
+    #[cfg(windows)]
+    mutex: HANDLE,
+    #[cfg(windows)]
+    pipe: String,
+}
+
+/// Another launch's request: the paths it was given, possibly none, in which case
+/// it only wants the viewer brought forward.
+pub type Forwarded = Vec<PathBuf>;
+
+/// Becomes the running viewer, or returns `None` once `paths` have been handed to
+/// the one that's already running. If that one can't be reached, this launch
+/// carries on as a second viewer rather than doing nothing. Viewers started with
+/// their own settings file, `config`, only hand over to those using the same file.
+#[cfg(windows)]
+pub fn claim(paths: &[PathBuf], config: Option<&Path>) -> Option<Lock> {
+    let scope = config.map(scope_suffix).unwrap_or_default();
+    let name = HSTRING::from(format!("Local\\GermiBoard.SingleInstance{}", scope));
+    let mutex = unsafe { CreateMutexW(None, true, &name) }.ok()?;
+    let running = unsafe { GetLastError() } == ERROR_ALREADY_EXISTS;
+    let pipe = pipe_name(&scope);
+    if !running {
+        return Some(Lock { mutex, pipe });
+    }
+
+    // Relative paths mean nothing in the other process's working directory.
+    let paths: Vec<String> = paths
+        .iter()
+        .map(|p| std::path::absolute(p).unwrap_or_else(|_| p.clone()).to_string_lossy().into_owned())
+        .collect();
+    let message = serde_json::to_vec(&paths).unwrap_or_default();
+
+    // The running viewer may be between two connections, so give it a moment.
+    for _ in 0..10 {
+        if let Ok(mut pipe) = OpenOptions::new().write(true).open(&pipe) {
+            // Lets the running viewer take the focus, which Windows otherwise only
+            // allows the app the user last interacted with.
+            let _ = unsafe { AllowSetForegroundWindow(ASFW_ANY) };
+            if pipe.write_all(&message).is_ok() {
+                let _ = unsafe { CloseHandle(mutex) };
+                return None;
+            }
+        }
+        thread::sleep(Duration::from_millis(200));
+    }
+    log::warn!("Couldn't reach the running viewer, starting another one");
+    Some(Lock { mutex, pipe })
+}
+
+#[cfg(not(windows))]
+pub fn claim(_paths: &[PathBuf], _config: Option<&Path>) -> Option<Lock> {
+    Some(Lock {})
+}
+
+/// Tells apart the names used by viewers with different settings files.
+#[cfg(windows)]
+fn scope_suffix(config: &Path) -> String {
+    use std::hash::{Hash, Hasher};
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    // Paths on Windows don't care about case.
+    config.to_string_lossy().to_lowercase().hash(&mut hasher);
+    format!(".{:016x}", hasher.finish())
+}
+
+#[cfg(windows)]
+fn pipe_name(scope: &str) -> String {
+    // Pipe names are machine-wide, unlike the mutex, so each user gets their own.
+    format!(r"\\.\pipe\GermiBoard-{}{}", std::env::var("USERNAME").unwrap_or_default(), scope)
+}
+
+impl Lock {
+    /// Starts taking requests from later launches.
+    pub fn listen(self, ctx: egui::Context) -> Instance {
+        let (tx, rx) = mpsc::channel();
+
+        #[cfg(windows)]
+        let name = HSTRING::from(self.pipe.as_str());
+        #[cfg(windows)]
+        thread::spawn(move || loop {
+            let pipe = unsafe {
+                CreateNamedPipeW(
+                    &name,
+                    PIPE_ACCESS_INBOUND,
+                    PIPE_TYPE_BYTE | PIPE_READMODE_BYTE | PIPE_WAIT,
+                    PIPE_UNLIMITED_INSTANCES,
+                    0,
+                    4096,
+                    0,
+                    None,
+                )
+            };
+            if pipe.is_invalid() {
+                return;
+            }
+            // A client that connected before this call is reported as an error too.
+            let connected = match unsafe { ConnectNamedPipe(pipe, None) } {
+                Ok(()) => true,
+                Err(e) => e.code() == ERROR_PIPE_CONNECTED.to_hresult(),
+            };
+            // The file takes over the handle and closes it.
+            let mut file = unsafe { File::from_raw_handle(pipe.0 as _) };
+            let mut message = Vec::new();
+            if connected && file.read_to_end(&mut message).is_ok() {
+                let paths: Vec<String> = serde_json::from_slice(&message).unwrap_or_default();
+                if tx.send(paths.into_iter().map(PathBuf::from).collect()).is_err() {
+                    return;
+                }
+                ctx.request_repaint();
+            }
+        });
+        #[cfg(not(windows))]
+        let _ = (tx, ctx);
+
+        Instance { _lock: self, rx }
+    }
+}
+
+#[cfg(windows)]
+impl Drop for Lock {
+    fn drop(&mut self) {
+        let _ = unsafe { CloseHandle(self.mutex) };
+    }
+}
+
+/// The running viewer's end of the forwarding.
+pub struct Instance {
+    _lock: Lock,
+    rx: Receiver<Forwarded>,
+}
+
+impl Instance {
+    /// Takes the requests received since the last call.
+    pub fn drain(&self) -> Vec<Forwarded> {
+        self.rx.try_iter().collect()
+    }
+}
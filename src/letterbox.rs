@@ -0,0 +1,51 @@
+//! A blurred, darkened copy of the image for the bars around it when its shape
+//! doesn't match the window's, the way video players fill them.
+
+use eframe::egui::ColorImage;
+use image::DynamicImage;
+
+/// The long edge of the copy, in pixels. It's stretched far past this, so the
+/// blur hides how few there are.
+const SIZE: u32 = 32;
+const RADIUS: i64 = 2;
+/// Three box blurs in a row come out close to a Gaussian one.
+const PASSES: usize = 3;
+/// How much of its brightness the copy keeps, so the image stands out from it.
+const BRIGHTNESS: f32 = 0.45;
+
+/// Scales `img` down to a few dozen pixels, box-blurs and darkens it. Slow enough
+/// on large images to be worth doing on another thread.
+pub fn blurred(img: &DynamicImage) -> ColorImage {
+    let small = img.thumbnail(SIZE, SIZE).to_rgba8();
+    let (width, height) = (small.width() as usize, small.height() as usize);
+    let mut pixels: Vec<[f32; 4]> = small.pixels().map(|p| p.0.map(f32::from)).collect();
+    for _ in 0..PASSES {
+        pixels = box_pass(&pixels, width, height, true);
+        pixels = box_pass(&pixels, width, height, false);
+    }
+    let rgba: Vec<u8> = pixels
+        .iter()
+        .flat_map(|[r, g, b, _]| [r * BRIGHTNESS, g * BRIGHTNESS, b * BRIGHTNESS, 255.0].map(|c| c.round() as u8))
+        .collect();
+    ColorImage::from_rgba_unmultiplied([width, height], &rgba)
+}
+
+/// Averages each pixel with its neighbors along rows, or along columns if not
+/// `horizontal`, repeating the edge pixels past the edges.
+fn box_pass(pixels: &[[f32; 4]], width: usize, height: usize, horizontal: bool) -> Vec<[f32; 4]> {
+    let (w, h) = (width as i64, height as i64);
+    let mut out = vec![[0.0; 4]; pixels.len()];
+    for y in 0..h {
+        for x in 0..w {
+            let mut sum = [0.0; 4];
+            for d in -RADIUS..=RADIUS {
+                let (sx, sy) = if horizontal { ((x + d).clamp(0, w - 1), y) } else { (x, (y + d).clamp(0, h - 1)) };
+                for (total, c) in sum.iter_mut().zip(pixels[(sy * w + sx) as usize]) {
+                    *total += c;
+                }
+            }
+            out[(y * w + x) as usize] = sum.map(|c| c / (2 * RADIUS + 1) as f32);
+        }
+    }
+    out
+}
@@ -0,0 +1,34 @@
+//! Writes the viewer's `log` messages to stderr with the time and level, for
+//! running it from a terminal. `GERMIBOARD_LOG=debug` shows more, `warn` less.
+
+use log::{LevelFilter, Log, Metadata, Record};
+
+struct StderrLogger;
+
+impl Log for StderrLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level() && metadata.target().starts_with(env!("CARGO_CRATE_NAME"))
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            eprintln!("{} {:<5} {}", chrono::Local::now().format("%H:%M:%S"), record.level(), record.args());
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+pub fn init() {
+    static LOGGER: StderrLogger = StderrLogger;
+    let level = match std::env::var("GERMIBOARD_LOG").as_deref() {
+        Ok("debug") => LevelFilter::Debug,
+        Ok("warn") => LevelFilter::Warn,
+        Ok("error") => LevelFilter::Error,
+        Ok("off") => LevelFilter::Off,
+        _ => LevelFilter::Info,
+    };
+    if log::set_logger(&LOGGER).is_ok() {
+        log::set_max_level(level);
+    }
+}
@@ -1,36 +1,760 @@
 #![windows_subsystem = "windows"]
 
+mod audio;
+mod autostart;
+mod backdrop;
+mod board;
+mod cli;
+mod crop;
+mod deck;
+mod eyedropper;
+mod fade;
+mod gesture;
+mod grid;
+mod histogram;
+mod hotkey;
+mod i18n;
+mod image_info;
+mod info_bar;
+mod instance;
+mod letterbox;
+mod logging;
+mod measure;
+mod notices;
+mod opacity;
+mod overlay;
+mod paths;
+mod placement;
+mod platform;
+mod pointer;
+mod posterize;
+mod probe;
+mod profile;
+mod ring;
+mod second_view;
+mod session;
+mod settings;
+mod settings_file;
+mod shortcut;
+mod snap;
+mod speech;
+mod stats;
+mod stats_db;
+mod theme;
+mod toast;
+mod tracker;
+mod thumbs;
+mod tray;
+mod zoom;
+
 use eframe::{egui, App};
-use image::DynamicImage;
+use image::{DynamicImage, ImageDecoder};
 use rfd::FileDialog;
 use std::{
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, HashMap, HashSet},
     fs,
     path::{Path, PathBuf},
+    sync::mpsc,
+    thread,
     time::{Duration, Instant},
 };
 
-use windows::{
-    Win32::UI::WindowsAndMessaging::{
-        GetForegroundWindow, GetWindowThreadProcessId,
-        WindowFromPoint, GetCursorPos,
-    },
-    Win32::System::Threading::{OpenProcess, PROCESS_QUERY_INFORMATION, PROCESS_VM_READ},
-    Win32::System::ProcessStatus::K32GetModuleBaseNameW,
-    Win32::Foundation::{HWND, POINT, CloseHandle},
-};
-
 use serde::{Deserialize, Serialize};
+use gesture::Gesture;
+use hotkey::{Binding, Command};
+use i18n::{tr, tr_args, tr_count, Language};
+use placement::WindowPlacement;
+use profile::{AutoSwitch, Profile};
+use platform::{power::Wake, taskbar::Progress};
 use rand::seq::SliceRandom;
+use session::ImageView;
+use board::{BoardAdvance, BoardLayout, BoardTimer};
+use notices::Level;
+use settings::Settings;
+use shortcut::Action;
+use snap::Spot;
+use zoom::FitMode;
+use stats::{format_hours, StatsStore};
+use tracker::{DocumentPattern, TitleFilter, TrackConfig, TrackState, Tracker};
+
+#[derive(Serialize, Deserialize, Default, Clone, Copy, PartialEq)]
+enum TimerDisplayMode {
+    #[default]
+    Elapsed,
+    Remaining,
+    Both,
+}
+
+impl TimerDisplayMode {
+    const ALL: [TimerDisplayMode; 3] = [Self::Elapsed, Self::Remaining, Self::Both];
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Elapsed => tr("Elapsed"),
+            Self::Remaining => tr("Remaining"),
+            Self::Both => tr("Both"),
+        }
+    }
+
+    fn next(self) -> Self {
+        match self {
+            Self::Elapsed => Self::Remaining,
+            Self::Remaining => Self::Both,
+            Self::Both => Self::Elapsed,
+        }
+    }
+}
+
+/// Whether the window follows each image's shape or the image fits the window.
+#[derive(Serialize, Deserialize, Default, Clone, Copy, PartialEq)]
+enum ImageFit {
+    /// Resizes the window around each image.
+    ResizeWindow,
+    /// Leaves the window as the user sized it, with bars beside the image.
+    #[default]
+    FitInWindow,
+}
+
+impl ImageFit {
+    const ALL: [ImageFit; 2] = [Self::FitInWindow, Self::ResizeWindow];
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::ResizeWindow => tr("Resize Window to Image"),
+            Self::FitInWindow => tr("Fit Image in Window"),
+        }
+    }
+}
+
+/// What to do with the images found by a scan of the folders.
+#[derive(Clone, Copy, PartialEq)]
+enum ScanThen {
+    /// Only the counts for the Folder Manager were wanted.
+    CountOnly,
+    /// The new deck takes over at the next advance.
+    Replace,
+    /// The new deck's first image replaces the current one.
+    ReplaceAndShow,
+}
+
+/// Folders and whether each is enabled, in the order the user put them. Saved
+/// as a map, so configs from before the order was kept still load.
+pub type FolderMap = indexmap::IndexMap<PathBuf, bool>;
+
+/// What the last scan found in one folder.
+struct FolderScan {
+    images: usize,
+    /// False when the folder couldn't be found.
+    reachable: bool,
+    scanned_at: chrono::DateTime<chrono::Local>,
+}
+
+/// The images a scan collected and what it found in each folder.
+type ScanResult = (Vec<PathBuf>, HashMap<PathBuf, FolderScan>);
+
+/// How the timer shows while the image fills the screen.
+#[derive(Serialize, Deserialize, Default, Clone, Copy, PartialEq)]
+enum FullscreenTimer {
+    #[default]
+    Normal,
+    /// Readable from the back of a classroom.
+    Large,
+    Hidden,
+}
+
+impl FullscreenTimer {
+    const ALL: [FullscreenTimer; 3] = [Self::Normal, Self::Large, Self::Hidden];
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Normal => tr("Normal"),
+            Self::Large => tr("Large"),
+            Self::Hidden => tr("Hidden"),
+        }
+    }
+}
+
+/// What else happens when an alarm fires, besides the sound.
+#[derive(Serialize, Deserialize, Default, Clone, Copy, PartialEq)]
+enum AlarmAction {
+    #[default]
+    None,
+    NextImage,
+    PauseTimer,
+    NextImageAndPause,
+}
+
+impl AlarmAction {
+    const ALL: [AlarmAction; 4] = [
+        AlarmAction::None,
+        AlarmAction::NextImage,
+        AlarmAction::PauseTimer,
+        AlarmAction::NextImageAndPause,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            AlarmAction::None => tr("Nothing else"),
+            AlarmAction::NextImage => tr("Next image"),
+            AlarmAction::PauseTimer => tr("Pause timer"),
+            AlarmAction::NextImageAndPause => tr("Next image + pause"),
+        }
+    }
+
+    fn advances(self) -> bool {
+        matches!(self, AlarmAction::NextImage | AlarmAction::NextImageAndPause)
+    }
+
+    fn pauses(self) -> bool {
+        matches!(self, AlarmAction::PauseTimer | AlarmAction::NextImageAndPause)
+    }
+}
+
+/// A wall-clock time of day an alarm fires at, on the weekdays in the mask.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+struct Schedule {
+    time: chrono::NaiveTime,
+    /// Bit 0 is Monday, bit 6 Sunday.
+    weekdays: u8,
+}
+
+impl Schedule {
+    const WEEKDAYS: [&'static str; 7] = ["Mo", "Tu", "We", "Th", "Fr", "Sa", "Su"];
+
+    fn new() -> Self {
+        Self {
+            time: chrono::NaiveTime::from_hms_opt(17, 30, 0).unwrap_or_default(),
+            weekdays: 0x7f,
+        }
+    }
+
+    /// Whether today's occurrence falls after `from` and no later than `to`.
+    fn due_between(&self, from: chrono::DateTime<chrono::Local>, to: chrono::DateTime<chrono::Local>) -> bool {
+        use chrono::Datelike;
+        let day = to.date_naive();
+        let at = day.and_time(self.time);
+        self.weekdays & (1 << day.weekday().num_days_from_monday()) != 0
+            && from.naive_local() < at
+            && at <= to.naive_local()
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct Alarm {
+    label: String,
+    seconds: u64,
+    sound_path: Option<PathBuf>,
+    enabled: bool,
+    #[serde(default)]
+    on_trigger: AlarmAction,
+    /// Fire again every `seconds` of tracked time instead of only once.
+    #[serde(default)]
+    repeating: bool,
+    /// Fire at a time of day instead of after `seconds` of tracked time.
+    #[serde(default)]
+    schedule: Option<Schedule>,
+    #[serde(skip)]
+    triggered: bool,
+    #[serde(skip)]
+    pre_triggered: bool,
+    /// How many times a repeating alarm has fired since it was last armed.
+    #[serde(skip)]
+    repetitions: u64,
+    /// The day a scheduled alarm last fired, so it goes off at most once a day.
+    #[serde(skip)]
+    fired_on: Option<chrono::NaiveDate>,
+}
+
+impl Alarm {
+    fn new(label: String, seconds: u64, sound_path: Option<PathBuf>) -> Self {
+        Self {
+            label,
+            seconds,
+            sound_path,
+            enabled: true,
+            on_trigger: AlarmAction::None,
+            repeating: false,
+            schedule: None,
+            triggered: false,
+            pre_triggered: false,
+            repetitions: 0,
+            fired_on: None,
+        }
+    }
+
+    /// Whether this is an enabled alarm that goes by tracked time.
+    fn counts_tracked_time(&self) -> bool {
+        self.enabled && self.schedule.is_none()
+    }
+
+    /// The tracked time at which the alarm next fires.
+    fn duration(&self) -> Duration {
+        Duration::from_secs(self.seconds * (self.repetitions + 1))
+    }
+
+    /// Marks the alarm as already fired if `elapsed` is past it, without any sound.
+    fn catch_up(&mut self, elapsed: Duration) {
+        if self.schedule.is_none() && elapsed >= self.duration() {
+            self.fire(elapsed);
+        }
+    }
+
+    /// Whether a tracked-time alarm has come due at `elapsed`, recording that it
+    /// fired if so.
+    fn due(&mut self, elapsed: Duration) -> bool {
+        let due = !self.triggered && elapsed >= self.duration();
+        if due {
+            self.fire(elapsed);
+        }
+        due
+    }
+
+    /// Records that the alarm fired at `elapsed`. A repeating alarm moves on to the
+    /// first deadline still ahead, so a long gap between updates fires it only once.
+    fn fire(&mut self, elapsed: Duration) {
+        if self.repeating {
+            self.repetitions = elapsed.as_secs() / self.seconds.max(1);
+            self.pre_triggered = false;
+        } else {
+            self.triggered = true;
+        }
+    }
+}
+
+/// The timer as of the last save, so a restart on the same day can pick it up again.
+#[derive(Serialize, Deserialize, Clone, Copy)]
+struct SavedElapsed {
+    seconds: f64,
+    saved_at: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+struct PomodoroSettings {
+    enabled: bool,
+    work_minutes: u64,
+    short_break_minutes: u64,
+    long_break_minutes: u64,
+    cycles_before_long_break: u32,
+    sound_path: Option<PathBuf>,
+}
+
+impl Default for PomodoroSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            work_minutes: 25,
+            short_break_minutes: 5,
+            long_break_minutes: 15,
+            cycles_before_long_break: 4,
+            sound_path: None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum PomodoroPhase {
+    Work,
+    ShortBreak,
+    LongBreak,
+}
+
+impl PomodoroPhase {
+    fn label(self) -> &'static str {
+        match self {
+            Self::Work => tr("Work"),
+            Self::ShortBreak => tr("Break"),
+            Self::LongBreak => tr("Long break"),
+        }
+    }
+}
+
+/// A completed work phase, kept for the statistics.
+#[derive(Serialize, Deserialize, Clone)]
+struct PomodoroRecord {
+    completed_at: u64,
+    work_seconds: u64,
+}
+
+struct PomodoroState {
+    phase: PomodoroPhase,
+    work_elapsed: Duration,
+    break_started: Instant,
+    completed_cycles: u32,
+    log: Vec<PomodoroRecord>,
+}
+
+impl PomodoroState {
+    fn new() -> Self {
+        Self {
+            phase: PomodoroPhase::Work,
+            work_elapsed: Duration::ZERO,
+            break_started: Instant::now(),
+            completed_cycles: 0,
+            log: Vec::new(),
+        }
+    }
+
+    fn reset(&mut self) {
+        self.phase = PomodoroPhase::Work;
+        self.work_elapsed = Duration::ZERO;
+        self.completed_cycles = 0;
+    }
+
+    /// Advances the current phase and returns true when it flipped to the next one.
+    /// Work only counts tracked time, breaks run on the wall clock.
+    fn tick(&mut self, settings: &PomodoroSettings, tracked_delta: Duration) -> bool {
+        match self.phase {
+            PomodoroPhase::Work => {
+                self.work_elapsed += tracked_delta;
+                let work = Duration::from_secs(settings.work_minutes * 60);
+                if self.work_elapsed < work {
+                    return false;
+                }
+
+                self.completed_cycles += 1;
+                self.log.push(PomodoroRecord {
+                    completed_at: unix_now(),
+                    work_seconds: self.work_elapsed.as_secs(),
+                });
+                self.work_elapsed = Duration::ZERO;
+                self.break_started = Instant::now();
+                self.phase = if self.completed_cycles.is_multiple_of(settings.cycles_before_long_break.max(1)) {
+                    PomodoroPhase::LongBreak
+                } else {
+                    PomodoroPhase::ShortBreak
+                };
+                true
+            }
+            PomodoroPhase::ShortBreak | PomodoroPhase::LongBreak => {
+                if self.break_started.elapsed() < self.phase_length(settings) {
+                    return false;
+                }
+                self.phase = PomodoroPhase::Work;
+                true
+            }
+        }
+    }
+
+    fn phase_length(&self, settings: &PomodoroSettings) -> Duration {
+        let minutes = match self.phase {
+            PomodoroPhase::Work => settings.work_minutes,
+            PomodoroPhase::ShortBreak => settings.short_break_minutes,
+            PomodoroPhase::LongBreak => settings.long_break_minutes,
+        };
+        Duration::from_secs(minutes * 60)
+    }
+
+    fn remaining(&self, settings: &PomodoroSettings) -> Duration {
+        let spent = match self.phase {
+            PomodoroPhase::Work => self.work_elapsed,
+            _ => self.break_started.elapsed(),
+        };
+        self.phase_length(settings).saturating_sub(spent)
+    }
+}
+
+/// What the statistics chart plots per day.
+#[derive(Clone, Copy, PartialEq)]
+enum ChartMetric {
+    TrackedTime,
+    ImagesViewed,
+}
+
+#[derive(Serialize, Deserialize, Default, Clone, Copy, PartialEq)]
+enum TrackingMode {
+    #[default]
+    Focused,
+    Hovered,
+    FocusedOrHovered,
+}
+
+impl TrackingMode {
+    const ALL: [TrackingMode; 3] = [Self::Focused, Self::Hovered, Self::FocusedOrHovered];
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Focused => tr("Focused"),
+            Self::Hovered => tr("Hovered"),
+            Self::FocusedOrHovered => tr("Focused or hovered"),
+        }
+    }
+}
+
+/// What the viewer does while the cursor is over the target app.
+#[derive(Serialize, Deserialize, Default, Clone, Copy, PartialEq)]
+enum HoverBehavior {
+    #[default]
+    Nothing,
+    Fade,
+    Hide,
+}
+
+impl HoverBehavior {
+    const ALL: [HoverBehavior; 3] = [Self::Nothing, Self::Fade, Self::Hide];
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Nothing => tr("Stay"),
+            Self::Fade => tr("Fade"),
+            Self::Hide => tr("Hide"),
+        }
+    }
+}
 
+/// When the viewer stays above other windows.
+#[derive(Serialize, Deserialize, Default, Clone, Copy, PartialEq)]
+enum PinMode {
+    #[default]
+    Unpinned,
+    Pinned,
+    /// Only while the target app has focus, so it drops back as soon as you switch away.
+    WhileTargetActive,
+}
+
+impl PinMode {
+    const ALL: [PinMode; 3] = [Self::Unpinned, Self::Pinned, Self::WhileTargetActive];
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Unpinned => tr("Off"),
+            Self::Pinned => tr("Always"),
+            Self::WhileTargetActive => tr("While Target App Is Active"),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+struct SpeechSettings {
+    enabled: bool,
+    pre_alarm_phrase: String,
+    alarm_phrase: String,
+    segment_phrase: String,
+}
+
+impl Default for SpeechSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            pre_alarm_phrase: "{seconds} seconds left".to_string(),
+            alarm_phrase: "Time".to_string(),
+            segment_phrase: "{phase}".to_string(),
+        }
+    }
+}
+
+/// The areas "Reset Settings" puts back to how a fresh install has them.
+#[derive(Default, Clone, Copy)]
+struct ResetScope {
+    folders: bool,
+    alarms: bool,
+    hotkeys: bool,
+    window_layout: bool,
+    statistics: bool,
+}
+
+impl ResetScope {
+    fn any(&self) -> bool {
+        self.folders || self.alarms || self.hotkeys || self.window_layout || self.statistics
+    }
+}
+
+/// The next image in the deck, decoded ahead for the second window. Moving on to
+/// it takes it over instead of decoding it again.
+struct Upcoming {
+    path: PathBuf,
+    /// `None` if it couldn't be opened, so it isn't tried again every frame.
+    loaded: Option<(DynamicImage, image_info::ImageInfo, egui::TextureHandle)>,
+    /// How the texture was uploaded to be sampled.
+    options: egui::TextureOptions,
+}
+
+/// Preview playback and decode results shared by every sound picker.
+#[derive(Default)]
+struct SoundChecks {
+    preview: audio::Preview,
+    preview_id: Option<egui::Id>,
+    results: HashMap<PathBuf, Result<(), String>>,
+}
+
+impl SoundChecks {
+    /// A "choose file" row with the file name and a preview toggle, followed by the
+    /// decode error if the file can't be played. Pickers with `uses_default` fall back
+    /// to the bundled chime when no file is set. `id_source` tells the previews apart,
+    /// as the label changes with the language. Returns true if the selection changed.
+    fn picker(
+        &mut self,
+        ui: &mut egui::Ui,
+        id_source: &str,
+        label: &str,
+        path: &mut Option<PathBuf>,
+        volume: f32,
+        uses_default: bool,
+    ) -> bool {
+        let mut changed = false;
+        let id = ui.id().with(id_source);
+
+        ui.horizontal(|ui| {
+            if ui.button(label).clicked() {
+                if let Some(picked) = FileDialog::new().add_filter("Audio", &["mp3", "wav", "ogg", "mp4"]).pick_file() {
+                    self.results.insert(picked.clone(), audio::check(&picked));
+                    *path = Some(picked);
+                    changed = true;
+                }
+            }
+
+            match path.as_ref() {
+                Some(current) => ui.label(current.file_name().unwrap_or_default().to_string_lossy()),
+                None if uses_default => ui.label(tr("Default chime")),
+                None => return,
+            };
+
+            if self.preview.is_playing() && self.preview_id == Some(id) {
+                if ui.small_button(tr("Stop")).clicked() {
+                    self.preview.stop();
+                }
+                ui.ctx().request_repaint_after(Duration::from_millis(100));
+            } else if ui.small_button(tr("Preview")).clicked() {
+                self.preview.start(path.clone(), volume);
+                self.preview_id = Some(id);
+            }
+
+            if uses_default && path.is_some() && ui.small_button(tr("Use Default")).clicked() {
+                *path = None;
+                changed = true;
+            }
+        });
+
+        if let Some(current) = path.as_ref() {
+            let result = self.results.entry(current.clone()).or_insert_with(|| audio::check(current));
+            if let Err(e) = result {
+                ui.colored_label(ui.visuals().error_fg_color, e.as_str());
+            }
+        }
+
+        changed
+    }
+}
+
+/// Bumped whenever `ConfigData::migrate` learns a new upgrade step.
+const CONFIG_VERSION: u32 = 3;
+
+const MAX_RECENT_FOLDERS: usize = 15;
+
+/// Any fainter and the viewer is hard to find again.
+const MIN_WINDOW_OPACITY_PERCENT: u8 = 30;
+/// However small the window is set to get, it doesn't go below this.
+const MIN_WINDOW_SIDE: u32 = 120;
+/// Below this the timer shrinks with the window, until it's too small to read
+/// and is left out.
+const SMALL_WINDOW_SIDE: f32 = 300.0;
+const MIN_TIMER_FONT_SIZE: f32 = 10.0;
+const MIN_UI_SCALE_PERCENT: u16 = 75;
+const MAX_UI_SCALE_PERCENT: u16 = 200;
+/// How much Ctrl+Plus and Ctrl+Minus change the UI scale by.
+const UI_SCALE_STEP: u16 = 10;
+
+/// How often the image index and the running timer are written to the config.
+const CONFIG_SAVE_INTERVAL: Duration = Duration::from_secs(30);
+
+const DEFAULT_PROFILE: &str = "Default";
+
+/// How long a notice stays in the corner of the window.
+const NOTICE_DURATION: Duration = Duration::from_secs(6);
+
+/// How long before an alarm a hidden HUD comes back, if asked to.
+const HUD_REVEAL: Duration = Duration::from_secs(10);
+
+/// Every field has a default, so a config written before a field existed still loads.
 #[derive(Serialize, Deserialize, Default)]
 struct ConfigData {
-    folder_map: HashMap<PathBuf, bool>,
+    /// Missing in configs from before versioning, which count as version 0.
+    #[serde(default)]
+    version: u32,
+    #[serde(default)]
+    folder_map: FolderMap,
+    #[serde(default)]
+    recent_folders: Vec<PathBuf>,
+    /// Clockwise quarter turns, for images that aren't upright.
+    #[serde(default)]
+    image_rotations: HashMap<PathBuf, u8>,
+    /// The part of each image shown, for images cropped to a region.
+    #[serde(default)]
+    image_crops: HashMap<PathBuf, crop::Crop>,
+    #[serde(default)]
     target_exe_name: Option<String>,
+    #[serde(default = "hotkey::default_bindings")]
+    hotkey_bindings: BTreeMap<Command, Binding>,
+    /// Keys for the viewer's own actions, which work while it has focus.
+    #[serde(default = "shortcut::default_shortcuts")]
+    shortcuts: BTreeMap<Action, Binding>,
+    /// What touch gestures do. Gestures left out do nothing.
+    #[serde(default = "gesture::default_gestures")]
+    gestures: BTreeMap<Gesture, Action>,
+    #[serde(default)]
+    window_placement: Option<WindowPlacement>,
+    #[serde(default)]
     current_index: usize,
+    // Replaced by `pin_mode`, read only to migrate older configs.
+    #[serde(default, skip_serializing)]
     is_pinned: bool,
+    #[serde(default)]
+    pin_mode: Option<PinMode>,
+    // Single-alarm fields from older configs, folded into `alarms` on load.
+    #[serde(default, skip_serializing)]
     alarm_seconds: Option<u64>,
+    #[serde(default, skip_serializing)]
     alarm_sound_path: Option<PathBuf>,
+    #[serde(default)]
+    alarms: Vec<Alarm>,
+    #[serde(default)]
+    saved_elapsed: Option<SavedElapsed>,
+    #[serde(default)]
+    profiles: Vec<Profile>,
+    #[serde(default)]
+    active_profile: Option<String>,
+    /// Options set in the Settings window.
+    #[serde(default)]
+    settings: Settings,
+    /// Settings from a newer version of the app, written back untouched.
+    #[serde(flatten)]
+    unknown: serde_json::Map<String, serde_json::Value>,
+}
+
+impl ConfigData {
+    /// Upgrades a config written by an older version to the current layout.
+    fn migrate(mut self) -> Self {
+        if self.version < 1 {
+            // Version 0 had a single alarm and an on/off pin.
+            if self.alarms.is_empty() {
+                if let Some(seconds) = self.alarm_seconds.take() {
+                    self.alarms.push(Alarm::new("Alarm".to_string(), seconds, self.alarm_sound_path.take()));
+                }
+            }
+            if self.pin_mode.is_none() {
+                self.pin_mode = Some(if self.is_pinned { PinMode::Pinned } else { PinMode::Unpinned });
+            }
+        }
+        if self.version < 2 && self.profiles.is_empty() {
+            // Version 1 could run without a profile; what it had becomes the first one.
+            self.profiles.push(Profile {
+                name: DEFAULT_PROFILE.to_string(),
+                target_exe_name: self.target_exe_name.clone(),
+                folder_map: self.folder_map.clone(),
+                alarms: self.alarms.clone(),
+            });
+            self.active_profile = Some(DEFAULT_PROFILE.to_string());
+        }
+        if self.version < 3 {
+            // Version 2 kept the options at the top level, where they were read as unknown.
+            self.settings = Settings::from_flat(&mut self.unknown);
+        }
+        // A config from a newer version keeps its number, so that version doesn't
+        // run its upgrades over it a second time.
+        self.version = self.version.max(CONFIG_VERSION);
+        self
+    }
 }
 
 struct ImageViewerApp {
@@ -39,359 +763,5236 @@ struct ImageViewerApp {
     current_index: usize,
     current_image: Option<DynamicImage>,
     texture: Option<egui::TextureHandle>,
+    /// Shows the image without color, for value studies.
+    grayscale: bool,
+    /// The grayscale version of the image, made the first time it's asked for.
+    gray_texture: Option<egui::TextureHandle>,
+    /// Shows the image in a few flat values instead, for notan and value studies.
+    value_filter: posterize::ValueFilter,
+    /// The image in each of those, all made together on another thread the first
+    /// time one is asked for.
+    value_textures: HashMap<posterize::ValueFilter, egui::TextureHandle>,
+    pending_values: Option<(mpsc::Receiver<posterize::Filtered>, PathBuf)>,
+    /// How the image textures were last uploaded to be sampled.
+    texture_options: egui::TextureOptions,
+    /// Draws every image with sharp pixels this session, for pixel art.
+    sharp_pixels: bool,
+    /// Where the image was last drawn, for fading it out from there.
+    image_rect: Option<egui::Rect>,
+    /// The last image fading out after moving on.
+    crossfade: Option<fade::Crossfade>,
+    /// The images beside the first one on the board.
+    board_slots: Vec<board::Slot>,
+    /// The position in the deck of the last image dealt to the board.
+    board_dealt: usize,
+    /// Where the board's slots were last laid out, the first one's included.
+    board_cells: Vec<egui::Rect>,
+    /// The reference window for another monitor.
+    second_view: second_view::SecondView,
+    upcoming: Option<Upcoming>,
+    /// The blurred copy of the image drawn in the bars around it.
+    letterbox: Option<egui::TextureHandle>,
+    /// The blurred copy being made on another thread, and which image it's of.
+    pending_letterbox: Option<(mpsc::Receiver<egui::ColorImage>, PathBuf)>,
+    /// The current image's tones, counted once per image.
+    histogram: Option<histogram::Histogram>,
+    pending_histogram: Option<(mpsc::Receiver<histogram::Histogram>, PathBuf)>,
+    show_grid_settings: bool,
+    eyedropper: eyedropper::Eyedropper,
+    measure: measure::Measure,
+    crop_tool: crop::CropTool,
+    /// When the bar last came up, for hiding it again.
+    info_bar_shown_at: Instant,
+    /// Size, format and EXIF details of the current image.
+    current_info: Option<image_info::ImageInfo>,
+    show_image_info: bool,
+    /// How far the image is zoomed in and where, and how it's turned, reset for each image.
+    view: zoom::View,
+    image_rotations: HashMap<PathBuf, u8>,
+    image_crops: HashMap<PathBuf, crop::Crop>,
     last_size: Option<egui::Vec2>,
-    last_hover: Instant,
+    pointer: pointer::PointerActivity,
     decorations_visible: bool,
-    folder_map: HashMap<PathBuf, bool>,
+    /// When the title bar last came or went, so it can't flicker back and forth.
+    decorations_toggled_at: Option<Instant>,
+    /// How far the content sits from the window's corner with the title bar on.
+    frame_offset: egui::Vec2,
+    folder_map: FolderMap,
+    /// Every folder added, most recent first, for re-adding from the context menu.
+    recent_folders: Vec<PathBuf>,
+    /// Folders added for this session only, which are never saved.
+    temporary_folders: HashSet<PathBuf>,
     show_folder_manager: bool,
-    show_context_menu: bool,
-    context_menu_pos: egui::Pos2,
+    /// The folder whose Remove button was clicked, awaiting confirmation.
+    confirm_remove_folder: Option<PathBuf>,
+    /// The position in the list of the folder being dragged to a new place.
+    dragged_folder: Option<usize>,
+    /// Whether the right-click menu showed last frame, so Escape closes it
+    /// rather than leaving fullscreen.
+    context_menu_open: bool,
     target_exe_name: Option<String>,
+    /// Compiled from the title pattern settings whenever they hold a valid pattern.
+    title_filter: Option<TitleFilter>,
+    title_filter_error: Option<String>,
+    /// Compiled from the document title pattern setting whenever it's valid.
+    document_pattern: Option<DocumentPattern>,
+    document_pattern_error: Option<String>,
+    /// The document open in the target, while it has focus.
+    current_document: Option<String>,
     target_is_active: bool,
     target_is_hovered: bool,
+    tracker: Tracker,
+    foreground_exe: Option<String>,
+    exe_times: HashMap<String, Duration>,
+    hover_faded: bool,
+    /// Set until the saved window opacity has been applied to the new window.
+    window_opacity_pending: bool,
+    fullscreen: bool,
+    /// Where the window was before going fullscreen, to put it back there after.
+    placement_before_fullscreen: Option<WindowPlacement>,
+    other_app_fullscreen: bool,
+    /// Hidden because another app went fullscreen while the viewer was on top.
+    fullscreen_hidden: bool,
+    /// Never saved, so a restart always brings back a window that can be clicked.
+    click_through: bool,
+    hotkeys: hotkey::Hotkeys,
+    hotkey_bindings: BTreeMap<Command, Binding>,
+    /// The command whose new combination is being recorded.
+    recording_hotkey: Option<Command>,
+    hotkey_record_hint: Option<String>,
+    shortcuts: BTreeMap<Action, Binding>,
+    /// Shortcuts that are left alone, each with why; see `shortcut::conflicts`.
+    shortcut_conflicts: BTreeMap<Action, String>,
+    /// The action whose new keys are being recorded.
+    recording_shortcut: Option<Action>,
+    /// The cheat sheet of keyboard shortcuts.
+    show_shortcuts: bool,
+    gestures: BTreeMap<Gesture, Action>,
+    touch: gesture::TouchGestures,
+    tray: Option<tray::Tray>,
+    /// Hidden to the tray. The window stays open but fully transparent, so the
+    /// timer and alarms keep running.
+    in_tray: bool,
+    /// Set by the tray's Quit, so closing isn't turned into hiding again.
+    quit_requested: bool,
+    /// Read from the registry rather than the config, so it can't disagree with it.
+    start_with_windows: bool,
+    /// Hide on the first frame, for a login launch with `start_minimized` set.
+    hide_on_start: bool,
+    window_placement: Option<WindowPlacement>,
+    /// The active profile's entry is out of date; its live settings are the
+    /// folders, alarms and target exe above.
+    profiles: Vec<Profile>,
+    active_profile: Option<usize>,
+    auto_switch: AutoSwitch,
+    show_profiles: bool,
+    new_profile_name: String,
+    /// The profile being renamed in the Profiles window and the name typed so far.
+    renaming_profile: Option<(usize, String)>,
+    /// Requests from later launches, unless this one was started as an extra viewer.
+    instance: Option<instance::Instance>,
+    /// Folders and images from this launch's own command line, opened on the first frame.
+    launch_paths: Vec<PathBuf>,
+    /// A scan of the folders running on another thread, and what to do with the
+    /// images it finds once it's done.
+    pending_image_list: Option<(mpsc::Receiver<ScanResult>, ScanThen)>,
+    /// What the last scan found in each folder, for the Folder Manager.
+    folder_scans: HashMap<PathBuf, FolderScan>,
+    taskbar_progress: platform::taskbar::TaskbarProgress,
+    awake: platform::power::KeepAwake,
+    /// When the window last moved or resized, if that isn't saved yet.
+    placement_changed_at: Option<Instant>,
+    /// The window's frame when last let go, to tell a move from a resize.
+    snapped_rect: Option<egui::Rect>,
+    /// When the hover state last started to differ from `hover_faded`.
+    hover_pending_since: Option<Instant>,
+    user_idle: bool,
     elapsed_time: Duration,
     last_timer_check: Instant,
-    is_pinned: bool,
+    pin_mode: PinMode,
+    /// Whether the last level sent to the window was always-on-top.
+    pinned_on_top: bool,
+    /// Forces the level to be sent again on the next frame.
     pin_state_changed: bool,
-    alarm_duration: Option<Duration>,
-    alarm_triggered: bool,
-    alarm_sound_path: Option<PathBuf>,
+    /// When the wanted level last started to differ from `pinned_on_top`.
+    pin_pending_since: Option<Instant>,
+    alarms: Vec<Alarm>,
     show_alarm_config: bool,
-    alarm_seconds: Option<u64>,
+    speaker: Option<speech::Speaker>,
+    overtime_escalated: bool,
+    view_overtime: Duration,
+    toaster: Option<toast::Toaster>,
+    timer_paused: bool,
+    last_schedule_check: chrono::DateTime<chrono::Local>,
+    pomodoro: PomodoroState,
+    stats: StatsStore,
+    /// Set while the statistics can't be saved, so that's only reported once.
+    stats_unsaved: bool,
+    show_stats: bool,
+    chart_metric: ChartMetric,
+    current_path: Option<PathBuf>,
+    view_started_at: u64,
+    view_tracked: Duration,
+    view_alarms_fired: usize,
+    view_adjustment: i64,
+    view_log: Vec<ImageView>,
+    show_summary: bool,
+    exit_confirmed: bool,
+    /// When this run started, as a unix timestamp.
+    started_at: u64,
+    /// Set when something was left for `maybe_save_config` to write.
+    config_dirty: bool,
+    last_config_save: Instant,
+    export_status: Option<String>,
+    timer_overlay_hovered: bool,
+    /// The timer's size last frame, for placing it in its corner.
+    timer_overlay_size: egui::Vec2,
+    /// Where the timer is while it's being dragged.
+    timer_drag: Option<egui::Pos2>,
+    sounds: SoundChecks,
+    /// A message at the bottom of the window, when it was shown and for how long.
+    notices: notices::Notices,
+    show_settings_transfer: bool,
+    show_about: bool,
+    /// Shows thumbnails of every image instead of the current one.
+    show_browser: bool,
+    /// Made the first time the browser opens.
+    thumbnails: Option<thumbs::Thumbnails>,
+    /// The image the browser last scrolled to, so it follows the current image.
+    browser_scrolled_to: Option<usize>,
+    show_reset: bool,
+    reset_scope: ResetScope,
+    /// Set once "Reset" was clicked, until it's confirmed or cancelled.
+    confirm_reset: bool,
+    /// Whether exported settings include the image folders, whose paths may not
+    /// exist on the other machine.
+    export_folders: bool,
+    /// Settings read for import and the summary of what they change, awaiting confirmation.
+    pending_import: Option<(ConfigData, String)>,
+    transfer_status: Option<String>,
+    config_version: u32,
+    /// Settings this version doesn't know about, kept for the version that does.
+    unknown_config: serde_json::Map<String, serde_json::Value>,
+    /// Why the config couldn't be loaded, until the user overwrites it.
+    config_error: Option<String>,
+    /// Hides the banner about `config_error` without overwriting the file.
+    config_error_dismissed: bool,
+    /// The theme, accent and system theme the visuals were last set for.
+    applied_theme: Option<(theme::Theme, Option<[u8; 3]>, Option<eframe::Theme>)>,
+    applied_min_window_size: Option<egui::Vec2>,
+    show_settings: bool,
+    settings_tab: settings::Tab,
+    /// Everything the Settings window sets, saved under `settings` in the config.
+    settings: Settings,
 }
 
 impl ImageViewerApp {
-    fn get_exe_name_from_hwnd(hwnd: HWND) -> Option<String> {
-        unsafe {
-            let mut pid = 0;
-            GetWindowThreadProcessId(hwnd, Some(&mut pid));
-            let handle = match OpenProcess(PROCESS_QUERY_INFORMATION | PROCESS_VM_READ, false, pid) {
-                Ok(h) => h,
-                Err(_) => return None,
-            };
-
-            let mut buffer = [0u16; 260];
-            let len = K32GetModuleBaseNameW(handle, None, &mut buffer);
-            let _ = CloseHandle(handle);
-
-            if len == 0 {
-                return None;
-            }
+    /// Shows a short message in the corner of the window for a few seconds, and
+    /// logs it.
+    fn toast(&mut self, level: Level, text: String) {
+        self.toast_for(level, text, NOTICE_DURATION);
+    }
 
-            Some(String::from_utf16_lossy(&buffer[..len as usize]).to_lowercase())
+    fn toast_for(&mut self, level: Level, text: String, how_long: Duration) {
+        match level {
+            Level::Info => log::info!("{}", text),
+            Level::Warning => log::warn!("{}", text),
+            Level::Error => log::error!("{}", text),
         }
+        self.notices.push(level, text, how_long);
     }
 
-    fn save_config(&self) {
-        let config = ConfigData {
-            folder_map: self.folder_map.clone(),
-            target_exe_name: self.target_exe_name.clone(),
-            current_index: self.current_index,
-            is_pinned: self.is_pinned,
-            alarm_seconds: self.alarm_seconds,
-            alarm_sound_path: self.alarm_sound_path.clone(),
-        };
+    /// Whether the timer and the overlays on the image show. A hidden HUD comes
+    /// back for the last seconds before an alarm if that's on.
+    fn hud_visible(&self) -> bool {
+        let display = &self.settings.display;
+        !display.hide_hud || (display.reveal_hud_before_alarm && self.countdown_left().is_some_and(|left| left <= HUD_REVEAL))
+    }
+
+    fn toggle_hud(&mut self) {
+        self.settings.display.hide_hud = !self.settings.display.hide_hud;
+        let text = if self.settings.display.hide_hud { tr("HUD hidden (T shows it)") } else { tr("HUD shown") };
+        self.toast_for(Level::Info, text.to_string(), Duration::from_millis(1500));
+        self.save_config();
+    }
 
-        if let Ok(json) = serde_json::to_string_pretty(&config) {
-            let _ = std::fs::write("viewer_config.json", json);
+    /// Plays an alarm-style sound, noting in the UI when the default chime had to
+    /// stand in for a configured file.
+    fn play_sound(&mut self, path: Option<&Path>, volume: f32) {
+        if self.settings.timer.muted {
+            return;
+        }
+        if let Some(e) = audio::play(path, volume) {
+            let name = path.and_then(|p| p.file_name()).unwrap_or_default().to_string_lossy();
+            self.toast(Level::Warning, tr_args("Couldn't play {name} ({error}), used the default chime instead.", &[("name", &name), ("error", &e)]));
         }
     }
 
-    fn load_image(&mut self, ctx: &egui::Context) {
-        while let Some(path) = self.image_paths.get(self.current_index) {
-            match image::open(path) {
-                Ok(img) => {
-                    let rgba = img.to_rgba8();
-                    let size = [img.width() as usize, img.height() as usize];
-                    let color_image = egui::ColorImage::from_rgba_unmultiplied(size, rgba.as_raw());
-                    self.texture = Some(ctx.load_texture("image", color_image, Default::default()));
-                    self.current_image = Some(img);
-                    self.image_timer = Instant::now();
-                    self.last_size = None;
-                    break;
-                }
-                Err(_) => {
-                    self.image_paths.remove(self.current_index);
-                    if self.current_index >= self.image_paths.len() && !self.image_paths.is_empty() {
-                        self.current_index = 0;
-                    } else {
-                        break;
-                    }
+    fn apply_hover_fade(&mut self, ctx: &egui::Context, frame: &eframe::Frame, faded: bool) {
+        self.hover_faded = faded;
+        if self.in_tray {
+            opacity::set_faded(frame, Some(0.0), true);
+            return;
+        }
+        if self.fullscreen_hidden {
+            opacity::set_hidden(ctx, frame, true, true);
+            return;
+        }
+        match self.settings.display.hover_behavior {
+            HoverBehavior::Fade => {
+                let percent = self.settings.display.hover_fade_percent.min(self.settings.display.window_opacity_percent);
+                let opacity = faded.then(|| percent as f32 / 100.0);
+                // Without window opacity, hiding is the nearest there is to fading.
+                if !opacity::set_faded(frame, opacity, self.click_through) {
+                    opacity::set_hidden(ctx, frame, faded, self.click_through);
                 }
             }
+            // Restoring also lands here if the behavior was switched off while faded.
+            HoverBehavior::Hide | HoverBehavior::Nothing => {
+                opacity::set_hidden(ctx, frame, faded, self.click_through)
+            }
+        }
+        if !faded && self.settings.display.window_opacity_percent < 100 {
+            opacity::set_alpha(frame, self.settings.display.window_opacity_percent as f32 / 100.0, self.click_through);
+        }
+        if !faded {
+            // A window that was hidden can come back without its always-on-top level.
+            self.pin_state_changed = true;
+            ctx.request_repaint();
         }
     }
 
-    fn next_image(&mut self, ctx: &egui::Context) {
-        if !self.image_paths.is_empty() {
-            self.current_index = (self.current_index + 1) % self.image_paths.len();
-            self.load_image(ctx);
-            self.elapsed_time = Duration::ZERO;
-            self.image_timer = Instant::now();
-            self.last_timer_check = Instant::now();
-            self.alarm_triggered = false;
-            self.save_config();
-        }
+    fn set_window_opacity(&mut self, ctx: &egui::Context, frame: &eframe::Frame, percent: u8) {
+        self.settings.display.window_opacity_percent = percent.clamp(MIN_WINDOW_OPACITY_PERCENT, 100);
+        self.config_dirty = true;
+        self.apply_hover_fade(ctx, frame, self.hover_faded);
     }
 
-    fn refresh_image_list(&mut self) {
-        let mut collected_paths = Vec::new();
-        let mut seen = HashSet::new();
+    /// A grid of thumbnails of every image in the deck, with the current one
+    /// highlighted. Only the rows in view are drawn or have thumbnails made, and
+    /// they're returned for the right-click menu to open on.
+    fn browser(&mut self, ctx: &egui::Context, ui: &mut egui::Ui) -> Vec<egui::Response> {
+        const CELL: f32 = 120.0;
+        let thumbnails = self
+            .thumbnails
+            .get_or_insert_with(|| {
+                let cache_dir = (!self.settings.general.private_folders).then(paths::thumbnail_dir);
+                thumbs::Thumbnails::new(ctx.clone(), cache_dir)
+            });
+        thumbnails.receive(ctx);
+
+        let spacing = ui.spacing().item_spacing;
+        let columns = ((ui.available_width() + spacing.x) / (CELL + spacing.x)).floor().max(1.0) as usize;
+        let rows = self.image_paths.len().div_ceil(columns);
+
+        let mut scroll = egui::ScrollArea::vertical().auto_shrink([false, false]);
+        if self.browser_scrolled_to != Some(self.current_index) {
+            let row_top = (self.current_index / columns) as f32 * (CELL + spacing.y);
+            scroll = scroll.vertical_scroll_offset((row_top - (ui.available_height() - CELL) / 2.0).max(0.0));
+            self.browser_scrolled_to = Some(self.current_index);
+        }
 
-        for (folder, enabled) in &self.folder_map {
-            if *enabled {
-                for path in get_image_paths(folder) {
-                    if seen.insert(path.clone()) {
-                        collected_paths.push(path);
+        let mut clicked = None;
+        let mut cells = Vec::new();
+        scroll.show_rows(ui, CELL, rows, |ui, visible_rows| {
+            for row in visible_rows {
+                ui.horizontal(|ui| {
+                    for index in row * columns..((row + 1) * columns).min(self.image_paths.len()) {
+                        let path = &self.image_paths[index];
+                        let (rect, response) = ui.allocate_exact_size(egui::vec2(CELL, CELL), egui::Sense::click());
+                        match thumbnails.get(path) {
+                            thumbs::Thumb::Ready(texture, size) => {
+                                let shown = egui::Rect::from_center_size(rect.center(), size * (CELL / size.max_elem()));
+                                let uv = egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0));
+                                ui.painter().image(texture, shown, uv, egui::Color32::WHITE);
+                            }
+                            thumbs::Thumb::Loading => {
+                                ui.painter().rect_filled(rect, 2.0, ui.visuals().faint_bg_color);
+                            }
+                            thumbs::Thumb::Failed => {
+                                ui.painter().rect_filled(rect, 2.0, ui.visuals().faint_bg_color);
+                                ui.painter().text(
+                                    rect.center(),
+                                    egui::Align2::CENTER_CENTER,
+                                    "?",
+                                    egui::FontId::proportional(24.0),
+                                    ui.visuals().weak_text_color(),
+                                );
+                            }
+                        }
+                        if index == self.current_index {
+                            ui.painter().rect_stroke(rect.expand(2.0), 2.0, egui::Stroke::new(2.0, ui.visuals().selection.bg_fill));
+                        }
+                        let name = path.file_name().unwrap_or_default().to_string_lossy();
+                        let response = response.on_hover_text(name);
+                        if response.clicked() {
+                            clicked = Some(index);
+                        }
+                        cells.push(response);
                     }
-                }
+                });
             }
+        });
+        thumbnails.finish_frame();
+
+        if let Some(index) = clicked {
+            self.show_browser = false;
+            self.show_image(ctx, index);
         }
-        collected_paths.shuffle(&mut rand::thread_rng()); 
-        self.image_paths = collected_paths;
+        cells
     }
-}
 
-impl App for ImageViewerApp {
-    fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
+    /// The Settings window, one tab of options at a time. Changes take effect and
+    /// are saved as they're made.
+    fn settings_window(&mut self, ctx: &egui::Context, frame: &eframe::Frame) {
+        let mut open = true;
+        egui::Window::new("Settings").open(&mut open).show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                for tab in settings::Tab::ALL {
+                    ui.selectable_value(&mut self.settings_tab, tab, tab.label());
+                }
+            });
+            ui.separator();
 
-        if self.pin_state_changed {
-            use egui::WindowLevel;
-            let level = if self.is_pinned {
-                WindowLevel::AlwaysOnTop
-            } else {
-                WindowLevel::Normal
-            };
-            ctx.send_viewport_cmd(egui::ViewportCommand::WindowLevel(level));
-            self.pin_state_changed = false;
-        }
+            let changed = egui::ScrollArea::vertical()
+                .show(ui, |ui| match self.settings_tab {
+                    settings::Tab::General => self.general_settings(ui),
+                    settings::Tab::Timer => self.timer_settings(ui),
+                    settings::Tab::Tracking => self.tracking_settings(ui),
+                    settings::Tab::Display => self.display_settings(ctx, frame, ui),
+                    settings::Tab::Hotkeys => self.hotkey_settings(ui),
+                })
+                .inner;
 
+            ui.separator();
+            let reset = ui
+                .button("Reset This Tab")
+                .on_hover_text(format!("Put the {} options back to how a fresh install has them.", self.settings_tab.label()));
+            if reset.clicked() {
+                self.reset_settings_tab(self.settings_tab);
+            } else if changed {
+                self.save_config();
+            }
+        });
+        // A combination half recorded is dropped once its tab is out of sight.
+        if !open || self.settings_tab != settings::Tab::Hotkeys {
+            if self.recording_hotkey.take().is_some() {
+                self.hotkey_record_hint = None;
+                self.hotkeys.rebind(&self.hotkey_bindings);
+            }
+            self.recording_shortcut = None;
+        }
+        self.show_settings = open;
+    }
+
+    /// Opens the Settings window at `tab`.
+    fn open_settings(&mut self, tab: settings::Tab) {
+        self.settings_tab = tab;
+        self.show_settings = true;
+    }
+
+    /// Puts the options on `tab` back to their defaults.
+    fn reset_settings_tab(&mut self, tab: settings::Tab) {
+        let mut settings = self.settings.clone();
+        match tab {
+            settings::Tab::General => settings.general = Default::default(),
+            settings::Tab::Timer => settings.timer = Default::default(),
+            settings::Tab::Tracking => settings.tracking = Default::default(),
+            settings::Tab::Display => settings.display = Default::default(),
+            settings::Tab::Hotkeys => {
+                self.hotkey_bindings = hotkey::default_bindings();
+                self.recording_hotkey = None;
+                self.hotkey_record_hint = None;
+                self.hotkeys.rebind(&self.hotkey_bindings);
+                self.shortcuts = shortcut::default_shortcuts();
+                self.recording_shortcut = None;
+                self.shortcuts_changed();
+                self.gestures = gesture::default_gestures();
+            }
+        }
+        self.apply_settings(settings);
+        self.info_bar_shown_at = Instant::now();
+        self.save_config();
+    }
+
+    /// Returns true if an option changed that still has to be saved.
+    fn general_settings(&mut self, ui: &mut egui::Ui) -> bool {
+        let mut changed = false;
+        egui::ComboBox::from_label(tr("Language"))
+            .selected_text(self.settings.general.language.label())
+            .show_ui(ui, |ui| {
+                for language in Language::ALL {
+                    if ui.selectable_value(&mut self.settings.general.language, language, language.label()).changed() {
+                        i18n::set_language(language);
+                        changed = true;
+                    }
+                }
+            });
+        ui.separator();
+
+        changed |= ui
+            .add_enabled(
+                self.tray.is_some(),
+                egui::Checkbox::new(&mut self.settings.general.close_to_tray, "Close button hides the viewer to the tray"),
+            )
+            .changed();
+
+        if ui.checkbox(&mut self.start_with_windows, "Start with Windows").changed() {
+            if let Err(e) = autostart::set_enabled(self.start_with_windows) {
+                self.start_with_windows = autostart::is_enabled();
+                self.toast(Level::Error, tr_args("Couldn't change the startup entry: {error}", &[("error", &e)]));
+            }
+        }
+        ui.indent("start_minimized", |ui| {
+            let label = if self.tray.is_some() { "Start hidden in the tray" } else { "Start minimized" };
+            changed |= ui
+                .add_enabled(self.start_with_windows, egui::Checkbox::new(&mut self.settings.general.start_minimized, label))
+                .changed();
+        });
+        changed |= ui
+            .checkbox(&mut self.settings.general.show_exit_summary, "Show a summary of the session on exit")
+            .changed();
+
+        ui.separator();
+        changed |= ui
+            .checkbox(&mut self.settings.general.persist_elapsed, "Keep the timer across restarts on the same day")
+            .changed();
+        if self.settings.general.persist_elapsed {
+            changed |= ui
+                .checkbox(&mut self.settings.general.replay_missed_alarms, "Sound alarms already passed when restoring")
+                .changed();
+        }
+        changed |= ui
+            .checkbox(&mut self.settings.general.persist_deck, "Continue the shuffled order across restarts")
+            .changed();
+        changed |= ui
+            .checkbox(&mut self.settings.general.auto_switch_profiles, "Switch to the profile of the focused app")
+            .changed();
+
+        let privacy = ui
+            .checkbox(&mut self.settings.general.private_folders, "Don't remember folders on this computer")
+            .on_hover_text("Folders added are kept for this session only. Other settings are still saved.");
+        if privacy.changed() {
+            // Takes the folders out of the file, or puts them back.
+            self.save_config();
+            if self.settings.general.private_folders {
+                self.forget_opened_images();
+                // Again, so the backup of the previous version loses them too.
+                self.save_config();
+            }
+        }
+
+        ui.separator();
+        changed |= ui
+            .checkbox(&mut self.settings.general.keep_awake, "Keep the computer awake while the timer or an alarm is running")
+            .changed();
+        ui.indent("keep_display_on", |ui| {
+            changed |= ui
+                .add_enabled(self.settings.general.keep_awake, egui::Checkbox::new(&mut self.settings.general.keep_display_on, "Keep the screen on too"))
+                .changed();
+        });
+
+        ui.separator();
+        // What's in use, which is stats.json if the database couldn't be opened at launch.
+        let mut use_database = self.stats.uses_database();
+        if ui
+            .checkbox(&mut use_database, "Keep statistics in a database")
+            .on_hover_text("Also keeps every session and image view, in stats.sqlite next to the settings.")
+            .changed()
+        {
+            self.set_stats_database(use_database);
+            changed = true;
+        }
+        if self.settings.general.stats_database && !self.stats.uses_database() {
+            ui.colored_label(ui.visuals().warn_fg_color, "The database couldn't be opened, so statistics are kept in stats.json for now.");
+        }
+        ui.horizontal(|ui| {
+            ui.label("Clean up statistics older than");
+            changed |= ui
+                .add(egui::DragValue::new(&mut self.settings.general.stats_retention_days).clamp_range(1..=3650))
+                .changed();
+            ui.label("days");
+        });
+
+        let mut auto_export = self.settings.general.auto_export_dir.is_some();
+        if ui.checkbox(&mut auto_export, "Export the session log on exit").changed() {
+            self.settings.general.auto_export_dir = if auto_export {
+                FileDialog::new().set_title("Auto-export Folder").pick_folder()
+            } else {
+                None
+            };
+            changed = true;
+        }
+        if let Some(dir) = &self.settings.general.auto_export_dir {
+            ui.small(format!("Exporting to {}", dir.display()));
+        }
+        changed
+    }
+
+    /// Returns true if an option changed that still has to be saved.
+    fn timer_settings(&mut self, ui: &mut egui::Ui) -> bool {
+        let timer = &mut self.settings.timer;
+        let mut changed = ui.checkbox(&mut timer.muted, "Mute alarm sounds and speech").changed();
+        changed |= ui.add(egui::Slider::new(&mut timer.alarm_volume, 0.0..=1.0).text("Alarm Volume")).changed();
+
+        ui.horizontal(|ui| {
+            ui.label("Timer display:");
+            for mode in TimerDisplayMode::ALL {
+                changed |= ui.radio_value(&mut timer.timer_display_mode, mode, mode.label()).changed();
+            }
+        });
+        changed |= ui.checkbox(&mut timer.countdown_tenths, "Show tenths in the last 10 seconds").changed();
+        changed |= ui.checkbox(&mut timer.show_progress_bar, "Show progress bar under the timer").changed();
+        changed |= ui
+            .checkbox(&mut timer.ring.enabled, "Show a frame around the window that runs down toward the alarm")
+            .changed();
+        ui.add_enabled_ui(timer.ring.enabled, |ui| {
+            ui.indent("ring", |ui| {
+                changed |= ui.add(egui::Slider::new(&mut timer.ring.thickness, 1.0..=20.0).text("Thickness")).changed();
+                ui.horizontal(|ui| {
+                    ui.label("Color from");
+                    changed |= ui.color_edit_button_srgb(&mut timer.ring.start_color).changed();
+                    ui.label("to");
+                    changed |= ui.color_edit_button_srgb(&mut timer.ring.end_color).changed();
+                });
+            });
+        });
+        changed |= ui.add(egui::Slider::new(&mut timer.overlay.font_size, 12.0..=72.0).text("Timer Size")).changed();
+        ui.horizontal(|ui| {
+            ui.label("Move the timer to:");
+            for corner in overlay::Corner::ALL {
+                if ui.button(corner.label()).clicked() {
+                    timer.overlay.snap_to(corner);
+                    changed = true;
+                }
+            }
+        });
+        changed |= ui
+            .checkbox(&mut timer.overlay.snap, "Snap into the nearest corner when dropped")
+            .on_hover_text("Drag the timer to put it anywhere over the image.")
+            .changed();
+
+        ui.separator();
+        let mut warn_enabled = timer.pre_alarm_seconds.is_some();
+        if ui.checkbox(&mut warn_enabled, "Warn before alarm").changed() {
+            timer.pre_alarm_seconds = warn_enabled.then_some(30);
+            changed = true;
+        }
+        if let Some(offset) = timer.pre_alarm_seconds.as_mut() {
+            changed |= ui.add(egui::Slider::new(offset, 5..=600).text("Warn Before Alarm (sec)")).changed();
+            changed |= ui.add(egui::Slider::new(&mut timer.pre_alarm_volume, 0.0..=1.0).text("Warning Volume")).changed();
+            changed |= self.sounds.picker(ui, "pre_alarm_sound", "Choose Warning Sound", &mut timer.pre_alarm_sound_path, timer.pre_alarm_volume, false);
+            changed |= ui.checkbox(&mut timer.pre_alarm_pulse, "Pulse timer during warning").changed();
+        }
+        for label in self.alarms_before_warning() {
+            ui.colored_label(ui.visuals().error_fg_color, tr_args("The warning must come before '{label}' itself.", &[("label", &label)]));
+        }
+
+        let timer = &mut self.settings.timer;
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.label("Overtime color:");
+            changed |= ui.color_edit_button_srgb(&mut timer.overtime_color).changed();
+        });
+        let mut escalate = timer.overtime_escalation_seconds.is_some();
+        if ui.checkbox(&mut escalate, "Escalate after overtime").changed() {
+            timer.overtime_escalation_seconds = escalate.then_some(300);
+            changed = true;
+        }
+        if let Some(limit) = timer.overtime_escalation_seconds.as_mut() {
+            changed |= ui.add(egui::Slider::new(limit, 30..=3600).text("Escalate After (sec over)")).changed();
+            changed |= self.sounds.picker(ui, "overtime_sound", "Choose Escalation Sound", &mut timer.overtime_sound_path, 1.0, true);
+        }
+
+        ui.separator();
+        changed |= ui.checkbox(&mut timer.alarm_toast, "Show a Windows notification when an alarm fires").changed();
+        changed |= ui.checkbox(&mut timer.speech.enabled, "Speak timer events").changed();
+        if timer.speech.enabled {
+            egui::Grid::new("speech_phrases").show(ui, |ui| {
+                ui.label("Warning:");
+                changed |= ui.text_edit_singleline(&mut timer.speech.pre_alarm_phrase).changed();
+                ui.end_row();
+                ui.label("Alarm:");
+                changed |= ui.text_edit_singleline(&mut timer.speech.alarm_phrase).changed();
+                ui.end_row();
+                ui.label("Pomodoro phase:");
+                changed |= ui.text_edit_singleline(&mut timer.speech.segment_phrase).changed();
+                ui.end_row();
+            });
+            ui.small("{label}, {seconds} and {phase} are replaced with the alarm name, warning time and pomodoro phase.");
+        }
+
+        ui.separator();
+        let pomodoro = &mut timer.pomodoro;
+        changed |= ui.checkbox(&mut pomodoro.enabled, "Enable pomodoro mode").changed();
+        changed |= ui.add(egui::Slider::new(&mut pomodoro.work_minutes, 1..=120).text("Work (min)")).changed();
+        changed |= ui.add(egui::Slider::new(&mut pomodoro.short_break_minutes, 1..=60).text("Break (min)")).changed();
+        changed |= ui.add(egui::Slider::new(&mut pomodoro.long_break_minutes, 1..=120).text("Long Break (min)")).changed();
+        changed |= ui
+            .add(egui::Slider::new(&mut pomodoro.cycles_before_long_break, 1..=12).text("Cycles Before Long Break"))
+            .changed();
+        changed |= self.sounds.picker(ui, "pomodoro_sound", "Choose Pomodoro Sound", &mut pomodoro.sound_path, timer.alarm_volume, true);
+        ui.small("Work time only counts while the tracked EXE is focused.");
+        ui.horizontal(|ui| {
+            ui.label(format!("Completed this session: {}", self.pomodoro.log.len()));
+            if ui.button("Restart Cycle").clicked() {
+                self.pomodoro.reset();
+            }
+        });
+        changed
+    }
+
+    /// The names of alarms on tracked time that the warning doesn't come before.
+    fn alarms_before_warning(&self) -> Vec<String> {
+        let Some(offset) = self.settings.timer.pre_alarm_seconds else {
+            return Vec::new();
+        };
+        self.alarms
+            .iter()
+            .filter(|a| a.counts_tracked_time() && offset >= a.seconds)
+            .map(|a| a.label.clone())
+            .collect()
+    }
+
+    /// Returns true if an option changed that still has to be saved.
+    fn tracking_settings(&mut self, ui: &mut egui::Ui) -> bool {
+        ui.label("Time is counted separately for each of these while it is focused.");
+
+        let mut remove_index = None;
+        for (i, exe) in self.settings.tracking.tracked_exes.iter().enumerate() {
+            ui.horizontal(|ui| {
+                let time = self.exe_times.get(exe).copied().unwrap_or_default();
+                ui.label(format!("{} ({})", exe, format_hours(time.as_secs())));
+                if ui.button("Remove").clicked() {
+                    remove_index = Some(i);
+                }
+            });
+        }
+
+        let mut changed = false;
+        if let Some(i) = remove_index {
+            self.settings.tracking.tracked_exes.remove(i);
+            changed = true;
+        }
+
+        ui.horizontal(|ui| {
+            if ui.button("Add EXE...").clicked() {
+                if let Some(path) = FileDialog::new().add_filter("EXE", &["exe"]).pick_file() {
+                    if let Some(name) = path.file_name().and_then(|s| s.to_str()) {
+                        let name = name.to_lowercase();
+                        if !self.settings.tracking.tracked_exes.contains(&name) {
+                            self.settings.tracking.tracked_exes.push(name);
+                            changed = true;
+                        }
+                    }
+                }
+            }
+
+            if let Some(target) = &self.target_exe_name {
+                if !self.settings.tracking.tracked_exes.contains(target) && ui.button(format!("Add {}", target)).clicked() {
+                    self.settings.tracking.tracked_exes.push(target.clone());
+                    changed = true;
+                }
+            }
+        });
+
+        changed |= ui
+            .checkbox(&mut self.settings.tracking.show_focused_exe_counter, "Show the focused app's counter on the timer")
+            .changed();
+
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.label("Count target time while:");
+            for mode in TrackingMode::ALL {
+                changed |= ui.radio_value(&mut self.settings.tracking.tracking_mode, mode, mode.label()).changed();
+            }
+        });
+
+        let mut title_changed = false;
+        ui.horizontal(|ui| {
+            ui.label("Only windows titled:");
+            title_changed |= ui.text_edit_singleline(&mut self.settings.tracking.target_title_pattern).changed();
+            title_changed |= ui.checkbox(&mut self.settings.tracking.target_title_regex, "Regex").changed();
+        });
+        ui.small("Leave empty to count every window of the target exe.");
+        if title_changed {
+            // An invalid regex leaves the last working filter in place until it's fixed.
+            match TitleFilter::new(&self.settings.tracking.target_title_pattern, self.settings.tracking.target_title_regex) {
+                Ok(filter) => {
+                    self.title_filter = filter;
+                    self.title_filter_error = None;
+                    changed = true;
+                }
+                Err(e) => self.title_filter_error = Some(e),
+            }
+        }
+        if let Some(error) = &self.title_filter_error {
+            ui.colored_label(ui.visuals().error_fg_color, error);
+        }
+
+        changed |= ui
+            .checkbox(&mut self.settings.tracking.include_child_processes, "Count windows of processes the target started")
+            .changed();
+
+        changed |= ui
+            .checkbox(&mut self.settings.tracking.track_documents, "Count time per document from the window title")
+            .changed();
+        if self.settings.tracking.track_documents {
+            ui.horizontal(|ui| {
+                ui.label("Document pattern:");
+                if ui.text_edit_singleline(&mut self.settings.tracking.document_title_pattern).changed() {
+                    match DocumentPattern::new(&self.settings.tracking.document_title_pattern) {
+                        Ok(pattern) => {
+                            self.document_pattern = Some(pattern);
+                            self.document_pattern_error = None;
+                            changed = true;
+                        }
+                        Err(e) => self.document_pattern_error = Some(e),
+                    }
+                }
+                if ui.small_button("Reset").clicked() {
+                    self.settings.tracking.document_title_pattern = DocumentPattern::DEFAULT.to_string();
+                    self.document_pattern = DocumentPattern::new(&self.settings.tracking.document_title_pattern).ok();
+                    self.document_pattern_error = None;
+                    changed = true;
+                }
+            });
+            ui.small("A regex; the first group is the document name. Titles it doesn't match are used whole.");
+            if let Some(error) = &self.document_pattern_error {
+                ui.colored_label(ui.visuals().error_fg_color, error);
+            }
+        }
+
+        ui.separator();
+        changed |= ui
+            .checkbox(&mut self.settings.tracking.idle_pause_enabled, "Pause when there is no keyboard or mouse input")
+            .changed();
+        if self.settings.tracking.idle_pause_enabled {
+            changed |= ui
+                .add(egui::Slider::new(&mut self.settings.tracking.idle_threshold_seconds, 10..=1800).text("Idle After (sec)"))
+                .changed();
+        }
+
+        changed |= ui
+            .add(egui::Slider::new(&mut self.settings.tracking.poll_interval_ms, 50..=2000).text("Check Every (ms)"))
+            .changed();
+
+        changed |= ui
+            .checkbox(&mut self.settings.tracking.hide_over_fullscreen, "Hide while another app is fullscreen")
+            .changed();
+        if self.settings.tracking.hide_over_fullscreen {
+            ui.indent("fullscreen_exclusions", |ui| {
+                let mut remove_index = None;
+                for (i, exe) in self.settings.tracking.fullscreen_exclusions.iter().enumerate() {
+                    ui.horizontal(|ui| {
+                        ui.label(exe);
+                        if ui.button("Remove").clicked() {
+                            remove_index = Some(i);
+                        }
+                    });
+                }
+                if let Some(i) = remove_index {
+                    self.settings.tracking.fullscreen_exclusions.remove(i);
+                    changed = true;
+                }
+
+                if ui.button("Stay Over EXE...").clicked() {
+                    if let Some(path) = FileDialog::new().add_filter("EXE", &["exe"]).pick_file() {
+                        if let Some(name) = path.file_name().and_then(|s| s.to_str()) {
+                            let name = name.to_lowercase();
+                            if !self.settings.tracking.fullscreen_exclusions.contains(&name) {
+                                self.settings.tracking.fullscreen_exclusions.push(name);
+                                changed = true;
+                            }
+                        }
+                    }
+                }
+                ui.small("The target app never hides the viewer.");
+            });
+        }
+        changed
+    }
+
+    /// Returns true if an option changed that still has to be saved.
+    fn display_settings(&mut self, ctx: &egui::Context, frame: &eframe::Frame, ui: &mut egui::Ui) -> bool {
+        let mut changed = false;
+        ui.horizontal(|ui| {
+            ui.label("Theme:");
+            for theme in theme::Theme::ALL {
+                changed |= ui.radio_value(&mut self.settings.display.theme, theme, theme.label()).changed();
+            }
+        });
+        ui.horizontal(|ui| {
+            let display = &mut self.settings.display;
+            let mut custom = display.accent_color.is_some();
+            if ui.checkbox(&mut custom, "Accent color").changed() {
+                // Starts from egui's own blue.
+                display.accent_color = custom.then_some([0, 92, 128]);
+                changed = true;
+            }
+            if let Some(accent) = &mut display.accent_color {
+                changed |= ui.color_edit_button_srgb(accent).changed();
+            }
+        });
+
+        ui.separator();
+        changed |= ui
+            .checkbox(&mut self.settings.display.borderless, "Borderless")
+            .on_hover_text("No title bar. Drag the image to move the window.")
+            .changed();
+        changed |= ui
+            .add_enabled(
+                !self.settings.display.borderless,
+                egui::Checkbox::new(&mut self.settings.display.auto_hide_title_bar, "Hide the title bar while the pointer is away"),
+            )
+            .changed();
+        let mut hide_cursor = self.settings.display.hide_cursor_after_seconds.is_some();
+        ui.horizontal(|ui| {
+            if ui.checkbox(&mut hide_cursor, "Hide the cursor when it's left still over the image").changed() {
+                self.settings.display.hide_cursor_after_seconds = hide_cursor.then_some(3);
+                changed = true;
+            }
+            if let Some(seconds) = self.settings.display.hide_cursor_after_seconds.as_mut() {
+                changed |= ui.add(egui::DragValue::new(seconds).clamp_range(1..=60).suffix(" s")).changed();
+            }
+        });
+
+        let mut percent = self.settings.display.window_opacity_percent;
+        if ui.add(egui::Slider::new(&mut percent, MIN_WINDOW_OPACITY_PERCENT..=100).text("Opacity (%)")).changed() {
+            self.set_window_opacity(ctx, frame, percent);
+        }
+        changed |= ui
+            .add(egui::Slider::new(&mut self.settings.display.ui_scale_percent, MIN_UI_SCALE_PERCENT..=MAX_UI_SCALE_PERCENT).text("Menu and window size (%)"))
+            .on_hover_text("On top of the Windows display scaling. Ctrl+Plus, Ctrl+Minus and Ctrl+0 change it too. The timer keeps its own size.")
+            .changed();
+
+        ui.horizontal(|ui| {
+            ui.label("While hovering the target:");
+            for behavior in HoverBehavior::ALL {
+                changed |= ui.radio_value(&mut self.settings.display.hover_behavior, behavior, behavior.label()).changed();
+            }
+        });
+        if self.settings.display.hover_behavior == HoverBehavior::Fade {
+            changed |= ui
+                .add(egui::Slider::new(&mut self.settings.display.hover_fade_percent, 5..=90).text("Faded Opacity (%)"))
+                .changed();
+        }
+
+        ui.horizontal(|ui| {
+            ui.label("Window size:");
+            for fit in ImageFit::ALL {
+                if ui.radio_value(&mut self.settings.display.image_fit, fit, fit.label()).changed() {
+                    self.last_size = None;
+                    changed = true;
+                }
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label("Smallest window:");
+            let [width, height] = &mut self.settings.display.min_window_size;
+            let mut resized = ui.add(egui::DragValue::new(width).clamp_range(MIN_WINDOW_SIDE..=1000)).changed();
+            ui.label("×");
+            resized |= ui.add(egui::DragValue::new(height).clamp_range(MIN_WINDOW_SIDE..=1000)).changed();
+            if resized {
+                self.last_size = None;
+                changed = true;
+            }
+        })
+        .response
+        .on_hover_text("Small images and resizing by hand stop at this size.");
+        let snap = &mut self.settings.display.snap;
+        ui.horizontal(|ui| {
+            changed |= ui
+                .checkbox(&mut snap.enabled, "Snap to screen edges within")
+                .on_hover_text("A window let go this close to an edge or corner of the screen moves flush with it.")
+                .changed();
+            ui.add_enabled_ui(snap.enabled, |ui| {
+                changed |= ui.add(egui::DragValue::new(&mut snap.distance).clamp_range(1.0..=100.0).suffix(" px")).changed();
+            });
+        });
+        changed |= ui
+            .add(egui::Slider::new(&mut snap.throw_size_percent, 10..=100).text("Size when moved by hotkey (% of screen)"))
+            .on_hover_text("Ctrl+Alt with the number pad moves the window to that part of the screen at this size.")
+            .changed();
+        ui.horizontal(|ui| {
+            ui.label("Image size:");
+            for mode in FitMode::ALL {
+                if ui.radio_value(&mut self.settings.display.fit_mode, mode, mode.label()).changed() {
+                    self.last_size = None;
+                    changed = true;
+                }
+            }
+        });
+        changed |= ui
+            .add(egui::Slider::new(&mut self.settings.display.crossfade_ms, 0..=1000).text("Crossfade Between Images (ms)"))
+            .on_hover_text("0 cuts straight to the next image.")
+            .changed();
+        ui.horizontal(|ui| {
+            ui.label("Timer while fullscreen:");
+            for mode in FullscreenTimer::ALL {
+                changed |= ui.radio_value(&mut self.settings.display.fullscreen_timer, mode, mode.label()).changed();
+            }
+        });
+
+        ui.separator();
+        let board = &mut self.settings.display.board;
+        ui.horizontal(|ui| {
+            ui.label("Reference board:");
+            for layout in BoardLayout::ALL {
+                if ui.radio_value(&mut board.layout, layout, layout.label()).changed() {
+                    self.last_size = None;
+                    changed = true;
+                }
+            }
+        });
+        if board.layout != BoardLayout::Single {
+            ui.horizontal(|ui| {
+                ui.label("Next image moves on:");
+                for advance in BoardAdvance::ALL {
+                    changed |= ui.radio_value(&mut board.advance, advance, advance.label()).changed();
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.label("Timing:");
+                for timer in BoardTimer::ALL {
+                    changed |= ui.radio_value(&mut board.timer, timer, timer.label()).changed();
+                }
+            });
+        }
+
+        ui.separator();
+        let second = &mut self.settings.display.second_view;
+        changed |= ui
+            .checkbox(&mut second.open, "Second window, for another monitor")
+            .on_hover_text("Closing it leaves the viewer running.")
+            .changed();
+        if second.open {
+            ui.horizontal(|ui| {
+                ui.label("It shows:");
+                for mode in second_view::SecondViewMode::ALL {
+                    changed |= ui.radio_value(&mut second.mode, mode, mode.label()).changed();
+                }
+            });
+            changed |= ui.checkbox(&mut second.borderless, "Borderless (drag the image to move it)").changed();
+            changed |= ui.checkbox(&mut second.always_on_top, "Always on top").changed();
+            changed |= ui.add(egui::Slider::new(&mut second.opacity_percent, 20..=100).text("Opacity (%)")).changed();
+        }
+
+        ui.separator();
+        if ui.checkbox(&mut self.settings.display.remember_rotation, "Remember how each image was rotated").changed() {
+            self.remember_rotation_changed();
+            changed = true;
+        }
+        changed |= ui.checkbox(&mut self.settings.display.sticky_flip, "Keep images flipped when moving on").changed();
+        changed |= ui
+            .checkbox(&mut self.settings.display.sticky_grayscale, "Keep showing images in grayscale when moving on")
+            .changed();
+        changed |= ui
+            .checkbox(&mut self.settings.display.keep_swatches, "Keep the last 8 colors picked with the eyedropper")
+            .on_hover_text("Shown in a strip while picking. Click one to copy it again.")
+            .changed();
+        ui.checkbox(&mut self.sharp_pixels, "Sharp pixels for every image (this session)")
+            .on_hover_text("Draws pixel art without smoothing the pixels into each other.");
+        let mut sharp_small = self.settings.display.sharp_pixels_up_to.is_some();
+        ui.horizontal(|ui| {
+            if ui.checkbox(&mut sharp_small, "Sharp pixels for images up to").changed() {
+                self.settings.display.sharp_pixels_up_to = sharp_small.then_some(256);
+                changed = true;
+            }
+            if let Some(up_to) = self.settings.display.sharp_pixels_up_to.as_mut() {
+                changed |= ui.add(egui::DragValue::new(up_to).clamp_range(16..=4096).suffix(" px")).changed();
+            }
+            ui.label("on the long edge");
+        });
+        changed |= ui
+            .checkbox(&mut self.settings.display.blurred_letterbox, "Fill the bars beside the image with a blurred copy of it")
+            .changed();
+        let backdrop = &mut self.settings.display.backdrop;
+        ui.horizontal(|ui| {
+            ui.label("Behind transparent images:");
+            for kind in backdrop::BackdropKind::ALL {
+                changed |= ui.radio_value(&mut backdrop.kind, kind, kind.label()).changed();
+            }
+        });
+        match backdrop.kind {
+            backdrop::BackdropKind::Panel => {}
+            backdrop::BackdropKind::Checkerboard => {
+                changed |= ui.add(egui::Slider::new(&mut backdrop.cell_size, 4.0..=64.0).text("Square Size")).changed();
+            }
+            backdrop::BackdropKind::Solid => {
+                ui.horizontal(|ui| {
+                    ui.label("Color:");
+                    changed |= ui.color_edit_button_srgb(&mut backdrop.color).changed();
+                });
+            }
+        }
+
+        ui.separator();
+        let label = self.with_shortcut("Hide the timer and overlays", Action::ToggleHud);
+        changed |= ui.checkbox(&mut self.settings.display.hide_hud, label).changed();
+        changed |= ui
+            .add_enabled(
+                self.settings.display.hide_hud,
+                egui::Checkbox::new(&mut self.settings.display.reveal_hud_before_alarm, "Show them for the last 10 seconds before an alarm"),
+            )
+            .changed();
+
+        ui.separator();
+        let accent = self.settings.display.accent_color;
+        let style = &mut self.settings.display.timer_style;
+        ui.horizontal(|ui| {
+            ui.label("Timer colors:");
+            for preset in overlay::Preset::ALL {
+                if ui.button(preset.label()).clicked() {
+                    *style = preset.style();
+                    changed = true;
+                }
+            }
+            if let Some(accent) = accent {
+                if ui.button("Accent Color").clicked() {
+                    *style = overlay::TimerStyle::accented(accent);
+                    changed = true;
+                }
+            }
+        });
+        egui::Grid::new("timer_style").num_columns(2).show(ui, |ui| {
+            ui.label("Text:");
+            changed |= ui.color_edit_button_srgb(&mut style.text_color).changed();
+            ui.end_row();
+            ui.label("Status lines:");
+            changed |= ui.color_edit_button_srgb(&mut style.detail_color).changed();
+            ui.end_row();
+            ui.label("Background:");
+            changed |= ui.color_edit_button_srgb(&mut style.background_color).changed();
+            ui.end_row();
+        });
+        changed |= ui
+            .add(egui::Slider::new(&mut style.background_opacity, 0.0..=1.0).text("Background Opacity"))
+            .changed();
+        changed |= ui
+            .checkbox(&mut style.outline, "Outline only")
+            .on_hover_text("Outline the text instead of putting it on a box. The box still shows for warnings and overtime.")
+            .changed();
+
+        ui.separator();
+        let label = self.with_shortcut("Show the file name over the image", Action::ToggleInfoBar);
+        let info_bar = &mut self.settings.display.info_bar;
+        let mut info_bar_changed = ui.checkbox(&mut info_bar.enabled, label).changed();
+        ui.indent("info_bar", |ui| {
+            ui.horizontal(|ui| {
+                for position in info_bar::Position::ALL {
+                    info_bar_changed |= ui.radio_value(&mut info_bar.position, position, position.label()).changed();
+                }
+            });
+            ui.horizontal(|ui| {
+                info_bar_changed |= ui.checkbox(&mut info_bar.auto_hide, "Hide after").changed();
+                info_bar_changed |= ui
+                    .add(egui::DragValue::new(&mut info_bar.hide_after_seconds).clamp_range(1..=60).suffix(" s"))
+                    .changed();
+            });
+        });
+        if info_bar_changed {
+            self.info_bar_shown_at = Instant::now();
+            changed = true;
+        }
+
+        ui.separator();
+        let label = self.with_shortcut("Show a histogram of the image's tones", Action::ToggleHistogram);
+        let histogram = &mut self.settings.display.histogram;
+        changed |= ui.checkbox(&mut histogram.enabled, label).changed();
+        ui.indent("histogram", |ui| {
+            ui.horizontal(|ui| {
+                for corner in overlay::Corner::ALL {
+                    changed |= ui.radio_value(&mut histogram.corner, corner, corner.label()).changed();
+                }
+            });
+            changed |= ui.checkbox(&mut histogram.channels, "Red, green and blue too").changed();
+        });
+
+        ui.separator();
+        let label = self.with_shortcut("Value studies: where notan and 3 or 5 values split", Action::CycleValueFilter);
+        ui.label(label);
+        let posterize = &mut self.settings.display.posterize;
+        let mut values_changed = ui
+            .checkbox(&mut posterize.automatic, "Find the splits from each image's own tones")
+            .on_hover_text("Otsu's method: splits where the values differ from each other the most.")
+            .changed();
+        if !posterize.automatic {
+            for filter in &posterize::ValueFilter::ALL[1..] {
+                ui.horizontal(|ui| {
+                    ui.label(filter.label());
+                    for threshold in posterize.thresholds_mut(*filter) {
+                        values_changed |= ui.add(egui::DragValue::new(threshold).clamp_range(1..=255)).changed();
+                    }
+                });
+            }
+        }
+        if values_changed {
+            self.clear_value_textures();
+            changed = true;
+        }
+
+        ui.separator();
+        changed |= self.grid_settings(ui);
+        changed
+    }
+
+    /// The grid options, for the Grid window and the Display tab. Returns true if
+    /// one changed.
+    fn grid_settings(&mut self, ui: &mut egui::Ui) -> bool {
+        let label = self.with_shortcut("Show grid", Action::ToggleGrid);
+        let settings = &mut self.settings.display.grid;
+        let mut changed = ui.checkbox(&mut settings.enabled, label).changed();
+        ui.horizontal(|ui| {
+            for spacing in grid::Spacing::ALL {
+                changed |= ui.radio_value(&mut settings.spacing, spacing, spacing.label()).changed();
+            }
+        });
+        match settings.spacing {
+            grid::Spacing::Divisions => {
+                ui.horizontal(|ui| {
+                    changed |= ui.add(egui::DragValue::new(&mut settings.rows).clamp_range(1..=100)).changed();
+                    ui.label("rows by");
+                    changed |= ui.add(egui::DragValue::new(&mut settings.columns).clamp_range(1..=100)).changed();
+                    ui.label("columns");
+                });
+            }
+            grid::Spacing::CellSize => {
+                ui.horizontal(|ui| {
+                    ui.label("Cell size:");
+                    changed |= ui.add(egui::DragValue::new(&mut settings.cell_size).clamp_range(4..=5000).suffix(" px")).changed();
+                });
+            }
+        }
+        ui.horizontal(|ui| {
+            ui.label("Line color:");
+            changed |= ui.color_edit_button_srgb(&mut settings.color).changed();
+        });
+        changed |= ui.add(egui::Slider::new(&mut settings.opacity, 0.1..=1.0).text("Opacity")).changed();
+        changed |= ui.checkbox(&mut settings.thirds, "Rule-of-thirds lines").changed();
+        changed |= ui.checkbox(&mut settings.diagonals, "Diagonals").changed();
+        changed
+    }
+
+    /// Starts keeping how images are turned, from the current one on, or forgets
+    /// all of it.
+    fn remember_rotation_changed(&mut self) {
+        if self.settings.display.remember_rotation {
+            if let Some(path) = self.current_path.clone().filter(|_| self.view.quarter_turns() != 0) {
+                self.image_rotations.insert(path, self.view.quarter_turns());
+            }
+        } else {
+            self.image_rotations.clear();
+        }
+    }
+
+    /// Bindings are saved as soon as they're recorded, so this never has
+    /// anything left to save.
+    fn hotkey_settings(&mut self, ui: &mut egui::Ui) -> bool {
+        ui.heading("Global Hotkeys");
+        ui.label("These work while another app has focus.");
+
+        if let Some(command) = self.recording_hotkey {
+            match pressed_combination(ui) {
+                Some((egui::Key::Escape, _)) => self.recording_hotkey = None,
+                // A bare key would be swallowed in every other app.
+                Some((_, modifiers)) if !modifiers.ctrl && !modifiers.alt => {
+                    self.hotkey_record_hint = Some("Hold Ctrl or Alt with the key.".to_string());
+                }
+                Some((key, modifiers)) => {
+                    self.hotkey_bindings.insert(command, Binding::new(modifiers, key));
+                    self.recording_hotkey = None;
+                    self.shortcuts_changed();
+                    self.save_config();
+                }
+                None => {}
+            }
+            if self.recording_hotkey.is_none() {
+                self.hotkey_record_hint = None;
+                self.hotkeys.rebind(&self.hotkey_bindings);
+            }
+        }
+
+        let recording = self.recording_hotkey.is_some() || self.recording_shortcut.is_some();
+        egui::Grid::new("hotkeys").num_columns(3).show(ui, |ui| {
+            for command in Command::ALL {
+                ui.label(command.label());
+                let text = if self.recording_hotkey == Some(command) {
+                    "Press keys...".to_string()
+                } else {
+                    self.hotkey_label(command)
+                };
+                if ui.button(text).clicked() && !recording {
+                    self.recording_hotkey = Some(command);
+                    // Registered combinations never reach the viewer, so they're
+                    // released while recording.
+                    self.hotkeys.rebind(&BTreeMap::new());
+                }
+                match self.hotkeys.error(command).filter(|_| self.recording_hotkey.is_none()) {
+                    Some(error) => ui.colored_label(ui.visuals().error_fg_color, error),
+                    None => ui.label(""),
+                };
+                ui.end_row();
+            }
+        });
+
+        if let Some(hint) = &self.hotkey_record_hint {
+            ui.colored_label(ui.visuals().warn_fg_color, hint);
+        } else if self.recording_hotkey.is_some() {
+            ui.small("Press the new combination, or Escape to cancel.");
+        }
+
+        ui.separator();
+        ui.heading("Keyboard Shortcuts");
+        ui.label("These work while the viewer has focus. Press F1 or ? to see them all.");
+
+        if let Some(action) = self.recording_shortcut {
+            match pressed_combination(ui) {
+                Some((egui::Key::Escape, _)) => self.recording_shortcut = None,
+                Some((key, modifiers)) => {
+                    self.shortcuts.insert(action, Binding::new(modifiers, key));
+                    self.recording_shortcut = None;
+                    self.shortcuts_changed();
+                    self.save_config();
+                }
+                None => {}
+            }
+        }
+
+        egui::Grid::new("shortcuts").num_columns(3).show(ui, |ui| {
+            for action in Action::ALL {
+                ui.label(action.label());
+                let text = if self.recording_shortcut == Some(action) {
+                    "Press keys...".to_string()
+                } else {
+                    self.shortcut_label(action)
+                };
+                if ui.button(text).clicked() && !recording {
+                    self.recording_shortcut = Some(action);
+                }
+                match self.shortcut_conflicts.get(&action).filter(|_| self.recording_shortcut.is_none()) {
+                    Some(conflict) => ui.colored_label(ui.visuals().error_fg_color, conflict),
+                    None => ui.label(""),
+                };
+                ui.end_row();
+            }
+        });
+
+        if self.recording_shortcut.is_some() {
+            ui.small("Press the new keys, or Escape to cancel.");
+        }
+
+        ui.separator();
+        ui.heading("Touch Gestures");
+        ui.label("For touch screens. Pinch to zoom; while zoomed in, a finger drags the image around instead of swiping.");
+        let mut changed = false;
+        egui::Grid::new("gestures").num_columns(2).show(ui, |ui| {
+            for gesture in Gesture::ALL {
+                ui.label(gesture.label());
+                let mut action = self.gestures.get(&gesture).copied();
+                egui::ComboBox::from_id_source(gesture)
+                    .selected_text(action.map_or("Nothing", Action::label))
+                    .show_ui(ui, |ui| {
+                        changed |= ui.selectable_value(&mut action, None, "Nothing").changed();
+                        for option in Action::ALL {
+                            changed |= ui.selectable_value(&mut action, Some(option), option.label()).changed();
+                        }
+                    });
+                match action {
+                    Some(action) => self.gestures.insert(gesture, action),
+                    None => self.gestures.remove(&gesture),
+                };
+                ui.end_row();
+            }
+        });
+        changed
+    }
+
+    /// A cheat sheet of every shortcut, opened with F1 or "?".
+    fn shortcuts_window(&mut self, ctx: &egui::Context) {
+        let mut open = true;
+        let mut change = false;
+        egui::Window::new("Keyboard Shortcuts")
+            .open(&mut open)
+            .collapsible(false)
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .show(ctx, |ui| {
+                egui::Grid::new("shortcut_sheet").num_columns(2).striped(true).show(ui, |ui| {
+                    for action in Action::ALL {
+                        ui.label(action.label());
+                        let keys = egui::RichText::new(self.shortcut_label(action)).monospace();
+                        match self.shortcut_conflicts.get(&action) {
+                            Some(conflict) => ui.label(keys.strikethrough()).on_hover_text(conflict),
+                            None => ui.label(keys),
+                        };
+                        ui.end_row();
+                    }
+                    for (what, keys) in [
+                        ("Leave fullscreen, close this list", "Escape"),
+                        ("Zoom", "Ctrl+scroll"),
+                        ("Window opacity", "Ctrl+scroll over the timer"),
+                    ] {
+                        ui.label(what);
+                        ui.label(egui::RichText::new(keys).monospace());
+                        ui.end_row();
+                    }
+                });
+
+                ui.separator();
+                ui.label("While another app has focus:");
+                egui::Grid::new("hotkey_sheet").num_columns(2).striped(true).show(ui, |ui| {
+                    for command in Command::ALL {
+                        ui.label(command.label());
+                        ui.label(egui::RichText::new(self.hotkey_label(command)).monospace());
+                        ui.end_row();
+                    }
+                });
+
+                ui.separator();
+                change = ui.button("Change Shortcuts...").clicked();
+            });
+        if change {
+            self.open_settings(settings::Tab::Hotkeys);
+        }
+        self.show_shortcuts = open && !change;
+    }
+
+    /// The right-click menu, opened over the image, the thumbnails, the timer or
+    /// any empty part of the window. Escape or a click elsewhere closes it.
+    fn context_menu(&mut self, ui: &mut egui::Ui, ctx: &egui::Context, frame: &eframe::Frame) {
+        self.context_menu_open = true;
+
+        ui.menu_button(tr("Navigation"), |ui| {
+            if ui.button(self.with_shortcut(tr("Next Image"), Action::NextImage)).clicked() {
+                self.next_image(ctx);
+                ui.close_menu();
+            }
+            if ui.button(self.with_shortcut(tr("Previous Image"), Action::PreviousImage)).clicked() {
+                self.previous_image(ctx);
+                ui.close_menu();
+            }
+            let mut browse = self.show_browser;
+            if ui.checkbox(&mut browse, self.with_shortcut(tr("Browse Images"), Action::ToggleBrowser)).changed() {
+                self.show_browser = browse;
+                self.browser_scrolled_to = None;
+                ui.close_menu();
+            }
+            ui.menu_button(tr("Reference Board"), |ui| {
+                for layout in BoardLayout::ALL {
+                    if ui.radio_value(&mut self.settings.display.board.layout, layout, layout.label()).changed() {
+                        self.last_size = None;
+                        self.save_config();
+                    }
+                }
+            });
+        });
+
+        ui.menu_button(tr("Folders"), |ui| {
+            if ui.button(tr("Folder Manager...")).clicked() {
+                self.show_folder_manager = true;
+                ui.close_menu();
+            }
+
+            let add_folder = ui.button(tr("Add Folder...")).on_hover_text(tr("Hold Shift to add it for this session only."));
+            if add_folder.clicked() {
+                ui.close_menu();
+                let temporary = ui.input(|i| i.modifiers.shift);
+                if let Some(new_folder) = FileDialog::new().set_title(tr("Add Folder")).pick_folder() {
+                    self.add_folder(new_folder, temporary);
+                    self.save_config();
+                }
+            }
+
+            ui.add_enabled_ui(!self.recent_folders.is_empty(), |ui| {
+                ui.menu_button(tr("Recent Folders"), |ui| {
+                    let mut add = None;
+                    let mut forget = None;
+                    for folder in &self.recent_folders {
+                        let label = folder.display().to_string();
+                        if folder.is_dir() {
+                            if ui.button(label).clicked() {
+                                add = Some(folder.clone());
+                            }
+                        } else {
+                            ui.horizontal(|ui| {
+                                ui.add_enabled(false, egui::Button::new(label))
+                                    .on_disabled_hover_text(tr("This folder no longer exists."));
+                                if ui.small_button(tr("Remove from list")).clicked() {
+                                    forget = Some(folder.clone());
+                                }
+                            });
+                        }
+                    }
+
+                    if let Some(folder) = add {
+                        self.add_folder(folder, false);
+                        self.save_config();
+                        ui.close_menu();
+                    }
+                    if let Some(folder) = forget {
+                        self.recent_folders.retain(|f| *f != folder);
+                        self.save_config();
+                    }
+                });
+            });
+        });
+
+        ui.menu_button(tr("Timer"), |ui| {
+            let label = self.with_shortcut(tr("Pause Timer"), Action::TogglePause);
+            ui.checkbox(&mut self.timer_paused, label);
+            let mut muted = self.settings.timer.muted;
+            if ui.checkbox(&mut muted, self.with_shortcut(tr("Mute Alarms"), Action::ToggleMute)).changed() {
+                self.toggle_mute();
+            }
+            ui.separator();
+            if ui.button(tr("Set Alarm...")).clicked() {
+                // Re-check sound files in case they were moved since the last visit.
+                self.sounds.results.clear();
+                self.show_alarm_config = true;
+                self.save_config();
+                ui.close_menu();
+            }
+            if ui.button(tr("Track EXE...")).clicked() {
+                ui.close_menu();
+                if let Some(path) = FileDialog::new().add_filter("EXE", &["exe"]).pick_file() {
+                    if let Some(name) = path.file_name().and_then(|s| s.to_str()) {
+                        self.target_exe_name = Some(name.to_lowercase());
+                        self.save_config();
+                    }
+                }
+            }
+            if ui.button(tr("Statistics...")).clicked() {
+                self.show_stats = true;
+                ui.close_menu();
+            }
+            if ui.button(tr("Export Session Log...")).clicked() {
+                ui.close_menu();
+                self.export_session_log();
+            }
+        });
+
+        ui.menu_button(tr("Display"), |ui| {
+            if self.current_image.is_some() {
+                ui.menu_button(tr("Rotate"), |ui| {
+                    if ui.button(self.with_shortcut(tr("Clockwise"), Action::RotateClockwise)).clicked() {
+                        self.rotate_image(true);
+                        ui.close_menu();
+                    }
+                    if ui.button(self.with_shortcut(tr("Counter-Clockwise"), Action::RotateCounterClockwise)).clicked() {
+                        self.rotate_image(false);
+                        ui.close_menu();
+                    }
+                    ui.separator();
+                    let remember = ui
+                        .checkbox(&mut self.settings.display.remember_rotation, tr("Remember for Each Image"))
+                        .on_hover_text(tr("Show an image turned the way you left it the next time it comes up."));
+                    if remember.changed() {
+                        self.remember_rotation_changed();
+                        self.save_config();
+                    }
+                });
+
+                ui.menu_button(tr("Flip"), |ui| {
+                    let (mut flip_x, mut flip_y) = self.view.flips();
+                    if ui.checkbox(&mut flip_x, self.with_shortcut(tr("Horizontally"), Action::FlipHorizontally)).changed() {
+                        self.view.flip(true);
+                    }
+                    if ui.checkbox(&mut flip_y, self.with_shortcut(tr("Vertically"), Action::FlipVertically)).changed() {
+                        self.view.flip(false);
+                    }
+                    ui.separator();
+                    if ui
+                        .checkbox(&mut self.settings.display.sticky_flip, tr("Keep for Next Images"))
+                        .on_hover_text(tr("Stay flipped when moving on, instead of showing each new image as it is."))
+                        .changed()
+                    {
+                        self.save_config();
+                    }
+                });
+
+                ui.menu_button(tr("Grayscale"), |ui| {
+                    let label = self.with_shortcut(tr("Show in Grayscale"), Action::ToggleGrayscale);
+                    ui.checkbox(&mut self.grayscale, label);
+                    ui.separator();
+                    for filter in posterize::ValueFilter::ALL {
+                        ui.radio_value(&mut self.value_filter, filter, filter.label());
+                    }
+                    ui.separator();
+                    if ui
+                        .checkbox(&mut self.settings.display.sticky_grayscale, tr("Keep for Next Images"))
+                        .on_hover_text(tr("Stay in grayscale or the same values for a whole value study."))
+                        .changed()
+                    {
+                        self.save_config();
+                    }
+                });
+
+                ui.checkbox(&mut self.sharp_pixels, tr("Sharp Pixels"))
+                    .on_hover_text(tr("Draws every image this session without smoothing, for pixel art."));
+
+                if ui.button(tr("Grid...")).clicked() {
+                    self.show_grid_settings = true;
+                    ui.close_menu();
+                }
+
+                let label = self.with_shortcut(tr("Eyedropper"), Action::ToggleEyedropper);
+                let mut picking = self.eyedropper.active;
+                if ui
+                    .checkbox(&mut picking, label)
+                    .on_hover_text(tr("Click the image to copy a color's hex code. Escape stops."))
+                    .changed()
+                {
+                    self.set_eyedropper(picking);
+                }
+
+                let label = self.with_shortcut(tr("Measure"), Action::ToggleMeasure);
+                let mut measuring = self.measure.active;
+                if ui
+                    .checkbox(&mut measuring, label)
+                    .on_hover_text(tr("Drag lines across the image to compare their lengths and angles."))
+                    .changed()
+                {
+                    self.set_measuring(measuring);
+                }
+
+                ui.menu_button(tr("Crop"), |ui| {
+                    let label = self.with_shortcut(tr("Crop to Region"), Action::ToggleCrop);
+                    let mut cropping = self.crop_tool.active;
+                    if ui
+                        .checkbox(&mut cropping, label)
+                        .on_hover_text(tr("Drag a rectangle on the image to show only that part of it. It's remembered for this image."))
+                        .changed()
+                    {
+                        self.set_cropping(cropping);
+                        ui.close_menu();
+                    }
+                    let label = self.with_shortcut(tr("Show Whole Image"), Action::ClearCrop);
+                    if ui.add_enabled(self.view.crop().is_some(), egui::Button::new(label)).clicked() {
+                        self.clear_crop();
+                        ui.close_menu();
+                    }
+                });
+
+                let label = self.with_shortcut(tr("Histogram"), Action::ToggleHistogram);
+                if ui
+                    .checkbox(&mut self.settings.display.histogram.enabled, label)
+                    .on_hover_text(tr("How the image's tones are spread, for checking values."))
+                    .changed()
+                {
+                    self.config_dirty = true;
+                }
+
+                if ui.button(tr("Image Info...")).clicked() {
+                    self.show_image_info = true;
+                    ui.close_menu();
+                }
+
+                ui.menu_button(tr("File Info"), |ui| {
+                    let mut changed = false;
+                    let label = self.with_shortcut(tr("Show File Name"), Action::ToggleInfoBar);
+                    if ui.checkbox(&mut self.settings.display.info_bar.enabled, label).changed() {
+                        self.info_bar_shown_at = Instant::now();
+                        changed = true;
+                    }
+                    ui.separator();
+                    for position in info_bar::Position::ALL {
+                        changed |= ui.radio_value(&mut self.settings.display.info_bar.position, position, position.label()).changed();
+                    }
+                    ui.separator();
+                    ui.horizontal(|ui| {
+                        changed |= ui.checkbox(&mut self.settings.display.info_bar.auto_hide, tr("Hide After")).changed();
+                        changed |= ui
+                            .add(egui::DragValue::new(&mut self.settings.display.info_bar.hide_after_seconds).clamp_range(1..=60).suffix(" s"))
+                            .changed();
+                    });
+                    if changed {
+                        self.info_bar_shown_at = Instant::now();
+                        self.config_dirty = true;
+                    }
+                });
+                ui.separator();
+            }
+
+            let mut hide_hud = self.settings.display.hide_hud;
+            if ui
+                .checkbox(&mut hide_hud, self.with_shortcut(tr("Hide Timer and Overlays"), Action::ToggleHud))
+                .on_hover_text(tr("A clean image for screenshots. The timer keeps running."))
+                .changed()
+            {
+                self.toggle_hud();
+            }
+
+            if ui
+                .checkbox(&mut self.settings.display.second_view.open, tr("Second Window"))
+                .on_hover_text(tr("The reference in a window of its own, for another monitor."))
+                .changed()
+            {
+                self.save_config();
+            }
+
+            ui.menu_button(tr("Pin to Top"), |ui| {
+                for mode in PinMode::ALL {
+                    if ui.radio_value(&mut self.pin_mode, mode, mode.label()).changed() {
+                        self.pin_state_changed = true;
+                        self.save_config();
+                    }
+                }
+            });
+
+            let borderless = ui
+                .checkbox(&mut self.settings.display.borderless, tr("Borderless"))
+                .on_hover_text(tr("No title bar. Drag the image to move the window."));
+            if borderless.changed() {
+                self.save_config();
+            }
+            if !self.settings.display.borderless && ui.checkbox(&mut self.settings.display.auto_hide_title_bar, tr("Hide Title Bar When Away")).changed() {
+                self.save_config();
+            }
+
+            let keys = self.hotkey_label(Command::ToggleClickThrough);
+            let click_through = ui
+                .add_enabled(
+                    self.hotkeys.is_registered(Command::ToggleClickThrough),
+                    egui::Button::new(tr("Click-Through")),
+                )
+                .on_hover_text(tr_args("Let the mouse pass through to the app underneath. {keys} turns it back off.", &[("keys", &keys)]))
+                .on_disabled_hover_text(tr_args("Unavailable: the {keys} hotkey couldn't be registered.", &[("keys", &keys)]));
+            if click_through.clicked() {
+                self.set_click_through(ctx, frame, true);
+                ui.close_menu();
+            }
+
+            let mut percent = self.settings.display.window_opacity_percent;
+            let slider = ui
+                .add(egui::Slider::new(&mut percent, MIN_WINDOW_OPACITY_PERCENT..=100).text(tr("Opacity (%)")))
+                .on_hover_text(tr("Ctrl+scroll over the timer changes it too."));
+            if slider.changed() {
+                self.set_window_opacity(ctx, frame, percent);
+            }
+            ui.separator();
+
+            ui.menu_button(self.with_shortcut(tr("Image Size"), Action::CycleFitMode), |ui| {
+                for mode in FitMode::ALL {
+                    if ui.radio_value(&mut self.settings.display.fit_mode, mode, mode.label()).changed() {
+                        self.last_size = None;
+                        self.save_config();
+                    }
+                }
+            });
+
+            ui.menu_button(tr("Window Size"), |ui| {
+                for fit in ImageFit::ALL {
+                    if ui.radio_value(&mut self.settings.display.image_fit, fit, fit.label()).changed() {
+                        self.last_size = None;
+                        self.save_config();
+                    }
+                }
+            });
+
+            ui.menu_button(tr("Fullscreen"), |ui| {
+                let mut fullscreen = self.fullscreen;
+                if ui.checkbox(&mut fullscreen, self.with_shortcut(tr("Fullscreen"), Action::ToggleFullscreen)).changed() {
+                    self.set_fullscreen(ctx, fullscreen);
+                    ui.close_menu();
+                }
+                let monitors = placement::monitor_centers();
+                if !self.fullscreen && monitors.len() > 1 {
+                    for (i, center) in monitors.into_iter().enumerate() {
+                        if ui.button(tr_args("On Monitor {number}", &[("number", &(i + 1))])).clicked() {
+                            self.fullscreen_on_monitor(ctx, center);
+                            ui.close_menu();
+                        }
+                    }
+                }
+                ui.separator();
+                ui.label(tr("Timer while fullscreen:"));
+                for mode in FullscreenTimer::ALL {
+                    if ui.radio_value(&mut self.settings.display.fullscreen_timer, mode, mode.label()).changed() {
+                        self.save_config();
+                    }
+                }
+            });
+        });
+
+        ui.separator();
+
+        ui.menu_button(tr("Profiles"), |ui| {
+            let mut activate = None;
+            for (i, profile) in self.profiles.iter().enumerate() {
+                if ui.radio(self.active_profile == Some(i), &profile.name).clicked() {
+                    activate = Some(i);
+                }
+            }
+            if let Some(i) = activate.filter(|&i| self.active_profile != Some(i)) {
+                self.activate_profile(ctx, i, true);
+                ui.close_menu();
+            }
+            if !self.profiles.is_empty() {
+                ui.separator();
+            }
+
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.new_profile_name)
+                        .hint_text(tr("New profile"))
+                        .desired_width(100.0),
+                );
+                let name = self.new_profile_name.trim().to_string();
+                if ui.add_enabled(self.profile_name_free(&name), egui::Button::new(tr("Create"))).clicked() {
+                    self.create_profile(name);
+                    self.new_profile_name.clear();
+                    ui.close_menu();
+                }
+            });
+
+            if let Some(active) = self.active_profile {
+                if ui.button(tr_args("Delete '{name}'", &[("name", &self.profiles[active].name)])).clicked() {
+                    self.remove_profile(active);
+                    ui.close_menu();
+                }
+            }
+
+            if ui.button(tr("Manage Profiles...")).clicked() {
+                self.show_profiles = true;
+                ui.close_menu();
+            }
+        });
+
+        if ui.button(tr("Settings...")).clicked() {
+            self.show_settings = true;
+            ui.close_menu();
+        }
+
+        if ui.button(tr("Export / Import Settings...")).clicked() {
+            self.show_settings_transfer = true;
+            ui.close_menu();
+        }
+
+        if ui.button(tr("Reset Settings...")).clicked() {
+            self.show_reset = true;
+            ui.close_menu();
+        }
+
+        ui.separator();
+
+        if ui.button(tr("About...")).clicked() {
+            self.show_about = true;
+            ui.close_menu();
+        }
+    }
+
+    /// Hides the cursor once it's been left still over the image, or anywhere
+    /// in the window outside the timer, while no window or menu is up.
+    fn hide_idle_cursor(&self, ctx: &egui::Context) {
+        let (Some(after), Some(still)) = (self.settings.display.hide_cursor_after_seconds, self.pointer.still_for()) else {
+            return;
+        };
+        let over_panel = ctx.pointer_hover_pos().is_some_and(|pos| ctx.layer_id_at(pos) == Some(egui::LayerId::background()));
+        let own = [egui::Id::new("timer_overlay"), egui::Id::new("notice")];
+        let dialog_open = ctx.memory(|m| {
+            m.any_popup_open()
+                || m.areas().visible_layer_ids().iter().any(|layer| {
+                    matches!(layer.order, egui::Order::Middle | egui::Order::Foreground) && !own.contains(&layer.id)
+                })
+        });
+        if !over_panel || dialog_open {
+            return;
+        }
+        let after = Duration::from_secs(after);
+        if still >= after {
+            ctx.set_cursor_icon(egui::CursorIcon::None);
+        } else {
+            ctx.request_repaint_after(after - still);
+        }
+    }
+
+    /// Sets egui's visuals for the chosen theme and accent when they, or the
+    /// system theme, change. eframe puts its own visuals back whenever the
+    /// system theme changes, so that needs them set again in any theme.
+    fn update_theme(&mut self, ctx: &egui::Context, frame: &eframe::Frame) {
+        let system = frame.info().system_theme;
+        let display = &self.settings.display;
+        let wanted = (display.theme, display.accent_color, system);
+        if self.applied_theme != Some(wanted) {
+            ctx.set_visuals(theme::visuals(display.theme.is_dark(system), display.accent_color));
+            self.applied_theme = Some(wanted);
+        }
+    }
+
+    fn set_ui_scale(&mut self, percent: u16) {
+        let percent = percent.clamp(MIN_UI_SCALE_PERCENT, MAX_UI_SCALE_PERCENT);
+        if percent != self.settings.display.ui_scale_percent {
+            self.settings.display.ui_scale_percent = percent;
+            self.toast_for(Level::Info, tr_args("Menu size: {percent}%", &[("percent", &percent)]), Duration::from_millis(1500));
+            self.config_dirty = true;
+        }
+    }
+
+    /// The smallest inner size of the window, in points.
+    fn min_window_size(&self) -> egui::Vec2 {
+        let [width, height] = self.settings.display.min_window_size.map(|side| side.max(MIN_WINDOW_SIDE) as f32);
+        egui::vec2(width, height)
+    }
+
+    /// Keeps the system from letting the window be resized below the minimum.
+    fn update_min_window_size(&mut self, ctx: &egui::Context) {
+        let min = self.min_window_size();
+        if self.applied_min_window_size != Some(min) {
+            ctx.send_viewport_cmd(egui::ViewportCommand::MinInnerSize(min));
+            self.applied_min_window_size = Some(min);
+        }
+    }
+
+    /// The room the image gets in a window sized to it, fitting within `room`.
+    fn window_size_for(&self, shown: egui::Vec2, room: egui::Vec2, pixels_per_point: f32) -> egui::Vec2 {
+        let fit_mode = self.settings.display.fit_mode;
+        let layout = self.settings.display.board.layout;
+        // Filling or stretching to a window sized to the image is fitting it.
+        let window_fit = if fit_mode == FitMode::ActualPixels { fit_mode } else { FitMode::Fit };
+        // On the board, sized so the first image fits its slot.
+        let (wanted, _) = window_fit.layout(shown, room / layout.grid(), pixels_per_point);
+        wanted * layout.grid()
+    }
+
+    /// Pulls the window flush with the screen edges it was let go near. Only a
+    /// move counts, not a resize, and nothing happens until the mouse is up.
+    fn snap_window(&mut self, ctx: &egui::Context) {
+        let maximized = ctx.input(|i| i.viewport().maximized == Some(true));
+        let rect = ctx.input(|i| i.viewport().outer_rect);
+        let (Some(rect), true) = (rect, self.settings.display.snap.enabled && !self.fullscreen && !maximized) else {
+            self.snapped_rect = None;
+            return;
+        };
+        let Some(settled) = self.snapped_rect.filter(|&settled| settled != rect) else {
+            self.snapped_rect = Some(rect);
+            return;
+        };
+        if ctx.input(|i| i.pointer.any_down()) || snap::mouse_held() {
+            ctx.request_repaint_after(Duration::from_millis(100));
+            return;
+        }
+        self.snapped_rect = Some(rect);
+        if (settled.size() - rect.size()).length_sq() > 1.0 {
+            return;
+        }
+        let distance = self.settings.display.snap.distance;
+        if let Some(position) = placement::work_rect(ctx).and_then(|work| snap::snapped(rect, work, distance)) {
+            ctx.send_viewport_cmd(egui::ViewportCommand::OuterPosition(position));
+        }
+    }
+
+    /// Moves the window to `spot` on its screen, sized to the share of it set for
+    /// throws. A window sized to its image keeps the image's shape within that.
+    fn throw_window(&mut self, ctx: &egui::Context, spot: Spot) {
+        if self.fullscreen {
+            return;
+        }
+        let rects = ctx.input(|i| Some((i.viewport().inner_rect?, i.viewport().outer_rect?)));
+        let (Some((inner, outer)), Some(work)) = (rects, placement::work_rect(ctx)) else {
+            return;
+        };
+        let decorations = outer.size() - inner.size();
+        let share = self.settings.display.snap.throw_size_percent as f32 / 100.0;
+        let mut size = (work.size() * share - decorations).max(self.min_window_size());
+        let image = self.current_image.as_ref().filter(|_| !self.show_browser);
+        if let (ImageFit::ResizeWindow, Some(img)) = (self.settings.display.image_fit, image) {
+            let shown = self.view.shown_size(img.width() as f32, img.height() as f32);
+            let margin = frame_margin(ctx);
+            let wanted = self.window_size_for(shown, size - margin, ctx.pixels_per_point()).max(self.min_window_size() - margin);
+            size = wanted + margin;
+            self.last_size = Some(wanted);
+        }
+        ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(size));
+        ctx.send_viewport_cmd(egui::ViewportCommand::OuterPosition(spot.position(size + decorations, work)));
+        self.snapped_rect = None;
+    }
+
+    /// Scales menus and windows as set, relative to the system's DPI scaling.
+    /// A change waits until the mouse is let go, so the slider setting it
+    /// doesn't move out from under the pointer while being dragged.
+    fn update_ui_scale(&self, ctx: &egui::Context) {
+        let zoom = self.settings.display.ui_scale_percent as f32 / 100.0;
+        if ctx.zoom_factor() != zoom && !ctx.input(|i| i.pointer.any_down()) {
+            ctx.set_zoom_factor(zoom);
+        }
+    }
+
+    /// Shows or hides the title bar as borderless mode and auto-hide ask. It's
+    /// left alone while the window is being dragged or resized, and for a moment
+    /// after each change, so it doesn't vanish from under the pointer.
+    fn update_decorations(&mut self, ctx: &egui::Context) {
+        let hide_after = Duration::from_secs(2);
+        let want_visible = if self.settings.display.borderless {
+            false
+        } else {
+            !self.settings.display.auto_hide_title_bar || self.pointer.away_for() < hide_after
+        };
+        if want_visible == self.decorations_visible || self.fullscreen {
+            return;
+        }
+
+        let settle = Duration::from_millis(500);
+        let since_toggle = self.decorations_toggled_at.map_or(settle, |t| t.elapsed());
+        let moving = self.placement_changed_at.is_some() || ctx.input(|i| i.pointer.any_down());
+        if moving || since_toggle < settle {
+            ctx.request_repaint_after(settle.saturating_sub(since_toggle).max(Duration::from_millis(100)));
+            return;
+        }
+
+        // The frame grows or shrinks around the content, which stays put on screen.
+        let rects = ctx.input(|i| Some((i.viewport().inner_rect?, i.viewport().outer_rect?)));
+        ctx.send_viewport_cmd(egui::ViewportCommand::Decorations(want_visible));
+        if let Some((inner, outer)) = rects {
+            let position = if want_visible {
+                inner.min - self.frame_offset
+            } else {
+                self.frame_offset = inner.min - outer.min;
+                inner.min
+            };
+            ctx.send_viewport_cmd(egui::ViewportCommand::OuterPosition(position));
+            ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(inner.size()));
+        }
+        self.decorations_visible = want_visible;
+        self.decorations_toggled_at = Some(Instant::now());
+    }
+
+    /// Fills the monitor with the image, or puts the window back where it was.
+    fn set_fullscreen(&mut self, ctx: &egui::Context, on: bool) {
+        if on == self.fullscreen {
+            return;
+        }
+        self.fullscreen = on;
+        if on {
+            self.placement_before_fullscreen = self.window_placement;
+        }
+        ctx.send_viewport_cmd(egui::ViewportCommand::Fullscreen(on));
+        if !on {
+            if let Some(placement) = self.placement_before_fullscreen.take() {
+                let scale = ctx.pixels_per_point();
+                ctx.send_viewport_cmd(egui::ViewportCommand::OuterPosition(egui::pos2(
+                    placement.x as f32 / scale,
+                    placement.y as f32 / scale,
+                )));
+                ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(egui::vec2(
+                    placement.width as f32 / scale,
+                    placement.height as f32 / scale,
+                )));
+            }
+            // A pinned window can come out of fullscreen stuck above the taskbar
+            // until its level is set again.
+            self.pin_state_changed = true;
+        }
+    }
+
+    /// Moves the window onto the monitor around `center`, in physical pixels, and
+    /// goes fullscreen there.
+    fn fullscreen_on_monitor(&mut self, ctx: &egui::Context, center: (i32, i32)) {
+        let scale = ctx.pixels_per_point();
+        ctx.send_viewport_cmd(egui::ViewportCommand::OuterPosition(egui::pos2(
+            center.0 as f32 / scale,
+            center.1 as f32 / scale,
+        )));
+        self.set_fullscreen(ctx, true);
+    }
+
+    /// Lets mouse input pass through the viewer to the app underneath. Refused when
+    /// the hotkey to turn it back off couldn't be registered.
+    fn set_click_through(&mut self, ctx: &egui::Context, frame: &eframe::Frame, on: bool) {
+        if on && !self.hotkeys.is_registered(Command::ToggleClickThrough) {
+            self.toast(Level::Warning, tr_args(
+                "Click-through needs the {keys} hotkey, which couldn't be registered.",
+                &[("keys", &self.hotkey_label(Command::ToggleClickThrough))],
+            ));
+            return;
+        }
+        self.click_through = on;
+        self.apply_hover_fade(ctx, frame, self.hover_faded);
+    }
+
+    /// Carries out what was picked from the tray icon, which only exists on Windows.
+    #[cfg(windows)]
+    fn run_tray_commands(&mut self, ctx: &egui::Context, frame: &eframe::Frame) {
+        let tray_commands = self.tray.as_ref().map(tray::Tray::drain).unwrap_or_default();
+        for command in tray_commands {
+            match command {
+                tray::TrayCommand::ToggleViewer => self.set_in_tray(ctx, frame, !self.in_tray),
+                tray::TrayCommand::TogglePause => self.timer_paused = !self.timer_paused,
+                tray::TrayCommand::NextImage => self.next_image(ctx),
+                tray::TrayCommand::Quit => {
+                    self.quit_requested = true;
+                    if self.in_tray {
+                        // The exit summary may still need to be seen.
+                        self.set_in_tray(ctx, frame, false);
+                    }
+                    ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+                }
+            }
+        }
+    }
+
+    /// Hides the viewer to the tray icon or brings it back.
+    fn set_in_tray(&mut self, ctx: &egui::Context, frame: &eframe::Frame, hidden: bool) {
+        if hidden && !opacity::set_taskbar_button(frame, false) {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(true));
+            return;
+        }
+        self.in_tray = hidden;
+        self.apply_hover_fade(ctx, frame, self.hover_faded);
+        if !hidden {
+            opacity::set_taskbar_button(frame, true);
+            ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+        }
+    }
+
+    fn hotkey_label(&self, command: Command) -> String {
+        self.hotkey_bindings.get(&command).map(Binding::label).unwrap_or_default()
+    }
+
+    fn shortcut_label(&self, action: Action) -> String {
+        self.shortcuts.get(&action).map(Binding::label).unwrap_or_default()
+    }
+
+    /// `text` followed by the shortcut for `action`, for menu entries.
+    fn with_shortcut(&self, text: &str, action: Action) -> String {
+        match self.shortcuts.get(&action).filter(|_| !self.shortcut_conflicts.contains_key(&action)) {
+            Some(binding) => format!("{} ({})", text, binding.label()),
+            None => text.to_string(),
+        }
+    }
+
+    /// Works out again which shortcuts clash, after they or the global hotkeys change.
+    fn shortcuts_changed(&mut self) {
+        self.shortcut_conflicts = shortcut::conflicts(&self.shortcuts, &self.hotkey_bindings);
+    }
+
+    /// Does what a keyboard shortcut is bound to.
+    fn run_shortcut(&mut self, ctx: &egui::Context, action: Action) {
+        match action {
+            Action::NextImage => self.next_image_under_pointer(ctx),
+            Action::PreviousImage => self.previous_image(ctx),
+            Action::ToggleBrowser => {
+                self.show_browser = !self.show_browser;
+                self.browser_scrolled_to = None;
+            }
+            Action::TogglePause => self.timer_paused = !self.timer_paused,
+            Action::ToggleMute => self.toggle_mute(),
+            Action::AddMinute => self.adjust_elapsed(60),
+            Action::SubtractMinute => self.adjust_elapsed(-60),
+            Action::AddFiveMinutes => self.adjust_elapsed(300),
+            Action::SubtractFiveMinutes => self.adjust_elapsed(-300),
+            Action::RotateClockwise | Action::RotateCounterClockwise => {
+                if self.current_image.is_some() {
+                    self.rotate_image(action == Action::RotateClockwise);
+                }
+            }
+            Action::FlipHorizontally => self.view.flip(true),
+            Action::FlipVertically => self.view.flip(false),
+            Action::ToggleGrayscale => self.grayscale = !self.grayscale,
+            Action::CycleValueFilter => self.cycle_value_filter(),
+            Action::ToggleEyedropper => self.set_eyedropper(!self.eyedropper.active),
+            Action::ToggleMeasure => self.set_measuring(!self.measure.active),
+            Action::ToggleCrop => self.set_cropping(!self.crop_tool.active),
+            Action::ClearCrop => self.clear_crop(),
+            Action::ToggleGrid => {
+                self.settings.display.grid.enabled = !self.settings.display.grid.enabled;
+                self.config_dirty = true;
+            }
+            Action::ToggleHistogram => {
+                self.settings.display.histogram.enabled = !self.settings.display.histogram.enabled;
+                self.config_dirty = true;
+            }
+            Action::ToggleInfoBar => {
+                self.settings.display.info_bar.enabled = !self.settings.display.info_bar.enabled;
+                self.info_bar_shown_at = Instant::now();
+                self.config_dirty = true;
+            }
+            Action::ToggleHud => self.toggle_hud(),
+            Action::ResetZoom => self.view.reset_zoom(),
+            Action::CycleFitMode => {
+                let mode = self.settings.display.fit_mode.next();
+                self.settings.display.fit_mode = mode;
+                self.last_size = None;
+                self.toast_for(Level::Info, tr_args("Image size: {mode}", &[("mode", &mode.label())]), Duration::from_millis(1500));
+                self.config_dirty = true;
+            }
+            Action::ToggleFullscreen => self.set_fullscreen(ctx, !self.fullscreen),
+            Action::UiScaleUp => self.set_ui_scale(self.settings.display.ui_scale_percent + UI_SCALE_STEP),
+            Action::UiScaleDown => self.set_ui_scale(self.settings.display.ui_scale_percent.saturating_sub(UI_SCALE_STEP)),
+            Action::UiScaleReset => self.set_ui_scale(100),
+            Action::ShowShortcuts => self.show_shortcuts = !self.show_shortcuts,
+        }
+    }
+
+    /// Silences a ringing alarm and the overtime escalation still to come for it.
+    /// The alarm itself stays fired.
+    fn dismiss_alarm(&mut self) {
+        audio::stop_all();
+        if self.overtime().is_some() {
+            self.overtime_escalated = true;
+        }
+    }
+
+    /// Sends the window level for the current pin mode. Following the target is
+    /// debounced, so alt-tabbing through it doesn't toggle the level on every press.
+    fn update_pin_level(&mut self, ctx: &egui::Context) {
+        use egui::WindowLevel;
+        let want_on_top = match self.pin_mode {
+            PinMode::Unpinned => false,
+            PinMode::Pinned => true,
+            PinMode::WhileTargetActive => self.target_is_active,
+        };
+
+        if !self.pin_state_changed && want_on_top != self.pinned_on_top {
+            let debounce = Duration::from_millis(500);
+            let waited = self.pin_pending_since.get_or_insert_with(Instant::now).elapsed();
+            if waited < debounce {
+                ctx.request_repaint_after(debounce - waited);
+                return;
+            }
+            self.pin_state_changed = true;
+        }
+        self.pin_pending_since = None;
+
+        if self.pin_state_changed {
+            let level = if want_on_top {
+                WindowLevel::AlwaysOnTop
+            } else {
+                WindowLevel::Normal
+            };
+            ctx.send_viewport_cmd(egui::ViewportCommand::WindowLevel(level));
+            self.pinned_on_top = want_on_top;
+            self.pin_state_changed = false;
+        }
+    }
+
+    /// Silences alarm sounds and speech. Alarms still fire and latch while muted,
+    /// so unmuting doesn't play anything that was missed.
+    fn toggle_mute(&mut self) {
+        self.settings.timer.muted = !self.settings.timer.muted;
+        self.save_config();
+    }
+
+    /// Speaks `phrase` if announcements are on. The speech thread is only started
+    /// the first time something is actually said.
+    fn announce(&mut self, phrase: &str, volume: f32) {
+        if self.settings.timer.speech.enabled && !self.settings.timer.muted && !phrase.trim().is_empty() {
+            self.speaker
+                .get_or_insert_with(speech::Speaker::new)
+                .say(phrase.to_string(), volume);
+        }
+    }
+
+    fn save_config(&mut self) {
+        self.last_config_save = Instant::now();
+        // Nothing is written over a config that failed to load until the user
+        // decides, so changes meanwhile go next to it.
+        let path = match self.config_error {
+            Some(_) => settings_file::pending_path(paths::config_path()),
+            None => paths::config_path().to_path_buf(),
+        };
+
+        let format = settings_file::Format::of(paths::config_path());
+        let previous = fs::read_to_string(&path).ok();
+        let mut config = self.config_data();
+        if self.settings.general.private_folders {
+            config.folder_map.clear();
+            config.recent_folders.clear();
+            config.current_index = 0;
+            config.image_rotations.clear();
+            config.image_crops.clear();
+            for profile in &mut config.profiles {
+                profile.folder_map.clear();
+            }
+        }
+        for folder in &self.temporary_folders {
+            config.folder_map.shift_remove(folder);
+            for profile in &mut config.profiles {
+                profile.folder_map.shift_remove(folder);
+            }
+        }
+        match settings_file::encode(format, &config, previous.as_deref()) {
+            Ok(text) => match settings_file::write(&path, text.as_bytes()) {
+                Ok(()) => self.config_dirty = false,
+                Err(e) => self.toast(Level::Error, tr_args("Couldn't save the settings: {error}", &[("error", &e)])),
+            },
+            Err(e) => self.toast(Level::Error, tr_args("Couldn't save the settings: {error}", &[("error", &e)])),
+        }
+        if self.settings.general.persist_deck && !self.settings.general.private_folders && self.config_error.is_none() {
+            if let Err(e) = deck::save(&self.image_paths, self.current_index) {
+                self.toast(Level::Error, tr_args("Couldn't save the shuffled order: {error}", &[("error", &e)]));
+            }
+        }
+    }
+
+    /// Writes state that changes too often to save each time, such as the image
+    /// index, once the save interval has passed. A crash loses at most that long.
+    fn maybe_save_config(&mut self) {
+        if self.config_dirty && self.last_config_save.elapsed() >= CONFIG_SAVE_INTERVAL {
+            self.save_config();
+        }
+    }
+
+    /// Moves the statistics into stats.sqlite or back into stats.json.
+    fn set_stats_database(&mut self, enabled: bool) {
+        match self.stats.set_database(enabled.then(paths::stats_database_path)) {
+            Ok(()) => self.settings.general.stats_database = enabled,
+            Err(e) => self.toast(Level::Error, tr_args("Couldn't open the statistics database: {error}", &[("error", &e)])),
+        }
+    }
+
+    /// Puts the areas in `scope` back to how a fresh install has them, once the
+    /// settings file has been copied aside.
+    fn reset_settings(&mut self, ctx: &egui::Context, scope: ResetScope) {
+        let path = paths::config_path();
+        let previous = fs::read_to_string(path).ok();
+        if let Ok(text) = settings_file::encode(settings_file::Format::of(path), &self.config_data(), previous.as_deref()) {
+            let backup = settings_file::reset_backup_path(path);
+            if let Err(e) = settings_file::write(&backup, text.as_bytes()) {
+                self.toast(Level::Error, tr_args("Nothing was reset, as the settings couldn't be backed up: {error}", &[("error", &e)]));
+                return;
+            }
+        }
+
+        if scope.folders {
+            self.folder_map.clear();
+            self.recent_folders.clear();
+            self.refresh_image_list();
+            self.current_index = 0;
+            self.current_image = None;
+            self.texture = None;
+            self.gray_texture = None;
+            self.letterbox = None;
+            self.board_slots.clear();
+        }
+        if scope.alarms {
+            self.alarms.clear();
+            self.rearm_alarms();
+        }
+        if scope.hotkeys {
+            self.hotkey_bindings = hotkey::default_bindings();
+            self.recording_hotkey = None;
+            self.hotkey_record_hint = None;
+            self.hotkeys.rebind(&self.hotkey_bindings);
+            self.shortcuts = shortcut::default_shortcuts();
+            self.recording_shortcut = None;
+            self.shortcuts_changed();
+            self.gestures = gesture::default_gestures();
+        }
+        if scope.window_layout {
+            let size = egui::vec2(800.0, 600.0);
+            self.window_placement = None;
+            self.placement_changed_at = None;
+            self.last_size = None;
+            ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(size));
+            if let Some(monitor) = ctx.input(|i| i.viewport().monitor_size) {
+                ctx.send_viewport_cmd(egui::ViewportCommand::OuterPosition(((monitor - size) / 2.0).to_pos2()));
+            }
+        }
+        if scope.statistics {
+            if let Err(e) = self.stats.reset() {
+                self.toast(Level::Error, tr_args("Couldn't reset the statistics: {error}", &[("error", &e)]));
+            }
+        }
+
+        self.save_config();
+        self.toast(Level::Info, tr("Settings reset").to_string());
+    }
+
+    fn config_data(&self) -> ConfigData {
+        ConfigData {
+            version: self.config_version,
+            folder_map: self.folder_map.clone(),
+            recent_folders: self.recent_folders.clone(),
+            image_rotations: self.image_rotations.clone(),
+            image_crops: self.image_crops.clone(),
+            target_exe_name: self.target_exe_name.clone(),
+            hotkey_bindings: self.hotkey_bindings.clone(),
+            shortcuts: self.shortcuts.clone(),
+            gestures: self.gestures.clone(),
+            window_placement: self.window_placement,
+            current_index: self.current_index,
+            is_pinned: false,
+            pin_mode: Some(self.pin_mode),
+            alarm_seconds: None,
+            alarm_sound_path: None,
+            alarms: self.alarms.clone(),
+            saved_elapsed: self.settings.general.persist_elapsed.then(|| SavedElapsed {
+                seconds: self.elapsed_time.as_secs_f64(),
+                saved_at: unix_now(),
+            }),
+            profiles: self
+                .profiles
+                .iter()
+                .enumerate()
+                .map(|(i, p)| if Some(i) == self.active_profile { self.live_profile(p.name.clone()) } else { p.clone() })
+                .collect(),
+            active_profile: self.active_profile.and_then(|i| self.profiles.get(i)).map(|p| p.name.clone()),
+            settings: self.settings.clone(),
+            unknown: self.unknown_config.clone(),
+        }
+    }
+
+    /// Writes the settings to a file for another machine. Window position, the
+    /// saved timer and the auto-export folder belong to this one and are left out.
+    fn export_settings(&mut self) {
+        let Some(path) = FileDialog::new()
+            .set_title("Export Settings")
+            .set_file_name("germiboard-settings.json")
+            .add_filter("JSON", &["json"])
+            .add_filter("TOML", &["toml"])
+            .save_file()
+        else {
+            return;
+        };
+
+        let mut config = self.config_data();
+        config.window_placement = None;
+        config.current_index = 0;
+        config.saved_elapsed = None;
+        config.settings.general.auto_export_dir = None;
+        if !self.export_folders {
+            config.folder_map.clear();
+            config.recent_folders.clear();
+            for profile in &mut config.profiles {
+                profile.folder_map.clear();
+            }
+        }
+
+        let result = settings_file::encode(settings_file::Format::of(&path), &config, None)
+            .and_then(|text| fs::write(&path, text).map_err(|e| e.to_string()));
+        self.transfer_status = Some(match result {
+            Ok(()) => format!("Exported to {}", path.display()),
+            Err(e) => format!("Export failed: {}", e),
+        });
+    }
+
+    /// Reads a settings file and describes what importing it would change, for
+    /// the user to confirm.
+    fn read_import(&mut self) {
+        let Some(path) = FileDialog::new()
+            .set_title("Import Settings")
+            .add_filter("Settings", &["json", "toml"])
+            .pick_file()
+        else {
+            return;
+        };
+
+        let config = match fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|text| settings_file::decode::<ConfigData>(settings_file::Format::of(&path), &text))
+        {
+            Ok(config) => config.migrate(),
+            Err(e) => {
+                self.transfer_status = Some(format!("Couldn't read {}: {}", path.display(), e));
+                return;
+            }
+        };
+
+        let mut changes = Vec::new();
+        if config.folder_map.is_empty() {
+            changes.push("keep your folders, as the file has none".to_string());
+        } else {
+            changes.push(format!("replace {} folders with {}", self.folder_map.len(), config.folder_map.len()));
+        }
+        changes.push(format!("replace {} alarms with {}", self.alarms.len(), config.alarms.len()));
+        let hotkeys = Command::ALL
+            .iter()
+            .filter(|c| config.hotkey_bindings.get(c).is_some_and(|b| self.hotkey_bindings.get(c) != Some(b)))
+            .count();
+        if hotkeys > 0 {
+            changes.push(format!("change {} hotkeys", hotkeys));
+        }
+        let shortcuts = Action::ALL
+            .iter()
+            .filter(|a| config.shortcuts.get(a).is_some_and(|b| self.shortcuts.get(a) != Some(b)))
+            .count();
+        if shortcuts > 0 {
+            changes.push(format!("change {} keyboard shortcuts", shortcuts));
+        }
+        if config.gestures != self.gestures {
+            changes.push("change the touch gestures".to_string());
+        }
+        if !config.profiles.is_empty() || !self.profiles.is_empty() {
+            changes.push(format!("replace {} profiles with {}", self.profiles.len(), config.profiles.len()));
+        }
+
+        let mut summary = format!("This will {}, along with the other settings.", changes.join(", "));
+        if config.version > CONFIG_VERSION {
+            summary.push_str("\nThe file is from a newer version; settings this one doesn't know are kept but unused.");
+        }
+        self.transfer_status = None;
+        self.pending_import = Some((config, summary));
+    }
+
+    /// Takes on imported settings, keeping this machine's window position and timer.
+    fn apply_config(&mut self, ctx: &egui::Context, config: ConfigData) {
+        if !config.folder_map.is_empty() {
+            self.folder_map = config.folder_map;
+        }
+        if !config.recent_folders.is_empty() {
+            self.recent_folders = config.recent_folders;
+        }
+        self.image_rotations = config.image_rotations;
+        self.image_crops = config.image_crops;
+        self.target_exe_name = config.target_exe_name;
+        self.apply_settings(config.settings);
+        self.hotkey_bindings = hotkey::default_bindings();
+        self.hotkey_bindings.extend(config.hotkey_bindings);
+        self.hotkeys.rebind(&self.hotkey_bindings);
+        self.shortcuts = shortcut::default_shortcuts();
+        self.shortcuts.extend(config.shortcuts);
+        self.shortcuts_changed();
+        self.gestures = config.gestures;
+        self.pin_mode = config.pin_mode.unwrap_or_default();
+        self.pin_state_changed = true;
+        self.alarms = config.alarms;
+        self.active_profile = config.active_profile.and_then(|name| config.profiles.iter().position(|p| p.name == name));
+        self.profiles = config.profiles;
+        self.unknown_config = config.unknown;
+        self.config_version = config.version;
+
+        self.refresh_image_list();
+        if !self.image_paths.is_empty() {
+            self.show_image(ctx, 0);
+        }
+        self.rearm_alarms();
+        self.save_config();
+    }
+
+    /// Deletes what's kept on disk of which images were opened, for private mode:
+    /// the saved deck, the cached thumbnails and the image views in the
+    /// statistics database.
+    fn forget_opened_images(&mut self) {
+        deck::forget();
+        // Made again without a cache the next time the browser is drawn.
+        self.thumbnails = None;
+        thumbs::forget(&paths::thumbnail_dir());
+        if let Err(e) = self.stats.forget_views() {
+            self.toast(Level::Error, tr_args("Couldn't clear the image views from the statistics: {error}", &[("error", &e)]));
+        }
+    }
+
+    /// Takes on `settings` as a whole, redoing what depends on them. The caller
+    /// saves the config.
+    fn apply_settings(&mut self, settings: Settings) {
+        let stats_database = settings.general.stats_database;
+        let was_private = self.settings.general.private_folders;
+        let previous_stats_database = self.settings.general.stats_database;
+        self.settings = settings;
+        i18n::set_language(self.settings.general.language);
+        // Only changed once the database has opened.
+        self.settings.general.stats_database = previous_stats_database;
+        if stats_database != previous_stats_database {
+            self.set_stats_database(stats_database);
+        }
+        if self.settings.general.private_folders && !was_private {
+            self.forget_opened_images();
+        }
+
+        let tracking = &self.settings.tracking;
+        (self.title_filter, self.title_filter_error) = match TitleFilter::new(&tracking.target_title_pattern, tracking.target_title_regex) {
+            Ok(filter) => (filter, None),
+            Err(e) => (None, Some(e)),
+        };
+        (self.document_pattern, self.document_pattern_error) = match DocumentPattern::new(&tracking.document_title_pattern) {
+            Ok(pattern) => (Some(pattern), None),
+            Err(e) => (None, Some(e)),
+        };
+        self.window_opacity_pending = true;
+        self.last_size = None;
+    }
+
+    /// The current folders, alarms and target exe as a profile.
+    fn live_profile(&self, name: String) -> Profile {
+        Profile {
+            name,
+            target_exe_name: self.target_exe_name.clone(),
+            folder_map: self.folder_map.clone(),
+            alarms: self.alarms.clone(),
+        }
+    }
+
+    /// Copies the live settings back into the active profile's entry.
+    fn store_active_profile(&mut self) {
+        if let Some(active) = self.active_profile {
+            let name = self.profiles[active].name.clone();
+            self.profiles[active] = self.live_profile(name);
+        }
+    }
+
+    fn profile_name_free(&self, name: &str) -> bool {
+        !name.is_empty() && !self.profiles.iter().any(|p| p.name == name)
+    }
+
+    /// Saves the current settings as a new profile, which becomes the active one.
+    fn create_profile(&mut self, name: String) {
+        self.store_active_profile();
+        self.profiles.push(self.live_profile(name));
+        self.active_profile = Some(self.profiles.len() - 1);
+        self.save_config();
+    }
+
+    /// Removing the active profile keeps its settings, just unnamed.
+    fn remove_profile(&mut self, index: usize) {
+        if index >= self.profiles.len() {
+            return;
+        }
+        self.profiles.remove(index);
+        self.active_profile = match self.active_profile {
+            Some(active) if active == index => None,
+            Some(active) if active > index => Some(active - 1),
+            active => active,
+        };
+        self.save_config();
+    }
+
+    /// Stores the live settings into the active profile and takes on those of
+    /// `index`. With `show_now` the new deck's first image replaces the current one
+    /// once it's collected; otherwise the image on screen stays until the next advance.
+    fn activate_profile(&mut self, ctx: &egui::Context, index: usize, show_now: bool) {
+        let Some(profile) = self.profiles.get(index).cloned() else { return };
+        self.store_active_profile();
+
+        self.active_profile = Some(index);
+        self.target_exe_name = profile.target_exe_name;
+        self.folder_map = profile.folder_map;
+        self.alarms = profile.alarms;
+        self.rearm_alarms();
+        // Alarms the counter is already past don't all go off at once.
+        for alarm in &mut self.alarms {
+            alarm.catch_up(self.elapsed_time);
+        }
+
+        self.start_scan(ctx, if show_now { ScanThen::ReplaceAndShow } else { ScanThen::Replace });
+
+        self.toast(Level::Info, tr_args("Switched to profile '{name}'", &[("name", &profile.name)]));
+        self.save_config();
+    }
+
+    /// Explains that the config failed to load and offers to look at it or replace it.
+    fn config_error_banner(&mut self, ui: &mut egui::Ui, error: String) {
+        let path = paths::config_path();
+        egui::Frame::group(ui.style()).fill(ui.visuals().extreme_bg_color).show(ui, |ui| {
+            ui.horizontal(|ui| {
+                ui.strong(tr("Your settings couldn't be loaded"));
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    if ui.small_button("✖").on_hover_text(tr("Dismiss")).clicked() {
+                        self.config_error_dismissed = true;
+                    }
+                });
+            });
+            let shown = path.display();
+            ui.label(tr_args("{path} and its backup couldn't be read:", &[("path", &shown)]));
+            ui.colored_label(ui.visuals().error_fg_color, error);
+            let pending = settings_file::pending_path(path);
+            ui.label(tr_args(
+                "The app started with default settings. Until you choose, changes are saved to {path} instead.",
+                &[("path", &pending.display())],
+            ));
+
+            ui.horizontal(|ui| {
+                if ui.button(tr("Open Config File Location")).clicked() {
+                    if let Err(e) = paths::reveal(path.parent().unwrap_or(Path::new("."))) {
+                        self.toast(Level::Error, tr_args("Couldn't open the folder: {error}", &[("error", &e)]));
+                    }
+                }
+                if ui.button(tr("Overwrite with Current Settings")).clicked() {
+                    // The damaged file is kept as a .corrupt copy.
+                    if let Err(e) = settings_file::set_aside(path) {
+                        self.toast(Level::Warning, tr_args("Couldn't keep a copy of the damaged settings: {error}", &[("error", &e)]));
+                    }
+                    self.config_error = None;
+                    self.save_config();
+                    let _ = fs::remove_file(settings_file::pending_path(path));
+                }
+            });
+        });
+    }
+
+    /// Ctrl+scroll zooms around the pointer and dragging pans while zoomed in, or
+    /// while the fit mode leaves some of the image out. A double-click or 0 goes
+    /// back to the whole image.
+    fn zoom_and_pan(&mut self, ui: &egui::Ui, response: &egui::Response) {
+        let rect = response.rect;
+        // egui turns Ctrl+scroll, and pinching, into a zoom factor. A pinch zooms
+        // around where the fingers came down.
+        let factor = ui.input(|i| i.zoom_delta());
+        if factor != 1.0 {
+            let pinch = ui.input(|i| i.multi_touch()).map(|touch| touch.start_pos);
+            if let Some(pos) = pinch.or_else(|| response.hover_pos()) {
+                self.view.zoom_at(factor, (pos - rect.min) / rect.size());
+            }
+        }
+
+        // The measuring and crop tools drag out lines and regions instead.
+        if self.view.can_pan() && !self.tool_drags() {
+            if response.dragged_by(egui::PointerButton::Primary) {
+                self.view.pan(response.drag_delta(), rect.size());
+            }
+            if response.double_clicked() && self.view.is_zoomed() {
+                self.view.reset_zoom();
+            }
+            if response.dragged() {
+                ui.ctx().set_cursor_icon(egui::CursorIcon::Grabbing);
+            } else if response.hovered() {
+                ui.ctx().set_cursor_icon(egui::CursorIcon::Grab);
+            }
+        }
+    }
+
+    /// Only one tool works the image at a time.
+    fn set_eyedropper(&mut self, on: bool) {
+        self.eyedropper.active = on;
+        self.measure.active &= !on;
+        self.crop_tool.active &= !on;
+    }
+
+    fn set_measuring(&mut self, on: bool) {
+        self.measure.active = on;
+        self.eyedropper.active &= !on;
+        self.crop_tool.active &= !on;
+    }
+
+    fn set_cropping(&mut self, on: bool) {
+        self.crop_tool.active = on;
+        self.eyedropper.active &= !on;
+        self.measure.active &= !on;
+    }
+
+    /// Whether dragging the image is taken by a tool rather than panning it or
+    /// moving the window.
+    fn tool_drags(&self) -> bool {
+        self.measure.active || self.crop_tool.active
+    }
+
+    /// Shows only the part of the image under `region` on screen, where the image
+    /// is drawn in `image_rect`, and remembers it for this image.
+    fn crop_to(&mut self, region: egui::Rect, image_rect: egui::Rect) {
+        let (Some(img), Some(path)) = (&self.current_image, &self.current_path) else {
+            return;
+        };
+        let (a, b) = (self.view.texture_at(image_rect, region.min), self.view.texture_at(image_rect, region.max));
+        let Some(crop) = crop::Crop::between(a, b, img.width(), img.height()) else {
+            return;
+        };
+        self.view = self.view.with_crop(crop.uv(img.width(), img.height()));
+        self.view.reset_zoom();
+        self.image_crops.insert(path.clone(), crop);
+        self.crop_tool.active = false;
+        self.last_size = None;
+        self.config_dirty = true;
+    }
+
+    /// Shows the whole image again and forgets its crop.
+    fn clear_crop(&mut self) {
+        if self.view.crop().is_none() {
+            return;
+        }
+        self.view = self.view.with_crop(None);
+        self.view.reset_zoom();
+        if let Some(path) = &self.current_path {
+            self.image_crops.remove(path);
+        }
+        self.last_size = None;
+        self.config_dirty = true;
+    }
+
+    /// Shows the loupe over the image under the pointer, and copies the color
+    /// there when it's clicked.
+    fn use_eyedropper(&mut self, ctx: &egui::Context, response: &egui::Response) {
+        let (Some(img), Some(pointer)) = (&self.current_image, response.hover_pos()) else {
+            return;
+        };
+        ctx.set_cursor_icon(egui::CursorIcon::Crosshair);
+        let painter = ctx.layer_painter(egui::LayerId::new(egui::Order::Tooltip, egui::Id::new("loupe")));
+        eyedropper::paint_loupe(&painter, img, &self.view, response.rect, pointer);
+        let picked = eyedropper::color_at(img, &self.view, response.rect, pointer);
+        if let Some(color) = picked.filter(|_| response.clicked()) {
+            self.pick_color(ctx, color);
+        }
+    }
+
+    /// Copies `color`'s hex code and keeps it in the swatch strip if that's on.
+    fn pick_color(&mut self, ctx: &egui::Context, color: [u8; 3]) {
+        let hex = eyedropper::hex(color);
+        ctx.output_mut(|o| o.copied_text = hex.clone());
+        if self.settings.display.keep_swatches {
+            self.eyedropper.remember(color);
+        }
+        self.toast_for(Level::Info, tr_args("Copied {color}", &[("color", &hex)]), Duration::from_millis(1500));
+    }
+
+    /// Turns the image by 90°, remembering it for this image if that's on.
+    fn rotate_image(&mut self, clockwise: bool) {
+        self.view.rotate(clockwise);
+        if self.settings.display.remember_rotation {
+            if let Some(path) = &self.current_path {
+                match self.view.quarter_turns() {
+                    0 => self.image_rotations.remove(path),
+                    turns => self.image_rotations.insert(path.clone(), turns),
+                };
+                self.config_dirty = true;
+            }
+        }
+    }
+
+    /// The texture to draw: the image itself, its grayscale version, or it in a
+    /// few values once those are ready.
+    fn shown_texture(&mut self, ctx: &egui::Context) -> Option<egui::TextureId> {
+        if self.value_filter != posterize::ValueFilter::Off {
+            if let Some(texture) = self.value_texture(ctx) {
+                return Some(texture.id());
+            }
+        }
+        if !self.grayscale {
+            return self.texture.as_ref().map(|t| t.id());
+        }
+        if self.gray_texture.is_none() {
+            let img = self.current_image.as_ref()?;
+            // Rec. 709 luminance, keeping transparency.
+            let gray = img.to_luma_alpha8();
+            let rgba: Vec<u8> = gray.pixels().flat_map(|p| [p[0], p[0], p[0], p[1]]).collect();
+            let size = [img.width() as usize, img.height() as usize];
+            let color_image = egui::ColorImage::from_rgba_unmultiplied(size, &rgba);
+            self.gray_texture = Some(ctx.load_texture("image_gray", color_image, self.texture_options));
+        }
+        self.gray_texture.as_ref().map(|t| t.id())
+    }
+
+    /// The current image in the value filter picked. All the filters are made on
+    /// another thread the first time one is asked for, so the image shows as it
+    /// is until they're ready and cycling through them afterwards is instant.
+    fn value_texture(&mut self, ctx: &egui::Context) -> Option<egui::TextureHandle> {
+        if self.value_textures.is_empty() {
+            if let Some((receiver, path)) = &self.pending_values {
+                if let Ok(images) = receiver.try_recv() {
+                    let current = self.current_path.as_ref() == Some(path);
+                    self.pending_values = None;
+                    if current {
+                        for (filter, image) in images {
+                            let texture = ctx.load_texture(format!("image_{:?}", filter), image, self.texture_options);
+                            self.value_textures.insert(filter, texture);
+                        }
+                    }
+                }
+            }
+        }
+        if self.value_textures.is_empty() && self.pending_values.is_none() {
+            let (img, path) = (self.current_image.clone()?, self.current_path.clone()?);
+            let settings = self.settings.display.posterize.clone();
+            let (sender, receiver) = mpsc::channel();
+            let repaint = ctx.clone();
+            thread::spawn(move || {
+                let _ = sender.send(settings.apply_all(&img));
+                repaint.request_repaint();
+            });
+            self.pending_values = Some((receiver, path));
+        }
+        self.value_textures.get(&self.value_filter).cloned()
+    }
+
+    /// Forgets the value filtered images, to be made again when next shown.
+    fn clear_value_textures(&mut self) {
+        self.value_textures.clear();
+        self.pending_values = None;
+    }
+
+    /// Moves on to the next value filter, or back to the full image after the
+    /// last, naming it for a moment.
+    fn cycle_value_filter(&mut self) {
+        self.value_filter = self.value_filter.next();
+        let text = tr_args("Values: {mode}", &[("mode", &self.value_filter.label())]);
+        self.toast_for(Level::Info, text, Duration::from_millis(1500));
+    }
+
+    /// The blurred copy of the image for the bars around it. It's made on another
+    /// thread the first time it's asked for, and taken in once it's ready.
+    fn letterbox_texture(&mut self, ctx: &egui::Context) -> Option<egui::TextureId> {
+        if self.letterbox.is_none() {
+            if let Some((receiver, path)) = &self.pending_letterbox {
+                let blurred = receiver.try_recv().ok()?;
+                let current = self.current_path.as_ref() == Some(path);
+                self.pending_letterbox = None;
+                if current {
+                    self.letterbox = Some(ctx.load_texture("letterbox", blurred, egui::TextureOptions::LINEAR));
+                }
+            }
+        }
+        if self.letterbox.is_none() && self.pending_letterbox.is_none() {
+            let (img, path) = (self.current_image.clone()?, self.current_path.clone()?);
+            let (sender, receiver) = mpsc::channel();
+            let repaint = ctx.clone();
+            thread::spawn(move || {
+                let _ = sender.send(letterbox::blurred(&img));
+                repaint.request_repaint();
+            });
+            self.pending_letterbox = Some((receiver, path));
+        }
+        self.letterbox.as_ref().map(|t| t.id())
+    }
+
+    /// Counts the current image's histogram on another thread the first time
+    /// it's needed, so the image shows without waiting for it, and takes it in
+    /// once it's ready.
+    fn update_histogram(&mut self, ctx: &egui::Context) {
+        if self.histogram.is_none() {
+            if let Some((receiver, path)) = &self.pending_histogram {
+                let Ok(histogram) = receiver.try_recv() else {
+                    return;
+                };
+                let current = self.current_path.as_ref() == Some(path);
+                self.pending_histogram = None;
+                if current {
+                    self.histogram = Some(histogram);
+                }
+            }
+        }
+        if self.histogram.is_none() && self.pending_histogram.is_none() {
+            let (Some(img), Some(path)) = (self.current_image.clone(), self.current_path.clone()) else {
+                return;
+            };
+            let (sender, receiver) = mpsc::channel();
+            let repaint = ctx.clone();
+            thread::spawn(move || {
+                let _ = sender.send(histogram::Histogram::of(&img));
+                repaint.request_repaint();
+            });
+            self.pending_histogram = Some((receiver, path));
+        }
+    }
+
+    /// What the second window shows: the image as it's shown here, unzoomed, or
+    /// the next image in the deck.
+    fn second_view_picture(&mut self, ctx: &egui::Context) -> Option<second_view::Picture> {
+        match self.settings.display.second_view.mode {
+            second_view::SecondViewMode::Mirror => {
+                let img = self.current_image.as_ref()?;
+                let size = egui::vec2(img.width() as f32, img.height() as f32);
+                let view = zoom::View::turned(self.view.quarter_turns()).with_flips(self.view.flips()).with_crop(self.view.crop());
+                let texture = self.shown_texture(ctx)?;
+                Some(second_view::Picture { texture, size, view })
+            }
+            second_view::SecondViewMode::NextImage => {
+                if self.image_paths.is_empty() {
+                    return None;
+                }
+                let path = &self.image_paths[(self.current_index + 1) % self.image_paths.len()];
+                if self.upcoming.as_ref().is_none_or(|u| u.path != *path) {
+                    let loaded = open_with_info(path).ok();
+                    let size = loaded.as_ref().map_or(egui::Vec2::ZERO, |(img, _)| egui::vec2(img.width() as f32, img.height() as f32));
+                    let one_to_one = self.settings.display.fit_mode == FitMode::ActualPixels;
+                    let options = self.wanted_texture_options(size, one_to_one);
+                    self.upcoming = Some(Upcoming {
+                        path: path.clone(),
+                        loaded: loaded.map(|(img, info)| {
+                            let texture = ctx.load_texture("upcoming", color_image(&img), options);
+                            (img, info, texture)
+                        }),
+                        options,
+                    });
+                }
+                let (img, _, texture) = self.upcoming.as_ref()?.loaded.as_ref()?;
+                let size = egui::vec2(img.width() as f32, img.height() as f32);
+                Some(second_view::Picture { texture: texture.id(), size, view: zoom::View::default() })
+            }
+        }
+    }
+
+    /// Sharp, unsmoothed pixels for pixel art: when asked for this session, for
+    /// images no larger than the setting allows, and for whole images at actual
+    /// pixels, which are being blown up. Smoothed otherwise.
+    fn wanted_texture_options(&self, image_size: egui::Vec2, one_to_one: bool) -> egui::TextureOptions {
+        let small = self.settings.display.sharp_pixels_up_to.is_some_and(|up_to| image_size.max_elem() <= up_to as f32);
+        if self.sharp_pixels || small || one_to_one {
+            egui::TextureOptions::NEAREST
+        } else {
+            egui::TextureOptions::LINEAR
+        }
+    }
+
+    /// Uploads the image again if it was last uploaded to be sampled otherwise,
+    /// leaving the grayscale version to be made again when it's next shown.
+    fn set_texture_options(&mut self, options: egui::TextureOptions) {
+        if options == self.texture_options {
+            return;
+        }
+        self.texture_options = options;
+        if let (Some(texture), Some(img)) = (&mut self.texture, &self.current_image) {
+            texture.set(color_image(img), options);
+        }
+        self.gray_texture = None;
+        self.clear_value_textures();
+    }
+
+    fn load_image(&mut self, ctx: &egui::Context) {
+        while let Some(path) = self.image_paths.get(self.current_index) {
+            let upcoming = self.upcoming.take().filter(|u| u.path == *path);
+            let options = upcoming.as_ref().map(|u| u.options);
+            let opened = match upcoming.and_then(|u| u.loaded) {
+                Some((img, info, texture)) => Ok((img, info, Some(texture))),
+                None => open_with_info(path).map(|(img, info)| (img, info, None)),
+            };
+            match opened {
+                Ok((img, info, texture)) => {
+                    let path = path.clone();
+                    let quarter_turns = match self.settings.display.remember_rotation {
+                        true => self.image_rotations.get(&path).copied().unwrap_or(0),
+                        false => 0,
+                    };
+                    let flips = if self.settings.display.sticky_flip { self.view.flips() } else { (false, false) };
+                    let crop = self.image_crops.get(&path).and_then(|crop| crop.uv(img.width(), img.height()));
+                    self.view = zoom::View::turned(quarter_turns).with_flips(flips).with_crop(crop);
+                    self.finish_view();
+                    self.stats.record_image_viewed();
+                    self.current_info = Some(info);
+                    self.info_bar_shown_at = Instant::now();
+                    self.current_path = Some(path);
+                    self.view_started_at = unix_now();
+                    // Whether it fits at actual pixels is known once it's laid out.
+                    let image_size = egui::vec2(img.width() as f32, img.height() as f32);
+                    let one_to_one = self.settings.display.fit_mode == FitMode::ActualPixels;
+                    self.texture_options = self.wanted_texture_options(image_size, one_to_one);
+                    self.texture = match texture {
+                        Some(mut texture) => {
+                            if options != Some(self.texture_options) {
+                                texture.set(color_image(&img), self.texture_options);
+                            }
+                            Some(texture)
+                        }
+                        None => Some(ctx.load_texture("image", color_image(&img), self.texture_options)),
+                    };
+                    self.gray_texture = None;
+                    self.clear_value_textures();
+                    self.letterbox = None;
+                    self.histogram = None;
+                    if !self.measure.pinned {
+                        self.measure.clear();
+                    }
+                    self.grayscale &= self.settings.display.sticky_grayscale;
+                    if !self.settings.display.sticky_grayscale {
+                        self.value_filter = posterize::ValueFilter::Off;
+                    }
+                    self.current_image = Some(img);
+                    self.image_timer = Instant::now();
+                    self.last_size = None;
+                    break;
+                }
+                Err(_) => {
+                    self.image_paths.remove(self.current_index);
+                    if self.current_index >= self.image_paths.len() && !self.image_paths.is_empty() {
+                        self.current_index = 0;
+                    } else {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    /// The view of the image currently on screen, as it would be logged right now.
+    fn current_view(&self) -> Option<ImageView> {
+        Some(ImageView {
+            path: self.current_path.clone()?,
+            started_at: self.view_started_at,
+            shown_for: self.image_timer.elapsed(),
+            tracked: self.view_tracked,
+            alarms_fired: self.view_alarms_fired,
+            overtime: self.view_overtime,
+            adjusted_seconds: self.view_adjustment,
+        })
+    }
+
+    fn finish_view(&mut self) {
+        if let Some(view) = self.current_view() {
+            // The database would keep a lasting list of what was opened.
+            if !self.settings.general.private_folders {
+                self.stats.record_view(&view);
+            }
+            self.view_log.push(view);
+        }
+        self.view_tracked = Duration::ZERO;
+        self.view_alarms_fired = 0;
+        self.view_overtime = Duration::ZERO;
+        self.view_adjustment = 0;
+    }
+
+    /// Manually credits or removes tracked time. Alarms whose threshold is now ahead
+    /// of the counter again are re-armed; ones that were jumped past fire on the next check.
+    fn adjust_elapsed(&mut self, seconds: i64) {
+        let before = self.elapsed_time;
+        let amount = Duration::from_secs(seconds.unsigned_abs());
+        self.elapsed_time = if seconds >= 0 {
+            before + amount
+        } else {
+            before.saturating_sub(amount)
+        };
+        self.view_adjustment += self.elapsed_time.as_secs() as i64 - before.as_secs() as i64;
+
+        let pre_alarm_offset = self.settings.timer.pre_alarm_seconds.map(Duration::from_secs);
+        for alarm in &mut self.alarms {
+            // Winding back makes passed repetitions due again; winding forward past
+            // a deadline lets it fire on the next check like a one-off alarm.
+            let passed = self.elapsed_time.as_secs() / alarm.seconds.max(1);
+            alarm.repetitions = alarm.repetitions.min(passed);
+            let duration = alarm.duration();
+            if self.elapsed_time < duration {
+                alarm.triggered = false;
+            }
+            if pre_alarm_offset.is_none_or(|offset| self.elapsed_time < duration.saturating_sub(offset)) {
+                alarm.pre_triggered = false;
+            }
+        }
+
+        let limit = self.settings.timer.overtime_escalation_seconds.map(Duration::from_secs);
+        if self.overtime().zip(limit).is_none_or(|(over, limit)| over < limit) {
+            self.overtime_escalated = false;
+        }
+    }
+
+    /// How far the counter is past the latest alarm that has fired.
+    fn overtime(&self) -> Option<Duration> {
+        self.alarms
+            .iter()
+            .filter(|a| a.counts_tracked_time() && a.triggered)
+            .map(Alarm::duration)
+            .max()
+            .map(|deadline| self.elapsed_time.saturating_sub(deadline))
+    }
+
+    fn session_views(&self) -> Vec<ImageView> {
+        self.view_log.iter().cloned().chain(self.current_view()).collect()
+    }
+
+    fn export_session_log(&mut self) {
+        let Some(path) = FileDialog::new()
+            .set_title("Export Session Log")
+            .set_file_name("session.csv")
+            .add_filter("CSV", &["csv"])
+            .add_filter("JSON", &["json"])
+            .save_file()
+        else {
+            return;
+        };
+
+        self.export_status = Some(match session::export(&self.session_views(), &path) {
+            Ok(()) => format!("Exported to {}", path.display()),
+            Err(e) => format!("Export failed: {}", e),
+        });
+    }
+
+    fn session_summary(&self) -> Option<String> {
+        let views = self.session_views();
+        if views.is_empty() {
+            return None;
+        }
+
+        let tracked: Duration = views.iter().map(|v| v.tracked).sum();
+        let shown: Duration = views.iter().map(|v| v.shown_for).sum();
+        let alarms_fired: usize = views.iter().map(|v| v.alarms_fired).sum();
+        let adjusted: i64 = views.iter().map(|v| v.adjusted_seconds).sum();
+        let overtime: Duration = views.iter().map(|v| v.overtime).sum();
+        let longest = views.iter().max_by_key(|v| v.shown_for)?;
+        let longest_name = longest.path.file_name().unwrap_or_default().to_string_lossy();
+
+        Some(format!(
+            "Total tracked time: {}\nImages viewed: {}\nAverage per image: {}s\nLongest image: {} ({})\nAlarms fired: {}",
+            format_hours(tracked.as_secs()),
+            views.len(),
+            shown.as_secs() / views.len() as u64,
+            longest_name,
+            format_hours(longest.shown_for.as_secs()),
+            if alarms_fired == 0 { "none".to_string() } else { alarms_fired.to_string() },
+        ) + &if !overtime.is_zero() {
+            format!("\nOvertime: {}", format_hours(overtime.as_secs()))
+        } else {
+            String::new()
+        } + &if adjusted != 0 {
+            format!(
+                "\nManual adjustments: {}{}",
+                if adjusted > 0 { "+" } else { "-" },
+                format_hours(adjusted.unsigned_abs()),
+            )
+        } else {
+            String::new()
+        })
+    }
+
+    fn next_image(&mut self, ctx: &egui::Context) {
+        if !self.board_slots.is_empty() {
+            self.advance_board(ctx);
+        } else if !self.image_paths.is_empty() {
+            self.show_image(ctx, (self.current_index + 1) % self.image_paths.len());
+        }
+    }
+
+    /// The next image from the hotkey or shortcut. On the board only the image
+    /// under the pointer moves on, if that's how it's set.
+    fn next_image_under_pointer(&mut self, ctx: &egui::Context) {
+        let under_pointer = match self.settings.display.board.advance {
+            BoardAdvance::UnderPointer if !self.board_slots.is_empty() => {
+                let pointer = ctx.input(|i| i.pointer.hover_pos());
+                pointer.and_then(|pos| self.board_cells.iter().position(|cell| cell.contains(pos)))
+            }
+            _ => None,
+        };
+        match under_pointer {
+            Some(slot) => self.advance_slot(ctx, slot),
+            None => self.next_image(ctx),
+        }
+    }
+
+    /// Moves every slot of the board on to the next images of the deck.
+    fn advance_board(&mut self, ctx: &egui::Context) {
+        for slot in 0..=self.board_slots.len() {
+            self.advance_slot(ctx, slot);
+        }
+    }
+
+    /// Moves one slot of the board on to the next image dealt from the deck. Slot
+    /// 0 is the viewer's usual image, which restarts the timer as always.
+    fn advance_slot(&mut self, ctx: &egui::Context, slot: usize) {
+        if slot == 0 {
+            if let Some(index) = self.deal() {
+                self.show_image(ctx, index);
+            }
+        } else if let Some(dealt) = self.deal_slot(ctx) {
+            if let Some(slot) = self.board_slots.get_mut(slot - 1) {
+                *slot = dealt;
+            }
+        }
+    }
+
+    /// The position in the deck of the next image for the board.
+    fn deal(&mut self) -> Option<usize> {
+        if self.image_paths.is_empty() {
+            return None;
+        }
+        self.board_dealt = (self.board_dealt + 1) % self.image_paths.len();
+        Some(self.board_dealt)
+    }
+
+    /// The next image of the deck that opens, loaded for a slot beside the first.
+    fn deal_slot(&mut self, ctx: &egui::Context) -> Option<board::Slot> {
+        for _ in 0..self.image_paths.len() {
+            let index = self.deal()?;
+            let path = self.image_paths[index].clone();
+            if let Ok(img) = open_upright(&path) {
+                let size = egui::vec2(img.width() as f32, img.height() as f32);
+                let options = self.wanted_texture_options(size, false);
+                let texture = ctx.load_texture("board_slot", color_image(&img), options);
+                return Some(board::Slot {
+                    size,
+                    texture,
+                    tracked: Duration::ZERO,
+                });
+            }
+        }
+        None
+    }
+
+    /// Deals images into the board, or takes them off it, to fill the layout's
+    /// slots. A board being set up deals on from the image already showing.
+    fn sync_board(&mut self, ctx: &egui::Context) {
+        let beside = self.settings.display.board.layout.slot_count() - 1;
+        self.board_slots.truncate(beside);
+        if self.board_slots.is_empty() {
+            self.board_dealt = self.current_index;
+        }
+        while self.board_slots.len() < beside {
+            match self.deal_slot(ctx) {
+                Some(slot) => self.board_slots.push(slot),
+                None => break,
+            }
+        }
+    }
+
+    /// Draws the images beside the first one on the board, with a Next button on
+    /// whichever slot the pointer is over.
+    fn board_ui(&mut self, ui: &mut egui::Ui, ctx: &egui::Context, frame: &eframe::Frame, cells: Vec<egui::Rect>, can_move: bool) {
+        let fit_mode = self.settings.display.fit_mode;
+        let clocks = self.settings.display.board.timer == BoardTimer::PerSlot && self.hud_visible();
+        let slots: Vec<_> = self.board_slots.iter().map(|slot| (slot.texture.id(), slot.size, slot.tracked)).collect();
+        for ((texture, size, tracked), cell) in slots.into_iter().zip(&cells[1..]) {
+            let (target_size, extent) = fit_mode.layout(size, cell.size(), ctx.pixels_per_point());
+            let rect = egui::Rect::from_center_size(cell.center(), target_size);
+            let response = ui.allocate_rect(rect, egui::Sense::click_and_drag());
+            let mut view = zoom::View::default();
+            view.set_extent(extent);
+            self.settings.display.backdrop.paint(ui.painter(), rect);
+            view.paint(ui.painter(), rect, texture);
+            if clocks {
+                flip_badge(ui.painter(), rect, &format_clock(tracked.as_secs()));
+            }
+            let response = response.context_menu(|ui| self.context_menu(ui, ctx, frame));
+            if can_move && response.drag_started_by(egui::PointerButton::Primary) {
+                ctx.send_viewport_cmd(egui::ViewportCommand::StartDrag);
+            }
+        }
+
+        let pointer = ctx.input(|i| i.pointer.hover_pos());
+        if let Some(slot) = pointer.and_then(|pos| cells.iter().position(|cell| cell.contains(pos))) {
+            let corner = cells[slot].right_top() + egui::vec2(-78.0, 6.0);
+            if ui.put(egui::Rect::from_min_size(corner, egui::vec2(72.0, 22.0)), egui::Button::new("Next ▶")).clicked() {
+                self.advance_slot(ctx, slot);
+            }
+        }
+        self.board_cells = cells;
+    }
+
+    fn previous_image(&mut self, ctx: &egui::Context) {
+        if !self.image_paths.is_empty() {
+            let len = self.image_paths.len();
+            self.show_image(ctx, (self.current_index + len - 1) % len);
+        }
+    }
+
+    fn show_image(&mut self, ctx: &egui::Context, index: usize) {
+        if index < self.image_paths.len() {
+            // Moving on again mid-fade snaps straight to the latest image.
+            let fade = Duration::from_millis(self.settings.display.crossfade_ms);
+            let crossfade = match (self.crossfade.take(), self.image_rect) {
+                (None, Some(rect)) if !fade.is_zero() => {
+                    let values = self.value_textures.get(&self.value_filter).cloned();
+                    let gray = if self.grayscale { self.gray_texture.clone() } else { None };
+                    values.or(gray).or_else(|| self.texture.clone()).map(|t| fade::Crossfade::new(t, self.view, rect, fade))
+                }
+                _ => None,
+            };
+            self.current_index = index;
+            self.load_image(ctx);
+            self.crossfade = crossfade;
+            self.elapsed_time = Duration::ZERO;
+            self.image_timer = Instant::now();
+            self.last_timer_check = Instant::now();
+            self.rearm_alarms();
+            self.config_dirty = true;
+        }
+    }
+
+    /// Adds a folder, or turns it back on, and shuffles its images into the end of
+    /// the list. A `temporary` folder is left out of the saved config.
+    fn add_folder(&mut self, folder: PathBuf, temporary: bool) {
+        if temporary {
+            self.temporary_folders.insert(folder.clone());
+        } else {
+            self.temporary_folders.remove(&folder);
+            remember_folder(&mut self.recent_folders, &folder);
+        }
+        if self.folder_map.insert(folder.clone(), true) != Some(true) {
+            let (mut new_images, scan) = scan_folder(&folder);
+            self.folder_scans.insert(folder, scan);
+            new_images.shuffle(&mut rand::thread_rng());
+            self.image_paths.extend(new_images);
+        }
+    }
+
+    /// Takes the images of `folder` out of the deck, except those another enabled
+    /// folder also holds. If the image showing was one of them, the next image
+    /// left is shown.
+    fn drop_folder_images(&mut self, ctx: &egui::Context, folder: &Path) {
+        let others: Vec<PathBuf> = self.folder_map.iter().filter(|(_, on)| **on).map(|(f, _)| f.clone()).collect();
+        let dropped = |path: &Path| path.starts_with(folder) && !others.iter().any(|f| path.starts_with(f));
+
+        let showing_dropped = self.current_path.as_deref().is_some_and(dropped);
+        let end = self.current_index.min(self.image_paths.len());
+        let before = self.image_paths[..end].iter().filter(|p| !dropped(p)).count();
+        self.image_paths.retain(|p| !dropped(p));
+        self.current_index = before;
+
+        if showing_dropped {
+            if self.image_paths.is_empty() {
+                self.current_image = None;
+                self.texture = None;
+                self.gray_texture = None;
+                self.clear_value_textures();
+                self.letterbox = None;
+                self.board_slots.clear();
+            } else {
+                self.current_index %= self.image_paths.len();
+                self.load_image(ctx);
+            }
+        }
+    }
+
+    fn remove_folder(&mut self, ctx: &egui::Context, folder: &Path) {
+        self.folder_map.shift_remove(folder);
+        self.folder_scans.remove(folder);
+        self.temporary_folders.remove(folder);
+        self.drop_folder_images(ctx, folder);
+        self.save_config();
+    }
+
+    /// Points the entry for `old`, a folder that was moved or renamed, at `new`,
+    /// keeping its place in the list, whether it's enabled and the rotations of
+    /// its images.
+    fn relocate_folder(&mut self, ctx: &egui::Context, old: &Path, new: PathBuf) {
+        let Some((index, _, enabled)) = self.folder_map.shift_remove_full(old) else { return };
+        self.folder_scans.remove(old);
+        self.drop_folder_images(ctx, old);
+        if self.temporary_folders.remove(old) {
+            self.temporary_folders.insert(new.clone());
+        }
+        for folder in &mut self.recent_folders {
+            if folder == old {
+                *folder = new.clone();
+            }
+        }
+        self.image_rotations = std::mem::take(&mut self.image_rotations)
+            .into_iter()
+            .map(|(path, turns)| match path.strip_prefix(old) {
+                Ok(rest) => (new.join(rest), turns),
+                Err(_) => (path, turns),
+            })
+            .collect();
+        self.image_crops = std::mem::take(&mut self.image_crops)
+            .into_iter()
+            .map(|(path, crop)| match path.strip_prefix(old) {
+                Ok(rest) => (new.join(rest), crop),
+                Err(_) => (path, crop),
+            })
+            .collect();
+
+        let (mut new_images, scan) = scan_folder(&new);
+        self.folder_scans.insert(new.clone(), scan);
+        if enabled && self.folder_map.get(&new) != Some(&true) {
+            new_images.shuffle(&mut rand::thread_rng());
+            self.image_paths.extend(new_images);
+        }
+        if self.folder_map.contains_key(&new) {
+            self.folder_map.insert(new, enabled);
+        } else {
+            self.folder_map.shift_insert(index, new, enabled);
+        }
+        if self.current_image.is_none() {
+            self.load_image(ctx);
+        }
+        self.save_config();
+    }
 
-        ctx.request_repaint_after(Duration::from_secs(1));
-         egui::Area::new("")
-        .fixed_pos(egui::pos2(10.0, 10.0))
-        .show(ctx, |ui| {
-            let now = Instant::now();
+    /// Adds folders and shows an image given on the command line, ours or one
+    /// forwarded from a later launch. Of several images, the last one is shown.
+    fn open_paths(&mut self, ctx: &egui::Context, paths: Vec<PathBuf>) {
+        let mut show = None;
+        for path in paths {
+            if path.is_dir() {
+                self.add_folder(path, false);
+            } else if path.is_file() {
+                show = Some(path);
+            }
+        }
+
+        if let Some(path) = show {
+            let index = match self.image_paths.iter().position(|p| *p == path) {
+                Some(index) => index,
+                None => {
+                    // Slotted in after the current image, so the rest of the order stays.
+                    let index = (self.current_index + 1).min(self.image_paths.len());
+                    self.image_paths.insert(index, path);
+                    index
+                }
+            };
+            self.show_image(ctx, index);
+        }
+        self.save_config();
+    }
+
+    fn target_is_tracked(&self) -> bool {
+        match self.settings.tracking.tracking_mode {
+            TrackingMode::Focused => self.target_is_active,
+            TrackingMode::Hovered => self.target_is_hovered,
+            TrackingMode::FocusedOrHovered => self.target_is_active || self.target_is_hovered,
+        }
+    }
+
+    /// Advances the timers to now. The tracker's state changes are replayed in order,
+    /// so each stretch since the last frame is counted with the state that was in
+    /// effect during it, however long egui went without calling `update`.
+    fn tick_timers(&mut self) {
+        let now = Instant::now();
+        let transitions = self.tracker.drain();
+        self.replay(transitions, now);
+        self.tracker.wake_at(self.next_alarm_at(now));
+
+        match self.stats.maybe_flush() {
+            Ok(()) => self.stats_unsaved = false,
+            Err(e) if !self.stats_unsaved => {
+                self.stats_unsaved = true;
+                self.toast(Level::Error, tr_args("Couldn't save the statistics: {error}", &[("error", &e)]));
+            }
+            Err(_) => {}
+        }
+        if self.settings.general.persist_elapsed {
+            self.config_dirty = true;
+        }
+        self.maybe_save_config();
+    }
+
+    /// Counts the time since the last check up to `now`, with the tracker's
+    /// `transitions` applied as they happened.
+    fn replay(&mut self, transitions: Vec<(Instant, TrackState)>, now: Instant) {
+        tracker::replay(self.last_timer_check, now, transitions, |delta, ended_ago, state| {
+            self.advance(delta, ended_ago);
+            if let Some(state) = state {
+                self.apply_track_state(state);
+            }
+        });
+        self.last_timer_check = now;
+    }
+
+    /// When the next tracked-time alarm, or the warning before one, comes due if
+    /// the timer keeps counting from `now`.
+    fn next_alarm_at(&self, now: Instant) -> Option<Instant> {
+        if !self.target_is_tracked() || self.user_idle || self.timer_paused {
+            return None;
+        }
+        let warning = self.settings.timer.pre_alarm_seconds.map(Duration::from_secs);
+        self.alarms
+            .iter()
+            .filter(|a| a.counts_tracked_time() && !a.triggered)
+            .flat_map(|a| [Some(a.duration()), warning.filter(|_| !a.pre_triggered).and_then(|w| a.duration().checked_sub(w))])
+            .flatten()
+            .filter(|&at| at > self.elapsed_time)
+            .min()
+            .map(|at| now + (at - self.elapsed_time))
+    }
+
+    fn apply_track_state(&mut self, state: TrackState) {
+        self.foreground_exe = state.foreground_exe;
+        self.target_is_active = state.target_active;
+        self.target_is_hovered = state.target_hovered;
+        self.user_idle = state.idle;
+        self.other_app_fullscreen = state.other_fullscreen;
+        self.current_document = state.document;
+    }
+
+    /// Counts `delta` of time that ended `ended_ago` before now.
+    fn advance(&mut self, delta: Duration, ended_ago: Duration) {
+        let counting = self.target_is_tracked() && !self.user_idle && !self.timer_paused;
+        let tracked_delta = if counting { delta } else { Duration::ZERO };
+        self.elapsed_time += tracked_delta;
+        self.view_tracked += tracked_delta;
+        for slot in &mut self.board_slots {
+            slot.tracked += tracked_delta;
+        }
+        if self.overtime().is_some() {
+            self.view_overtime += tracked_delta;
+            self.stats.record_overtime(tracked_delta, ended_ago);
+        }
+        if let Some(document) = &self.current_document {
+            self.stats.record_document(document, tracked_delta, ended_ago);
+        }
+
+        // Time goes to whichever tracked exe was in the foreground since the last
+        // check, so switching between two of them never counts an interval twice.
+        if let Some(exe) = self.foreground_exe.as_ref().filter(|_| !self.user_idle) {
+            if self.target_exe_name.as_ref() == Some(exe) || self.settings.tracking.tracked_exes.contains(exe) {
+                *self.exe_times.entry(exe.clone()).or_default() += delta;
+                self.stats.record(exe, delta, ended_ago);
+            }
+        }
+
+        if self.settings.timer.pomodoro.enabled && self.pomodoro.tick(&self.settings.timer.pomodoro, tracked_delta) {
+            log::info!("Pomodoro phase changed to {}", self.pomodoro.phase.label());
+            if self.pomodoro.phase != PomodoroPhase::Work {
+                self.stats.record_pomodoro();
+            }
+            let path = self.settings.timer.pomodoro.sound_path.clone();
+            self.play_sound(path.as_deref(), self.settings.timer.alarm_volume);
+            let phrase = self.settings.timer.speech.segment_phrase.replace("{phase}", self.pomodoro.phase.label());
+            self.announce(&phrase, self.settings.timer.alarm_volume);
+        }
+    }
+
+    fn rearm_alarms(&mut self) {
+        for alarm in &mut self.alarms {
+            alarm.triggered = false;
+            alarm.pre_triggered = false;
+            alarm.repetitions = 0;
+        }
+        self.overtime_escalated = false;
+    }
+
+    /// Whether a session is underway that sleep would interrupt: the timer is
+    /// running, or an alarm is still waiting to go off.
+    fn session_running(&self) -> bool {
+        !self.timer_paused
+            && (self.target_is_tracked()
+                || self.settings.timer.pomodoro.enabled
+                || self.alarms.iter().any(|a| a.enabled && !a.triggered))
+    }
+
+    /// The session counter of the focused tracked exe, if the overlay should show it.
+    fn focused_exe_counter(&self) -> Option<(&str, Duration)> {
+        if !self.settings.tracking.show_focused_exe_counter {
+            return None;
+        }
+        let exe = self.foreground_exe.as_deref()?;
+        self.exe_times.get(exe).map(|time| (exe, *time))
+    }
+
+    /// How far the counter is toward the countdown alarm, from 0 to 1. A repeating
+    /// alarm shows progress through its current interval.
+    fn alarm_progress(&self) -> Option<f32> {
+        let alarm = self.countdown_alarm()?;
+        if alarm.triggered {
+            return Some(1.0);
+        }
+        let deadline = alarm.duration();
+        let start = if alarm.repeating { deadline.saturating_sub(Duration::from_secs(alarm.seconds)) } else { Duration::ZERO };
+        let span = (deadline - start).as_secs_f32().max(1.0);
+        Some((self.elapsed_time.saturating_sub(start).as_secs_f32() / span).clamp(0.0, 1.0))
+    }
+
+    /// Time left until the countdown alarm, while it hasn't been reached yet.
+    fn countdown_left(&self) -> Option<Duration> {
+        let alarm = self.countdown_alarm()?.duration();
+        (self.elapsed_time < alarm).then(|| alarm - self.elapsed_time)
+    }
+
+    fn showing_tenths(&self) -> bool {
+        self.settings.timer.countdown_tenths
+            && self.settings.timer.timer_display_mode != TimerDisplayMode::Elapsed
+            && self.countdown_left().is_some_and(|left| left < Duration::from_secs(10))
+    }
+
+    /// Delay until just after the displayed second (or tenth) next changes, so the
+    /// overlay ticks on the boundary instead of drifting against a fixed cadence.
+    fn next_repaint_delay(&self) -> Duration {
+        const SLACK: Duration = Duration::from_millis(5);
+
+        if !self.target_is_tracked() || self.user_idle {
+            return Duration::from_secs(1);
+        }
+
+        let step = if self.showing_tenths() {
+            Duration::from_millis(100)
+        } else {
+            Duration::from_secs(1)
+        };
+        let into_step = self.elapsed_time.as_nanos() % step.as_nanos();
+        step - Duration::from_nanos(into_step as u64) + SLACK
+    }
+
+    /// The alarm the countdown display refers to: the earliest enabled alarm that
+    /// hasn't fired yet, or the last one to fire once they all have.
+    fn countdown_alarm(&self) -> Option<&Alarm> {
+        let enabled = || self.alarms.iter().filter(|a| a.counts_tracked_time());
+        enabled()
+            .filter(|a| !a.triggered)
+            .min_by_key(|a| a.duration())
+            .or_else(|| enabled().max_by_key(|a| a.duration()))
+    }
+
+    fn refresh_image_list(&mut self) {
+        (self.image_paths, self.folder_scans) = scan_folders(&self.folder_map);
+    }
 
-            if self.target_is_active {
-                let delta = now.duration_since(self.last_timer_check);
-                self.elapsed_time += delta;
+    /// Scans the folders on another thread. `update` takes in the result.
+    fn start_scan(&mut self, ctx: &egui::Context, then: ScanThen) {
+        let folder_map = self.folder_map.clone();
+        let (sender, receiver) = mpsc::channel();
+        let repaint = ctx.clone();
+        thread::spawn(move || {
+            let _ = sender.send(scan_folders(&folder_map));
+            repaint.request_repaint();
+        });
+        self.pending_image_list = Some((receiver, then));
+    }
+}
+
+impl App for ImageViewerApp {
+    fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
+
+
+        if self.hide_on_start {
+            self.hide_on_start = false;
+            if self.tray.is_some() {
+                self.set_in_tray(ctx, frame, true);
+            } else {
+                ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(true));
+            }
+        }
+
+        if self.window_opacity_pending {
+            self.window_opacity_pending = false;
+            self.apply_hover_fade(ctx, frame, self.hover_faded);
+        }
+        self.update_theme(ctx, frame);
+        self.update_ui_scale(ctx);
+        self.update_min_window_size(ctx);
+        self.snap_window(ctx);
+
+        let close_requested = ctx.input(|i| i.viewport().close_requested());
+        if close_requested && self.settings.general.close_to_tray && self.tray.is_some() && !self.quit_requested {
+            ctx.send_viewport_cmd(egui::ViewportCommand::CancelClose);
+            self.set_in_tray(ctx, frame, true);
+        } else if close_requested
+            && self.settings.general.show_exit_summary
+            && !self.exit_confirmed
+            && self.session_summary().is_some()
+        {
+            ctx.send_viewport_cmd(egui::ViewportCommand::CancelClose);
+            self.show_summary = true;
+        }
+
+        self.tick_timers();
+        ctx.request_repaint_after(self.next_repaint_delay());
+
+        // A fired alarm flashes the taskbar button red, once a second.
+        let progress = match self.alarm_progress() {
+            None => Progress::None,
+            Some(fraction) if fraction >= 1.0 => {
+                ctx.request_repaint_after(Duration::from_secs(1));
+                if (ctx.input(|i| i.time) as u64).is_multiple_of(2) {
+                    Progress::Error(1.0)
+                } else {
+                    Progress::None
+                }
+            }
+            Some(fraction) if self.timer_paused => Progress::Paused(fraction),
+            Some(fraction) => Progress::Normal(fraction),
+        };
+        self.taskbar_progress.set(frame, progress);
+
+        let wake = match (self.settings.general.keep_awake && self.session_running(), self.settings.general.keep_display_on) {
+            (false, _) => Wake::Off,
+            (true, false) => Wake::System,
+            (true, true) => Wake::SystemAndDisplay,
+        };
+        self.awake.set(wake);
+
+        // Saved once the window has stayed put for a moment, not on every frame of a drag.
+        if let Some(placement) = WindowPlacement::capture(ctx).filter(|_| !self.in_tray) {
+            if self.window_placement != Some(placement) {
+                self.window_placement = Some(placement);
+                self.placement_changed_at = Some(Instant::now());
+            }
+        }
+        if let Some(changed_at) = self.placement_changed_at {
+            let settle = Duration::from_secs(1);
+            if close_requested || changed_at.elapsed() >= settle {
+                self.placement_changed_at = None;
+                self.save_config();
+            } else {
+                ctx.request_repaint_after(settle - changed_at.elapsed());
+            }
+        }
+
+        // The hover state has to hold for a moment before the window fades or comes
+        // back, so sweeping the cursor across the target doesn't make it flicker.
+        let want_faded = self.settings.display.hover_behavior != HoverBehavior::Nothing && self.target_is_hovered;
+        if want_faded == self.hover_faded {
+            self.hover_pending_since = None;
+        } else {
+            let hysteresis = Duration::from_millis(400);
+            let waited = self.hover_pending_since.get_or_insert_with(Instant::now).elapsed();
+            if waited >= hysteresis {
+                self.apply_hover_fade(ctx, frame, want_faded);
+                self.hover_pending_since = None;
+            } else {
+                ctx.request_repaint_after(hysteresis - waited);
+            }
+        }
+
+        // A viewer left on top would cover the game or video; it comes back once
+        // the fullscreen app loses focus.
+        let want_hidden = self.settings.tracking.hide_over_fullscreen && self.other_app_fullscreen && self.pinned_on_top;
+        if want_hidden != self.fullscreen_hidden {
+            self.fullscreen_hidden = want_hidden;
+            self.apply_hover_fade(ctx, frame, self.hover_faded);
+        }
+
+        self.update_pin_level(ctx);
+
+        if self.settings.general.auto_switch_profiles {
+            let switch_to = self.auto_switch.poll(&self.profiles, self.active_profile, self.foreground_exe.as_deref());
+            if let Some(index) = switch_to {
+                self.activate_profile(ctx, index, false);
+            } else if let Some(wait) = self.auto_switch.wait() {
+                ctx.request_repaint_after(wait);
+            }
+        }
+
+        let collected = self.pending_image_list.as_ref().and_then(|(r, then)| Some((r.try_recv().ok()?, *then)));
+        if let Some(((paths, scans), then)) = collected {
+            self.pending_image_list = None;
+            self.folder_scans = scans;
+            if then != ScanThen::CountOnly {
+                self.image_paths = paths;
+                self.board_slots.clear();
+                if then == ScanThen::ReplaceAndShow && !self.image_paths.is_empty() {
+                    self.show_image(ctx, 0);
+                } else if self.current_image.is_none() {
+                    self.current_index = 0;
+                    self.load_image(ctx);
+                } else {
+                    // The next advance wraps around to the start of the new list.
+                    self.current_index = self.image_paths.len().saturating_sub(1);
+                }
+            }
+        }
+
+        let menu_was_open = std::mem::take(&mut self.context_menu_open);
+        let screen = ctx.screen_rect();
+        let overlay_pos = self
+            .timer_drag
+            .unwrap_or_else(|| self.settings.timer.overlay.top_left(self.timer_overlay_size, screen));
+        let mut dropped = false;
+         let overlay = egui::Area::new("timer_overlay")
+        .fixed_pos(overlay_pos)
+        .show(ctx, |ui| {
+            if !self.hud_visible() {
+                return;
+            }
+            let scale = match (self.fullscreen, self.settings.display.fullscreen_timer) {
+                (true, FullscreenTimer::Hidden) => return,
+                (true, FullscreenTimer::Large) => 2.5,
+                _ => 1.0,
+            };
+            // The timer has a size of its own, which the UI scale leaves alone. In a
+            // small window it shrinks so as not to cover the image.
+            let shrink = (screen.size().min_elem() / SMALL_WINDOW_SIDE).min(1.0);
+            let font_size = self.settings.timer.overlay.font_size * scale * shrink / ctx.zoom_factor();
+            if font_size < MIN_TIMER_FONT_SIZE {
+                return;
             }
 
-            self.last_timer_check = now;
+            let elapsed_secs = self.elapsed_time.as_secs();
+            let elapsed_text = match self.focused_exe_counter() {
+                Some((exe, time)) => format!("{} {}", exe.trim_end_matches(".exe"), format_clock(time.as_secs())),
+                None => format_clock(elapsed_secs),
+            };
+
+            // Counting down past zero turns into a negative overtime display.
+            let remaining = self.countdown_alarm().map(|alarm| match self.countdown_left() {
+                Some(left) if self.showing_tenths() => (format_clock_tenths(left), false),
+                Some(left) => (format_clock(left.as_secs() + u64::from(left.subsec_nanos() > 0)), false),
+                None => (format!("-{}", format_clock(elapsed_secs.saturating_sub(alarm.duration().as_secs()))), true),
+            });
+
+            let (timer_text, overtime) = match (self.settings.timer.timer_display_mode, remaining) {
+                (TimerDisplayMode::Remaining, Some((text, overtime))) => (text, overtime),
+                (TimerDisplayMode::Both, Some((text, overtime))) => {
+                    (format!("{} / {}", elapsed_text, text), overtime)
+                }
+                _ => (elapsed_text, false),
+            };
 
-            let minutes = self.elapsed_time.as_secs() / 60;
-            let seconds = self.elapsed_time.as_secs() % 60;
-            let timer_text = format!("{:02}:{:02}", minutes, seconds);
+            let emphasis = if overtime {
+                1.0
+            } else if self.settings.timer.pre_alarm_pulse && self.alarms.iter().any(|a| a.pre_triggered && !a.triggered) {
+                // Pulse the background between the normal and overtime shades.
+                ctx.request_repaint();
+                ((ui.input(|i| i.time) * std::f64::consts::TAU).sin() * 0.5 + 0.5) as f32
+            } else {
+                0.0
+            };
 
-            ui.label(
-                egui::RichText::new(timer_text)
-                    .color(egui::Color32::RED)
-                    .background_color(egui::Color32::from_rgb(30, 0, 0))
-                    .font(egui::FontId::monospace(28.0)),
+            let style = self.settings.display.timer_style.clone();
+            let detail = |ui: &mut egui::Ui, text: String, color| {
+                style.label(ui, text, egui::FontId::monospace(font_size / 2.0), color, 0.0, egui::Sense::hover());
+            };
+            let response = style.label(
+                ui,
+                timer_text,
+                egui::FontId::monospace(font_size),
+                style.text(),
+                emphasis,
+                egui::Sense::click_and_drag(),
             );
+
+            if response.clicked() {
+                self.settings.timer.timer_display_mode = self.settings.timer.timer_display_mode.next();
+                self.save_config();
+            }
+            let response = response.context_menu(|ui| self.context_menu(ui, ctx, frame));
+
+            // Moved here rather than by making the Area movable, which would
+            // take the clicks meant for the timer and the buttons under it.
+            let delta = response.drag_delta();
+            if response.dragged_by(egui::PointerButton::Primary) && (delta != egui::Vec2::ZERO || self.timer_drag.is_some()) {
+                let pos = self.timer_drag.unwrap_or(overlay_pos) + delta;
+                self.timer_drag = Some(overlay::clamp_into(pos, self.timer_overlay_size, screen));
+                ctx.set_cursor_icon(egui::CursorIcon::Grabbing);
+            }
+            dropped = response.drag_released();
+
+            // Ctrl+scroll over the timer changes the window opacity in 5% steps.
+            if response.hovered() {
+                let factor = ui.input(|i| i.zoom_delta());
+                if factor != 1.0 {
+                    let step: i16 = if factor > 1.0 { 5 } else { -5 };
+                    let percent = (self.settings.display.window_opacity_percent as i16 + step).clamp(0, 100) as u8;
+                    self.set_window_opacity(ctx, frame, percent);
+                }
+            }
+
+            if let Some(progress) = self.alarm_progress().filter(|_| self.settings.timer.show_progress_bar) {
+                let (rect, _) = ui.allocate_exact_size(egui::vec2(response.rect.width(), 4.0), egui::Sense::hover());
+                let color = if progress >= 1.0 {
+                    // Once reached, the full bar pulses like the warning does.
+                    let t = ((ui.input(|i| i.time) * std::f64::consts::TAU).sin() * 0.5 + 0.5) as f32;
+                    ctx.request_repaint();
+                    egui::Color32::from_rgb(255, (80.0 * t) as u8, 0)
+                } else if progress < 0.5 {
+                    // Green to yellow over the first half, yellow to red over the second.
+                    egui::Color32::from_rgb((510.0 * progress) as u8, 200, 0)
+                } else {
+                    egui::Color32::from_rgb(255, (200.0 * (2.0 - 2.0 * progress)) as u8, 0)
+                };
+                ui.painter().rect_filled(rect, 1.0, style.background(0.0));
+                let mut filled = rect;
+                filled.set_width(rect.width() * progress);
+                ui.painter().rect_filled(filled, 1.0, color);
+            }
+
+            if let Some(over) = self.overtime() {
+                let [r, g, b] = self.settings.timer.overtime_color;
+                detail(ui, format!("+{} over", format_clock(over.as_secs())), egui::Color32::from_rgb(r, g, b));
+            }
+
+            if self.target_exe_name.is_some() && self.settings.tracking.tracking_mode != TrackingMode::Focused {
+                let condition = match (self.target_is_active, self.target_is_hovered) {
+                    (true, true) => "focused + hovered",
+                    (true, false) => "focused",
+                    (false, true) => "hovered",
+                    (false, false) => "not tracking",
+                };
+                detail(ui, condition.into(), style.detail());
+            }
+
+            if self.user_idle && self.target_is_tracked() {
+                detail(ui, "idle".into(), style.detail());
+            }
+
+            if self.timer_paused {
+                detail(ui, "paused".into(), style.detail());
+            }
+
+            if self.awake.is_active() {
+                detail(ui, "keeping awake".into(), style.detail());
+            }
+
+            if self.click_through {
+                let text = format!("click-through ({} to exit)", self.hotkey_label(Command::ToggleClickThrough));
+                detail(ui, text, style.detail());
+            }
+
+            for alarm in self.alarms.iter().filter(|a| a.counts_tracked_time() && a.repeating) {
+                detail(ui, format!("{} #{}", alarm.label, alarm.repetitions + 1), style.detail());
+            }
+
+            if self.settings.timer.pomodoro.enabled {
+                let cycles = self.settings.timer.pomodoro.cycles_before_long_break.max(1);
+                let cycle = match self.pomodoro.phase {
+                    PomodoroPhase::Work => self.pomodoro.completed_cycles % cycles + 1,
+                    _ => (self.pomodoro.completed_cycles - 1) % cycles + 1,
+                };
+                let pomodoro_text = format!(
+                    "{} {} left - cycle {}/{}",
+                    self.pomodoro.phase.label(),
+                    format_clock(self.pomodoro.remaining(&self.settings.timer.pomodoro).as_secs()),
+                    cycle,
+                    cycles,
+                );
+                detail(ui, pomodoro_text, style.text());
+            }
+
+            // The speaker stays visible while muted so it's obvious why nothing plays.
+            if self.timer_overlay_hovered || self.settings.timer.muted {
+                ui.horizontal(|ui| {
+                    let icon = if self.settings.timer.muted { "🔇" } else { "🔊" };
+                    if ui.small_button(icon).on_hover_text(self.with_shortcut("Mute alarm sounds", Action::ToggleMute)).clicked() {
+                        self.toggle_mute();
+                    }
+                    if self.timer_overlay_hovered {
+                        for (label, seconds) in [("-5m", -300), ("-1m", -60), ("+1m", 60), ("+5m", 300)] {
+                            if ui.small_button(label).clicked() {
+                                self.adjust_elapsed(seconds);
+                            }
+                        }
+                    }
+                });
+            }
+        });
+        self.timer_overlay_hovered = overlay.response.hovered()
+            || ctx.pointer_hover_pos().is_some_and(|pos| overlay.response.rect.contains(pos));
+        self.timer_overlay_size = overlay.response.rect.size();
+        if let Some(pos) = self.timer_drag.filter(|_| dropped) {
+            let rect = egui::Rect::from_min_size(pos, self.timer_overlay_size);
+            self.settings.timer.overlay.dropped(rect, screen);
+            self.timer_drag = None;
+            self.save_config();
+        }
+
+
+
+        ctx.input(|i| self.pointer.update(i));
+        self.update_decorations(ctx);
+
+        // Keys pressed while recording a hotkey or shortcut belong to the recording.
+        let recording = self.recording_hotkey.is_some() || self.recording_shortcut.is_some();
+        if !recording && !ctx.wants_keyboard_input() {
+            let pressed = ctx.input(|i| shortcut::pressed(&self.shortcuts, &self.shortcut_conflicts, i));
+            for action in pressed {
+                self.run_shortcut(ctx, action);
+            }
+        }
+        // Touches only count over the image, away from any window or menu, and a
+        // finger dragging a zoomed image pans it rather than swiping.
+        let swipes = !self.view.can_pan();
+        let events = ctx.input(|i| i.events.clone());
+        let gesture = self.touch.update(&events, swipes, |pos| ctx.layer_id_at(pos) == Some(egui::LayerId::background()));
+        if let Some(action) = gesture.and_then(|g| self.gestures.get(&g).copied()) {
+            self.run_shortcut(ctx, action);
+        }
+        if !recording && !ctx.wants_keyboard_input() {
+            // Neither can be rebound: Escape always backs out, and "?" is where
+            // people look for help.
+            let (escape, question_mark) = ctx.input(|i| {
+                let typed = i.events.iter().any(|event| matches!(event, egui::Event::Text(text) if text == "?"));
+                (i.key_pressed(egui::Key::Escape), typed)
+            });
+            if question_mark {
+                self.show_shortcuts = !self.show_shortcuts;
+            }
+            if escape && self.show_shortcuts {
+                self.show_shortcuts = false;
+            } else if escape && self.eyedropper.active {
+                self.eyedropper.active = false;
+            } else if escape && self.crop_tool.active {
+                self.crop_tool.active = false;
+            } else if escape && self.measure.has_lines() {
+                self.measure.clear();
+            } else if escape && self.measure.active {
+                self.measure.active = false;
+            } else if escape && self.fullscreen && !menu_was_open {
+                self.set_fullscreen(ctx, false);
+            }
+        }
+
+        self.tracker.configure(TrackConfig {
+            target: self.target_exe_name.clone(),
+            title_filter: self.title_filter.clone(),
+            include_child_processes: self.settings.tracking.include_child_processes,
+            watch_foreground: !self.settings.tracking.tracked_exes.is_empty() || self.settings.general.auto_switch_profiles,
+            idle_threshold: self.settings.tracking.idle_pause_enabled.then(|| Duration::from_secs(self.settings.tracking.idle_threshold_seconds)),
+            interval: Duration::from_millis(self.settings.tracking.poll_interval_ms),
+            fullscreen_exclusions: self.settings.tracking.hide_over_fullscreen.then(|| self.settings.tracking.fullscreen_exclusions.clone()),
+            document_pattern: self.document_pattern.clone().filter(|_| self.settings.tracking.track_documents),
         });
 
+        //timer logic
+        let pre_alarm_offset = self.settings.timer.pre_alarm_seconds.map(Duration::from_secs);
+        let mut sounds = Vec::new();
+        let mut phrases = Vec::new();
+        let mut toasts = Vec::new();
+        let mut advance = false;
+        let mut pause = false;
+        let now_local = chrono::Local::now();
+        for alarm in self.alarms.iter_mut().filter(|a| a.enabled) {
+            let fired_body = if let Some(schedule) = alarm.schedule {
+                // Scheduled alarms fire when their time passes between two checks, even
+                // if the window wasn't updating right at that moment.
+                let today = now_local.date_naive();
+                let due = alarm.fired_on != Some(today) && schedule.due_between(self.last_schedule_check, now_local);
+                if due {
+                    alarm.fired_on = Some(today);
+                }
+                due.then(|| tr_args("Scheduled for {time}", &[("time", &schedule.time.format("%H:%M"))]))
+            } else {
+                let duration = alarm.duration();
+
+                if let Some(offset) = pre_alarm_offset {
+                    if !alarm.pre_triggered
+                        && !alarm.triggered
+                        && offset < duration
+                        && self.elapsed_time < duration
+                        && self.elapsed_time >= duration - offset
+                    {
+                        alarm.pre_triggered = true;
+                        if let Some(path) = &self.settings.timer.pre_alarm_sound_path {
+                            sounds.push((Some(path.clone()), self.settings.timer.pre_alarm_volume));
+                        }
+                        phrases.push((
+                            self.settings.timer.speech
+                                .pre_alarm_phrase
+                                .replace("{seconds}", &offset.as_secs().to_string())
+                                .replace("{label}", &alarm.label),
+                            self.settings.timer.pre_alarm_volume,
+                        ));
+                    }
+                }
+
+                alarm.due(self.elapsed_time).then(|| tr_args("{time} of tracked time", &[("time", &format_clock(self.elapsed_time.as_secs()))]))
+            };
+
+            if let Some(body) = fired_body {
+                self.view_alarms_fired += 1;
+                log::info!("Alarm '{}' triggered at {:?}", alarm.label, self.elapsed_time);
+                sounds.push((alarm.sound_path.clone(), self.settings.timer.alarm_volume));
+                phrases.push((self.settings.timer.speech.alarm_phrase.replace("{label}", &alarm.label), self.settings.timer.alarm_volume));
+                toasts.push((alarm.label.clone(), body));
+                advance |= alarm.on_trigger.advances();
+                pause |= alarm.on_trigger.pauses();
+            }
+        }
+        self.last_schedule_check = now_local;
+        // Escalate once with a louder sound after enough overtime.
+        if let (Some(limit), Some(over)) = (self.settings.timer.overtime_escalation_seconds, self.overtime()) {
+            if !self.overtime_escalated && over >= Duration::from_secs(limit) {
+                self.overtime_escalated = true;
+                log::info!("Overtime escalation after {:?}", over);
+                sounds.push((self.settings.timer.overtime_sound_path.clone(), 1.0));
+            }
+        }
+
+        for (path, volume) in sounds {
+            self.play_sound(path.as_deref(), volume);
+        }
+        for (phrase, volume) in phrases {
+            self.announce(&phrase, volume);
+        }
+        if self.settings.timer.alarm_toast {
+            for (title, body) in toasts {
+                self.toaster
+                    .get_or_insert_with(|| toast::Toaster::new(ctx.clone()))
+                    .show(title, body);
+            }
+        }
+
+        // Goes through the same path as the hotkey, which also resets and re-arms.
+        let per_slot = self.settings.display.board.timer == BoardTimer::PerSlot;
+        if advance && per_slot {
+            self.advance_slot(ctx, 0);
+        } else if advance {
+            self.next_image(ctx);
+        }
+        // Timed on their own, the other slots move on when their time reaches the
+        // first alarm that moves on.
+        let moves_on = self
+            .alarms
+            .iter()
+            .filter(|a| a.counts_tracked_time() && a.on_trigger.advances())
+            .map(|a| Duration::from_secs(a.seconds.max(1)))
+            .min();
+        if let Some(after) = moves_on.filter(|_| per_slot) {
+            let due: Vec<usize> = (1..=self.board_slots.len()).filter(|i| self.board_slots[i - 1].tracked >= after).collect();
+            for slot in due {
+                self.advance_slot(ctx, slot);
+            }
+        }
+        if pause {
+            self.timer_paused = true;
+        }
+
+        if self.hotkeys.take_unavailable() {
+            self.toast(Level::Warning, tr("Global hotkeys aren't available here, so click-through is turned off.").to_string());
+        }
+        for command in self.hotkeys.drain() {
+            match command {
+                Command::NextImage => self.next_image_under_pointer(ctx),
+                Command::TogglePause => self.timer_paused = !self.timer_paused,
+                Command::DismissAlarm => self.dismiss_alarm(),
+                Command::ToggleClickThrough => self.set_click_through(ctx, frame, !self.click_through),
+                command => {
+                    if let Some(spot) = command.spot() {
+                        self.throw_window(ctx, spot);
+                    }
+                }
+            }
+        }
+
+        let forwarded = self.instance.as_ref().map(instance::Instance::drain).unwrap_or_default();
+        if !forwarded.is_empty() {
+            // Launching again brings the viewer forward, even from the tray.
+            if self.in_tray {
+                self.set_in_tray(ctx, frame, false);
+            }
+            ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(false));
+            ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+        }
+        let opened: Vec<PathBuf> = std::mem::take(&mut self.launch_paths)
+            .into_iter()
+            .chain(forwarded.into_iter().flatten())
+            .collect();
+        if !opened.is_empty() {
+            self.open_paths(ctx, opened);
+        }
+
+        #[cfg(windows)]
+        self.run_tray_commands(ctx, frame);
+
+        if self.tray.is_some() {
+            let tracking = self.target_is_tracked() && !self.user_idle && !self.timer_paused;
+            let mut tooltip = format!("GermiBoard - {} tracked", format_clock(self.elapsed_time.as_secs()));
+            if self.timer_paused {
+                tooltip.push_str(" (paused)");
+            } else if !tracking {
+                tooltip.push_str(" (idle)");
+            }
+            let status = tray::TrayStatus {
+                tooltip,
+                tracking,
+                viewer_visible: !self.in_tray,
+                paused: self.timer_paused,
+            };
+            if let Some(tray) = self.tray.as_mut() {
+                tray.set_status(status);
+            }
+        }
+
+        // Clicking a toast brings the viewer back even if it was minimized.
+        if self.toaster.as_ref().is_some_and(|t| t.take_activation()) {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(false));
+            ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+        }
+
+        self.notices.show(ctx);
+
+        if self.show_alarm_config {
+            egui::Window::new(tr("Set Alarm")).id(egui::Id::new("set_alarm")).show(ctx, |ui| {
+                let mut remove_index = None;
+
+                for (i, alarm) in self.alarms.iter_mut().enumerate() {
+                    ui.push_id(i, |ui| {
+                        ui.horizontal(|ui| {
+                            ui.checkbox(&mut alarm.enabled, "");
+                            ui.add(egui::TextEdit::singleline(&mut alarm.label).desired_width(120.0));
+                            if ui.button(tr("Remove")).clicked() {
+                                remove_index = Some(i);
+                            }
+                        });
+
+                        let mut scheduled = alarm.schedule.is_some();
+                        ui.horizontal(|ui| {
+                            ui.radio_value(&mut scheduled, false, tr("After tracked time"));
+                            ui.radio_value(&mut scheduled, true, tr("At time of day"));
+                        });
+                        if scheduled != alarm.schedule.is_some() {
+                            alarm.schedule = scheduled.then(Schedule::new);
+                        }
+
+                        if let Some(schedule) = alarm.schedule.as_mut() {
+                            use chrono::Timelike;
+                            let mut hour = schedule.time.hour();
+                            let mut minute = schedule.time.minute();
+                            ui.horizontal(|ui| {
+                                ui.label(tr("At"));
+                                ui.add(egui::DragValue::new(&mut hour).clamp_range(0..=23));
+                                ui.label(":");
+                                ui.add(egui::DragValue::new(&mut minute).clamp_range(0..=59));
+                            });
+                            if let Some(time) = chrono::NaiveTime::from_hms_opt(hour, minute, 0) {
+                                schedule.time = time;
+                            }
+
+                            ui.horizontal(|ui| {
+                                for (bit, name) in Schedule::WEEKDAYS.iter().enumerate() {
+                                    let mut on = schedule.weekdays & (1 << bit) != 0;
+                                    if ui.toggle_value(&mut on, tr(name)).changed() {
+                                        schedule.weekdays ^= 1 << bit;
+                                    }
+                                }
+                            });
+                        } else {
+                            ui.horizontal(|ui| {
+                                ui.radio_value(&mut alarm.repeating, false, tr("Once"));
+                                ui.radio_value(&mut alarm.repeating, true, tr("Repeating"));
+                            });
+                            ui.add(
+                                egui::Slider::new(&mut alarm.seconds, 10..=3600)
+                                    .text(if alarm.repeating { tr("Repeat Every (sec)") } else { tr("Trigger Alarm After (sec)") })
+                            );
+                        }
+
+                        self.sounds.picker(ui, "alarm_sound", tr("Choose Sound"), &mut alarm.sound_path, self.settings.timer.alarm_volume, true);
+
+                        egui::ComboBox::from_label(tr("When Triggered"))
+                            .selected_text(alarm.on_trigger.label())
+                            .show_ui(ui, |ui| {
+                                for action in AlarmAction::ALL {
+                                    ui.selectable_value(&mut alarm.on_trigger, action, action.label());
+                                }
+                            });
+                    });
+                    ui.separator();
+                }
+
+                if let Some(i) = remove_index {
+                    self.alarms.remove(i);
+                }
+
+                if ui.button(tr("Add Alarm")).clicked() {
+                    let label = tr_args("Alarm {number}", &[("number", &(self.alarms.len() + 1))]);
+                    self.alarms.push(Alarm::new(label, 180, None));
+                }
+
+                let conflicts = self.alarms_before_warning();
+                for label in &conflicts {
+                    ui.colored_label(ui.visuals().error_fg_color, tr_args("The warning must come before '{label}' itself.", &[("label", label)]));
+                }
+
+                ui.horizontal(|ui| {
+                    if ui.add_enabled(conflicts.is_empty(), egui::Button::new(tr("Set Alarms"))).clicked() {
+                        self.rearm_alarms();
+                        self.show_alarm_config = false;
+                        self.save_config();
+                    }
+                    if ui.button(tr("Timer & Alarm Options...")).clicked() {
+                        self.open_settings(settings::Tab::Timer);
+                    }
+                });
+            });
+        }
+
+
+        if self.show_settings {
+            self.settings_window(ctx, frame);
+        }
+
+        if self.show_shortcuts {
+            self.shortcuts_window(ctx);
+        }
+
+        if self.show_profiles {
+            let mut open = true;
+            egui::Window::new("Profiles").open(&mut open).show(ctx, |ui| {
+                ui.label("Each profile has its own folders, alarms and tracked EXE.");
+
+                let mut activate = None;
+                let mut remove_index = None;
+                let mut rename = None;
+                let mut start_rename = None;
+                egui::Grid::new("profiles").num_columns(3).show(ui, |ui| {
+                    for (i, profile) in self.profiles.iter().enumerate() {
+                        let active = self.active_profile == Some(i);
+                        let target = if active { &self.target_exe_name } else { &profile.target_exe_name };
+                        match &mut self.renaming_profile {
+                            Some((renaming, name)) if *renaming == i => {
+                                ui.add(egui::TextEdit::singleline(name).desired_width(120.0));
+                                ui.label(target.as_deref().unwrap_or("no target"));
+                                if ui.button("OK").clicked() {
+                                    rename = Some(i);
+                                }
+                            }
+                            _ => {
+                                let name = egui::RichText::new(&profile.name);
+                                ui.label(if active { name.strong() } else { name });
+                                ui.label(target.as_deref().unwrap_or("no target"));
+                                ui.horizontal(|ui| {
+                                    if ui.add_enabled(!active, egui::Button::new("Switch")).clicked() {
+                                        activate = Some(i);
+                                    }
+                                    if ui.button("Rename").clicked() {
+                                        start_rename = Some(i);
+                                    }
+                                    if ui.button("Remove").clicked() {
+                                        remove_index = Some(i);
+                                    }
+                                });
+                            }
+                        }
+                        ui.end_row();
+                    }
+                });
+
+                let mut changed = false;
+                if let Some(i) = activate {
+                    self.activate_profile(ctx, i, true);
+                }
+                if let Some(i) = start_rename {
+                    self.renaming_profile = Some((i, self.profiles[i].name.clone()));
+                }
+                if let Some(i) = rename {
+                    if let Some((_, name)) = self.renaming_profile.take() {
+                        // An empty or taken name leaves the profile as it was.
+                        let name = name.trim().to_string();
+                        if self.profile_name_free(&name) {
+                            self.profiles[i].name = name;
+                            changed = true;
+                        }
+                    }
+                }
+                if let Some(i) = remove_index {
+                    self.renaming_profile = None;
+                    self.remove_profile(i);
+                }
 
+                ui.horizontal(|ui| {
+                    ui.text_edit_singleline(&mut self.new_profile_name);
+                    let name = self.new_profile_name.trim().to_string();
+                    if ui.add_enabled(self.profile_name_free(&name), egui::Button::new("Save Current as Profile")).clicked() {
+                        self.create_profile(name);
+                        self.new_profile_name.clear();
+                    }
+                });
 
-        let pointer_over = ctx.input(|i| i.pointer.hover_pos().is_some());
+                ui.separator();
+                changed |= ui
+                    .checkbox(&mut self.settings.general.auto_switch_profiles, "Switch to the profile of the focused app")
+                    .changed();
 
-        if pointer_over {
-            self.last_hover = Instant::now();
-            if !self.decorations_visible {
-                ctx.send_viewport_cmd(egui::ViewportCommand::Decorations(true));
-                self.decorations_visible = true;
+                if changed {
+                    self.save_config();
+                }
+            });
+            if !open {
+                self.renaming_profile = None;
             }
-        } else if self.decorations_visible && self.last_hover.elapsed() > Duration::from_secs(2) {
-            ctx.send_viewport_cmd(egui::ViewportCommand::Decorations(false));
-            self.decorations_visible = false;
+            self.show_profiles = open;
         }
 
-        if ctx.input(|i| i.key_pressed(egui::Key::ArrowRight)) {
-            self.next_image(ctx);
+        if self.show_settings_transfer {
+            let mut open = true;
+            egui::Window::new("Export / Import Settings").open(&mut open).show(ctx, |ui| {
+                if let Some((_, summary)) = &self.pending_import {
+                    ui.label(summary);
+                    let mut apply = false;
+                    ui.horizontal(|ui| {
+                        apply = ui.button("Import").clicked();
+                        if ui.button("Cancel").clicked() {
+                            self.pending_import = None;
+                        }
+                    });
+                    if apply {
+                        if let Some((config, _)) = self.pending_import.take() {
+                            self.apply_config(ctx, config);
+                            self.transfer_status = Some("Settings imported.".to_string());
+                        }
+                    }
+                } else {
+                    ui.checkbox(&mut self.export_folders, "Include image folders");
+                    ui.horizontal(|ui| {
+                        if ui.button("Export Settings...").clicked() {
+                            self.export_settings();
+                        }
+                        if ui.button("Import Settings...").clicked() {
+                            self.read_import();
+                        }
+                    });
+                }
+                if let Some(status) = &self.transfer_status {
+                    ui.label(status);
+                }
+            });
+            if !open {
+                self.pending_import = None;
+                self.transfer_status = None;
+            }
+            self.show_settings_transfer = open;
         }
 
-        if ctx.input(|i| i.pointer.secondary_clicked()) {
-            if let Some(pos) = ctx.input(|i| i.pointer.hover_pos()) {
-                self.context_menu_pos = pos;
-                self.show_context_menu = true;
+        if self.show_reset {
+            let mut open = true;
+            egui::Window::new("Reset Settings").open(&mut open).resizable(false).show(ctx, |ui| {
+                ui.add_enabled_ui(!self.confirm_reset, |ui| {
+                    ui.label("Put these back to how a fresh install has them:");
+                    ui.checkbox(&mut self.reset_scope.folders, "Folders");
+                    ui.checkbox(&mut self.reset_scope.alarms, "Alarms");
+                    ui.checkbox(&mut self.reset_scope.hotkeys, "Hotkeys");
+                    ui.checkbox(&mut self.reset_scope.window_layout, "Window position and size");
+                    ui.checkbox(&mut self.reset_scope.statistics, "Statistics");
+                });
+
+                if !self.confirm_reset {
+                    if ui.add_enabled(self.reset_scope.any(), egui::Button::new("Reset...")).clicked() {
+                        self.confirm_reset = true;
+                    }
+                    return;
+                }
+                ui.separator();
+                ui.label(format!(
+                    "This can't be undone from here, but the current settings are first copied to {}.",
+                    settings_file::reset_backup_path(paths::config_path()).display()
+                ));
+                ui.horizontal(|ui| {
+                    if ui.button("Reset").clicked() {
+                        self.reset_settings(ctx, self.reset_scope);
+                        self.reset_scope = ResetScope::default();
+                        self.confirm_reset = false;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        self.confirm_reset = false;
+                    }
+                });
+            });
+            if !open {
+                self.confirm_reset = false;
             }
+            self.show_reset = open;
         }
 
-        if let Some(target_name) = &self.target_exe_name {
-            let active_hwnd = unsafe { GetForegroundWindow() };
-            self.target_is_active = Self::get_exe_name_from_hwnd(active_hwnd)
-                .map_or(false, |name| name == *target_name);
-
-            let mut pt = POINT::default();
-            let _ = unsafe { GetCursorPos(&mut pt) };
-            let hovered_hwnd = unsafe { WindowFromPoint(pt) };
-            self.target_is_hovered = Self::get_exe_name_from_hwnd(hovered_hwnd)
-                .map_or(false, |name| name == *target_name);
+        if self.show_grid_settings {
+            let mut open = true;
+            egui::Window::new("Grid").open(&mut open).resizable(false).show(ctx, |ui| {
+                if self.grid_settings(ui) {
+                    self.config_dirty = true;
+                }
+            });
+            self.show_grid_settings = open;
         }
 
-        //timer logic
-        if let Some(alarm) = self.alarm_duration {
-            if !self.alarm_triggered && self.elapsed_time >= alarm {
-                self.alarm_triggered = true;
-                println!("Alarm triggered at {:?}", self.elapsed_time); // Debug log
-                if let Some(path) = &self.alarm_sound_path {
-                    println!("Attempting to play: {:?}", path); // Debug log
-                    play_alarm_sound(path.clone()); 
+        if self.show_image_info {
+            let mut open = true;
+            egui::Window::new("Image Info").open(&mut open).resizable(false).show(ctx, |ui| {
+                let Some(info) = &self.current_info else {
+                    ui.label("No image is showing.");
+                    return;
+                };
+                if let Some(name) = self.current_path.as_ref().and_then(|p| p.file_name()) {
+                    ui.strong(name.to_string_lossy());
                 }
-            }
+                egui::Grid::new("image_info").num_columns(2).striped(true).show(ui, |ui| {
+                    for (name, value) in info.rows() {
+                        ui.label(name);
+                        ui.label(value);
+                        ui.end_row();
+                    }
+                });
+                if info.exif.is_empty() {
+                    ui.weak("No camera details in this file.");
+                }
+                if ui.button("Copy All").clicked() {
+                    let mut text = info.to_text();
+                    if let Some(path) = &self.current_path {
+                        text = format!("{}\n{}", path.display(), text);
+                    }
+                    ui.output_mut(|o| o.copied_text = text);
+                }
+            });
+            self.show_image_info = open;
         }
 
+        if self.show_about {
+            let mut open = true;
+            egui::Window::new("About").open(&mut open).resizable(false).show(ctx, |ui| {
+                ui.strong(format!("Germi Board {}", env!("CARGO_PKG_VERSION")));
+                let path = paths::config_path();
+                let origin = match paths::config_origin() {
+                    paths::Origin::CommandLine => " (from --config)",
+                    paths::Origin::Portable => " (portable)",
+                    paths::Origin::Default => "",
+                };
+                ui.label(format!("Settings file{}: {}", origin, path.display()));
+                if ui.button("Open Config Folder").clicked() {
+                    if let Err(e) = paths::reveal(path.parent().unwrap_or(Path::new("."))) {
+                        self.toast(Level::Error, tr_args("Couldn't open the folder: {error}", &[("error", &e)]));
+                    }
+                }
+            });
+            self.show_about = open;
+        }
 
-        if self.show_context_menu {
-            egui::Area::new("right_click_menu")
-                .fixed_pos(self.context_menu_pos)
+        if self.show_summary {
+            egui::Window::new("Session Summary")
+                .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+                .collapsible(false)
+                .resizable(false)
                 .show(ctx, |ui| {
-                    egui::Frame::popup(ui.style()).show(ui, |ui| {
-                        if ui.button("Next Image").clicked() {
-                            self.next_image(ctx);
-                        }
+                    let summary = self.session_summary().unwrap_or_default();
+                    ui.label(&summary);
+                    ui.separator();
 
-                        if ui.button(if self.is_pinned { "Unpin from Top" } else { "Pin to Top" }).clicked() {
-                            self.is_pinned = !self.is_pinned;
-                            self.pin_state_changed = true;
-                            self.save_config();
-                            self.show_context_menu = false;
-                        }
+                    if ui.checkbox(&mut self.settings.general.show_exit_summary, "Show this summary on exit").changed() {
+                        self.save_config();
+                    }
 
-                        if ui.button("Folder Manager").clicked() {
-                            self.show_folder_manager = true;
-                            self.show_context_menu = false;
+                    ui.horizontal(|ui| {
+                        if ui.button("Copy to Clipboard").clicked() {
+                            ui.output_mut(|o| o.copied_text = summary);
+                        }
+                        if ui.button("Keep Open").clicked() {
+                            self.show_summary = false;
                         }
+                        if ui.button("Close").clicked() {
+                            self.exit_confirmed = true;
+                            ctx.send_viewport_cmd(egui::ViewportCommand::Close);
+                        }
+                    });
+                });
+        }
 
-                        use rand::seq::SliceRandom;
-                        
+        if self.show_stats {
+            let mut open = true;
+            egui::Window::new("Statistics")
+                .open(&mut open)
+                .collapsible(false)
+                .show(ctx, |ui| {
+                    let today = stats::today();
 
-                        if ui.button("Add Folder").clicked() {
-                            self.show_context_menu = false;
-                            if let Some(new_folder) = FileDialog::new().set_title("Add Folder").pick_folder() {
-                                self.folder_map.insert(new_folder.clone(), true);
-                                let mut new_images = get_image_paths(&new_folder);
-                                new_images.shuffle(&mut rand::thread_rng());
-                                self.image_paths.extend(new_images);
-                                self.save_config();
+                    ui.heading("Today");
+                    match self.stats.day(today) {
+                        Some(day) if day.total_seconds() > 0 || day.pomodoros > 0 || day.overtime_seconds > 0 => {
+                            for (exe, secs) in &day.exe_seconds {
+                                ui.label(format!("{}: {}", exe, format_hours(*secs)));
+                            }
+                            if day.pomodoros > 0 {
+                                ui.label(format!("Pomodoros completed: {}", day.pomodoros));
+                            }
+                            if day.overtime_seconds > 0 {
+                                ui.label(format!("Overtime: {}", format_hours(day.overtime_seconds)));
                             }
                         }
+                        _ => {
+                            ui.label("Nothing tracked yet today.");
+                        }
+                    }
 
-                        if ui.button("Set Alarm...").clicked() {
-                            self.show_alarm_config = true;
-                            self.show_context_menu = false;
-                            self.save_config();
+                    if !self.exe_times.is_empty() {
+                        ui.separator();
+                        ui.heading("This session");
+                        let mut session: Vec<_> = self.exe_times.iter().collect();
+                        session.sort_by(|a, b| b.1.cmp(a.1));
+                        for (exe, time) in session {
+                            ui.label(format!("{}: {}", exe, format_hours(time.as_secs())));
                         }
+                    }
 
-                        if ui.button("Track EXE...").clicked() {
-                            self.show_context_menu = false;
-                            if let Some(path) = FileDialog::new().add_filter("EXE", &["exe"]).pick_file() {
-                                if let Some(name) = path.file_name().and_then(|s| s.to_str()) {
-                                    self.target_exe_name = Some(name.to_lowercase());
-                                    self.save_config();
-                                }
-                            }
+                    // Documents are only recorded with per-document tracking on.
+                    let mut documents: BTreeMap<&str, u64> = BTreeMap::new();
+                    for day in today.iter_days().rev().take(7).filter_map(|day| self.stats.day(day)) {
+                        for (document, secs) in &day.document_seconds {
+                            *documents.entry(document.as_str()).or_default() += secs;
                         }
+                    }
+                    if !documents.is_empty() {
+                        ui.separator();
+                        ui.heading("Documents, last 7 days");
+                        let mut documents: Vec<_> = documents.into_iter().collect();
+                        documents.sort_by_key(|d| std::cmp::Reverse(d.1));
+                        egui::ScrollArea::vertical().id_source("stats_documents").max_height(120.0).show(ui, |ui| {
+                            egui::Grid::new("stats_documents_grid").striped(true).show(ui, |ui| {
+                                for (document, secs) in documents {
+                                    ui.label(document);
+                                    ui.label(format_hours(secs));
+                                    ui.end_row();
+                                }
+                            });
+                        });
+                    }
 
-                        if ui.button("Close Menu").clicked() {
-                            self.show_context_menu = false;
+                    ui.separator();
+                    ui.heading("Last 7 days");
+                    let mut week_total = 0;
+                    egui::Grid::new("stats_week").striped(true).show(ui, |ui| {
+                        for day in today.iter_days().rev().take(7) {
+                            let secs = self.stats.day(day).map_or(0, |d| d.total_seconds());
+                            week_total += secs;
+                            ui.label(day.format("%a %Y-%m-%d").to_string());
+                            ui.label(format_hours(secs));
+                            ui.end_row();
                         }
                     });
-                });
-        }
+                    ui.label(format!("Week total: {}", format_hours(week_total)));
 
-        if self.show_alarm_config {
-            egui::Window::new("Set Alarm").show(ctx, |ui| {
-                if self.alarm_seconds.is_none() {
-                    self.alarm_seconds = Some(180);
-                }
+                    ui.separator();
+                    ui.heading("Last 14 days");
+                    let mut days: Vec<_> = today.iter_days().rev().take(14).collect();
+                    days.reverse();
+                    ui.horizontal(|ui| {
+                        ui.radio_value(&mut self.chart_metric, ChartMetric::TrackedTime, "Tracked minutes");
+                        ui.radio_value(&mut self.chart_metric, ChartMetric::ImagesViewed, "Images viewed");
+                    });
 
-                ui.add(
-                    egui::Slider::new(self.alarm_seconds.as_mut().unwrap(), 10..=3600)
-                        .text("Trigger Alarm After (sec)")
-                );
+                    // Every day gets a bar, empty ones at zero height, so the axis stays continuous.
+                    let metric = self.chart_metric;
+                    let bars: Vec<_> = days
+                        .iter()
+                        .enumerate()
+                        .map(|(i, day)| {
+                            let stats = self.stats.day(*day);
+                            let value = match metric {
+                                ChartMetric::TrackedTime => stats.map_or(0, |d| d.total_seconds()) as f64 / 60.0,
+                                ChartMetric::ImagesViewed => stats.map_or(0, |d| d.images_viewed) as f64,
+                            };
+                            egui_plot::Bar::new(i as f64, value)
+                                .width(0.7)
+                                .name(day.format("%a %Y-%m-%d"))
+                                .fill(egui::Color32::from_rgb(200, 40, 40))
+                        })
+                        .collect();
+                    let chart = egui_plot::BarChart::new(bars).element_formatter(Box::new(move |bar, _| match metric {
+                        ChartMetric::TrackedTime => format!("{}\n{}", bar.name, format_hours((bar.value * 60.0) as u64)),
+                        ChartMetric::ImagesViewed => format!("{}\n{} images", bar.name, bar.value),
+                    }));
+
+                    let labels: Vec<String> = days.iter().map(|d| d.format("%m-%d").to_string()).collect();
+                    egui_plot::Plot::new("stats_chart")
+                        .height(160.0)
+                        .allow_zoom(false)
+                        .allow_drag(false)
+                        .allow_scroll(false)
+                        .include_y(0.0)
+                        .show_grid([false, true])
+                        .x_axis_formatter(move |x, _, _| {
+                            let i = x.round();
+                            if (x - i).abs() < 1e-6 && i >= 0.0 {
+                                labels.get(i as usize).cloned().unwrap_or_default()
+                            } else {
+                                String::new()
+                            }
+                        })
+                        .show(ui, |plot_ui| plot_ui.bar_chart(chart));
 
-                if ui.button("Choose Sound").clicked() {
-                    if let Some(path) = FileDialog::new().add_filter("Audio", &["mp3", "wav", "ogg", "mp4"]).pick_file() {
-                        self.alarm_sound_path = Some(path);
+                    if ui.button("Export Chart Data...").clicked() {
+                        if let Some(path) = FileDialog::new()
+                            .set_title("Export Daily Stats")
+                            .set_file_name("daily-stats.csv")
+                            .add_filter("CSV", &["csv"])
+                            .save_file()
+                        {
+                            self.export_status = Some(match self.stats.export_days(&days, &path) {
+                                Ok(()) => format!("Exported to {}", path.display()),
+                                Err(e) => format!("Export failed: {}", e),
+                            });
+                        }
                     }
-                }
 
-                if ui.button("Set Alarm").clicked() {
-                    let seconds = self.alarm_seconds.unwrap_or(180);
-                    self.alarm_duration = Some(Duration::from_secs(seconds));
-                    self.alarm_triggered = false;
-                    self.show_alarm_config = false;
-                    self.save_config();
-                }
-            });
+                    ui.separator();
+                    ui.label(format!("All time: {}", format_hours(self.stats.all_time_seconds())));
+                    ui.small(format!("Saved to {}", self.stats.path().display()));
+
+                    if !self.stats.most_viewed().is_empty() {
+                        ui.separator();
+                        ui.heading("Most viewed images, last 30 days");
+                        egui::Grid::new("stats_most_viewed").striped(true).show(ui, |ui| {
+                            for count in self.stats.most_viewed() {
+                                let name = count.path.file_name().unwrap_or(count.path.as_os_str());
+                                ui.label(name.to_string_lossy()).on_hover_text(count.path.display().to_string());
+                                ui.label(format!("{}×", count.views));
+                                ui.label(format_hours(count.shown_seconds as u64));
+                                ui.end_row();
+                            }
+                        });
+                    }
+
+                    ui.separator();
+                    let mut use_database = self.settings.general.stats_database;
+                    if ui
+                        .checkbox(&mut use_database, "Keep statistics in a database")
+                        .on_hover_text("Also keeps every session and image view, in stats.sqlite next to the settings.")
+                        .changed()
+                    {
+                        self.set_stats_database(use_database);
+                        self.save_config();
+                    }
+                    ui.horizontal(|ui| {
+                        ui.label("Keep the last");
+                        let retention = ui.add(egui::DragValue::new(&mut self.settings.general.stats_retention_days).clamp_range(1..=3650));
+                        ui.label("days");
+                        if retention.changed() {
+                            self.config_dirty = true;
+                        }
+                        if ui.button("Clean Up Now").clicked() {
+                            match self.stats.prune(self.settings.general.stats_retention_days) {
+                                Ok(removed) => self.export_status = Some(format!("Removed {} days of statistics", removed)),
+                                Err(e) => self.toast(Level::Error, tr_args("Couldn't clean up the statistics: {error}", &[("error", &e)])),
+                            }
+                        }
+                    });
+
+                    ui.separator();
+                    ui.heading("Session log");
+                    let mut export = false;
+                    let mut changed = false;
+                    ui.horizontal(|ui| {
+                        export = ui.button("Export Session Log...").clicked();
+
+                        let mut auto_export = self.settings.general.auto_export_dir.is_some();
+                        if ui.checkbox(&mut auto_export, "Auto-export on exit").changed() {
+                            self.settings.general.auto_export_dir = if auto_export {
+                                FileDialog::new().set_title("Auto-export Folder").pick_folder()
+                            } else {
+                                None
+                            };
+                            changed = true;
+                        }
+                    });
+                    if let Some(dir) = &self.settings.general.auto_export_dir {
+                        ui.small(format!("Exporting to {}", dir.display()));
+                    }
+                    if let Some(status) = &self.export_status {
+                        ui.label(status);
+                    }
+                    if export {
+                        self.export_session_log();
+                    }
+                    if changed {
+                        self.save_config();
+                    }
+                });
+            self.show_stats = open;
         }
 
+        if self.crossfade.as_ref().is_some_and(fade::Crossfade::is_finished) {
+            self.crossfade = None;
+        }
 
         egui::CentralPanel::default().show(ctx, |ui| {
+            // Without a title bar the window is moved by dragging anywhere that
+            // doesn't drag something else.
+            let can_move = !self.decorations_visible && !self.fullscreen;
+            let background = ui.interact(ui.max_rect(), ui.id().with("move_window"), egui::Sense::drag());
+            if can_move && background.drag_started_by(egui::PointerButton::Primary) {
+                ctx.send_viewport_cmd(egui::ViewportCommand::StartDrag);
+            }
+            // Here rather than after the image, where it would cover the image
+            // and thumbnails for every click after this one.
+            background.context_menu(|ui| self.context_menu(ui, ctx, frame));
+
+            if let Some(error) = self.config_error.clone().filter(|_| !self.config_error_dismissed) {
+                self.config_error_banner(ui, error);
+            }
 
             if self.current_image.is_none() && self.image_paths.is_empty() {
-                ui.label("No image to display. Right-click to add folders.");
+                if self.settings.general.private_folders {
+                    ui.label(tr("Folders aren't remembered on this computer. Right-click to add folders for this session."));
+                } else {
+                    ui.label(tr("No image to display. Right-click to add folders."));
+                }
+            }
+
+            if self.current_image.is_some() {
+                self.sync_board(ctx);
             }
 
-            if let Some(img) = &self.current_image {
-                let img_width = img.width() as f32;
-                let img_height = img.height() as f32;
-                let aspect_ratio = img_width / img_height;
+            if self.show_browser {
+                self.image_rect = None;
+                for thumbnail in self.browser(ctx, ui) {
+                    thumbnail.context_menu(|ui| self.context_menu(ui, ctx, frame));
+                }
+            } else if let Some(img) = &self.current_image {
+                let image_size = egui::vec2(img.width() as f32, img.height() as f32);
+                let shown = self.view.shown_size(image_size.x, image_size.y);
+                let fit_mode = self.settings.display.fit_mode;
+                let pixels_per_point = ctx.pixels_per_point();
+                let layout = self.settings.display.board.layout;
 
-                let available = ui.available_size();
-                let mut target_width = available.x;
-                let mut target_height = target_width / aspect_ratio;
+                // The window keeps the monitor's size while fullscreen, and until it has left.
+                let fullscreen = self.fullscreen || ctx.input(|i| i.viewport().fullscreen == Some(true));
+                let resize_window = self.settings.display.image_fit == ImageFit::ResizeWindow && !fullscreen;
+                let frame_margin = frame_margin(ctx);
 
-                if target_height > available.y {
-                    target_height = available.y;
-                    target_width = target_height * aspect_ratio;
+                if resize_window {
+                    let mut room = ui.available_size();
+                    if let Some(work_area) = placement::work_area(ctx) {
+                        room = room.min(work_area - frame_margin);
+                    }
+                    let wanted = self.window_size_for(shown, room, pixels_per_point).max(self.min_window_size() - frame_margin);
+                    if self.last_size.is_none_or(|s| (s - wanted).length_sq() > 1.0) {
+                        ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(wanted + frame_margin));
+                        self.last_size = Some(wanted);
+                    }
                 }
 
-                let target_size = egui::Vec2::new(target_width.max(300.0), target_height.max(200.0));
+                let cells = layout.cells(ui.available_rect_before_wrap());
+                let room = cells[0];
+                let (target_size, extent) = fit_mode.layout(shown, room.size(), pixels_per_point);
+                self.view.set_extent(extent);
+                let one_to_one = fit_mode == FitMode::ActualPixels && extent == egui::Vec2::splat(1.0);
+                self.set_texture_options(self.wanted_texture_options(image_size, one_to_one));
 
-                if self.last_size.map_or(true, |s| (s - target_size).length_sq() > 1.0) {
-                    ctx.send_viewport_cmd(egui::ViewportCommand::InnerSize(target_size + egui::vec2(16.0, 56.0)));
-                    self.last_size = Some(target_size);
+                if let Some(texture_id) = self.shown_texture(ctx) {
+                    let rect = egui::Rect::from_center_size(room.center(), target_size);
+                    let response = ui.allocate_rect(rect, egui::Sense::click_and_drag());
+                    let letterboxed = (room.size() - target_size).max_elem() > 1.0;
+                    if self.settings.display.blurred_letterbox && letterboxed {
+                        if let Some(blurred) = self.letterbox_texture(ctx) {
+                            let unzoomed = zoom::View::turned(self.view.quarter_turns()).with_flips(self.view.flips()).with_crop(self.view.crop());
+                            unzoomed.paint(ui.painter(), room, blurred);
+                        }
+                    }
+                    self.settings.display.backdrop.paint(ui.painter(), rect);
+                    match &self.crossfade {
+                        Some(crossfade) => {
+                            crossfade.paint(ui.painter());
+                            self.view.paint_faded(ui.painter(), rect, texture_id, crossfade.progress());
+                            ctx.request_repaint();
+                        }
+                        None => self.view.paint(ui.painter(), rect, texture_id),
+                    }
+                    self.image_rect = Some(rect);
+                    if self.settings.display.grid.enabled {
+                        self.settings.display.grid.paint(ui.painter(), rect, &self.view, image_size);
+                    }
+                    if self.measure.active {
+                        self.measure.update(&response, &self.view);
+                    }
+                    if self.measure.active || self.measure.has_lines() {
+                        self.measure.paint(ui.painter(), rect, &self.view, image_size, response.interact_pointer_pos());
+                    }
+                    if self.crop_tool.active {
+                        if response.hovered() {
+                            ctx.set_cursor_icon(egui::CursorIcon::Crosshair);
+                        }
+                        self.crop_tool.paint(ui.painter(), rect, response.interact_pointer_pos());
+                        if let Some(region) = self.crop_tool.update(&response) {
+                            self.crop_to(region, rect);
+                        }
+                    }
+                    let hud_visible = self.hud_visible();
+                    if let Some(label) = self.view.flip_label().filter(|_| hud_visible) {
+                        flip_badge(ui.painter(), rect, label);
+                    }
+                    if self.settings.display.histogram.enabled && hud_visible {
+                        self.update_histogram(ctx);
+                        let shown = rect.intersect(ui.clip_rect());
+                        if let Some(histogram) = &self.histogram {
+                            self.settings.display.histogram.paint(ui.painter(), shown, histogram);
+                        }
+                    }
+                    if self.settings.display.info_bar.enabled && hud_visible {
+                        if response.hovered() && ctx.input(|i| i.pointer.delta() != egui::Vec2::ZERO) {
+                            self.info_bar_shown_at = Instant::now();
+                        }
+                        let shown_for = self.info_bar_shown_at.elapsed().as_secs_f32();
+                        let visibility = self.settings.display.info_bar.visibility(shown_for);
+                        if let (Some(path), true) = (&self.current_path, visibility > 0.0) {
+                            let pixels = [image_size.x as u32, image_size.y as u32];
+                            let file_size = self.current_info.as_ref().and_then(|info| info.file_size);
+                            self.settings.display.info_bar.paint(ui.painter(), rect, path, pixels, file_size, visibility);
+                            if self.settings.display.info_bar.auto_hide {
+                                let until_fade = self.settings.display.info_bar.hide_after_seconds as f32 - shown_for;
+                                ctx.request_repaint_after(Duration::from_secs_f32(until_fade.max(0.0)));
+                            }
+                        }
+                    }
+                    let response = response.context_menu(|ui| self.context_menu(ui, ctx, frame));
+                    self.zoom_and_pan(ui, &response);
+                    if self.eyedropper.active {
+                        self.use_eyedropper(ctx, &response);
+                    }
+                    // A finger on the image is swiping, not moving the window.
+                    if can_move && !self.view.can_pan() && !self.tool_drags() && !self.touch.touching() && response.drag_started_by(egui::PointerButton::Primary) {
+                        ctx.send_viewport_cmd(egui::ViewportCommand::StartDrag);
+                    }
+                }
+                if layout != BoardLayout::Single {
+                    self.board_ui(ui, ctx, frame, cells, can_move);
                 }
+            }
 
-                if let Some(texture) = &self.texture {
-                    ui.allocate_ui_with_layout(
-                        ui.available_size(),
-                        egui::Layout::centered_and_justified(egui::Direction::TopDown),
-                        |ui| {
-                            ui.image((texture.id(), target_size));
-                        },
-                    );
+            let ring = &self.settings.timer.ring;
+            if let Some(progress) = self.alarm_progress().filter(|_| ring.enabled && self.hud_visible()) {
+                let pulse = ((ui.input(|i| i.time) * std::f64::consts::TAU).sin() * 0.5 + 0.5) as f32;
+                if progress >= 1.0 {
+                    ctx.request_repaint();
                 }
+                ring.paint(ui.painter(), ui.clip_rect(), progress, pulse);
             }
         });
 
+        if self.measure.active {
+            self.measure.toolbar(ctx);
+        }
+        if self.eyedropper.active && self.settings.display.keep_swatches {
+            if let Some(color) = self.eyedropper.swatches(ctx) {
+                self.pick_color(ctx, color);
+            }
+        }
+
+        if self.second_view.take_closed() {
+            self.settings.display.second_view.open = false;
+            self.save_config();
+        }
+        if self.settings.display.second_view.open {
+            let picture = self.second_view_picture(ctx);
+            self.second_view.show(ctx, &self.settings.display.second_view, picture);
+            let placement = self.second_view.placement();
+            if placement.is_some() && placement != self.settings.display.second_view.placement {
+                self.settings.display.second_view.placement = placement;
+                self.config_dirty = true;
+            }
+        } else {
+            self.second_view.hide();
+            self.upcoming = None;
+        }
+
         let mut apply_changes = false;
+        let mut privacy_changed = false;
+        let mut temporary_changed = false;
+        let mut remove = None;
+        let mut relocate = None;
+        let mut order_changed = false;
 
         if self.show_folder_manager {
-            egui::Window::new("Folder Manager")
+            let unscanned = self.folder_map.keys().any(|folder| !self.folder_scans.contains_key(folder));
+            if unscanned && self.pending_image_list.is_none() {
+                self.start_scan(ctx, ScanThen::CountOnly);
+            }
+            egui::Window::new(tr("Folder Manager"))
+                .id(egui::Id::new("folder_manager"))
                 .open(&mut self.show_folder_manager)
                 .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
                 .collapsible(false)
                 .resizable(true)
                 .show(ctx, |ui| {
-                    for (folder, enabled) in &mut self.folder_map {
-                        ui.checkbox(enabled, &format!("{}", folder.display()));
+                    let today = chrono::Local::now().date_naive();
+                    let mut row_rects = Vec::new();
+                    for (index, (folder, enabled)) in self.folder_map.iter_mut().enumerate() {
+                        let mut temporary = self.temporary_folders.contains(folder);
+                        let row = ui.horizontal(|ui| {
+                            let handle = ui
+                                .add(egui::Label::new("☰").sense(egui::Sense::drag()))
+                                .on_hover_cursor(egui::CursorIcon::Grab)
+                                .on_hover_text(tr("Drag to reorder"));
+                            if handle.drag_started() {
+                                self.dragged_folder = Some(index);
+                            }
+                            let label = egui::RichText::new(folder.display().to_string());
+                            ui.checkbox(enabled, if temporary { label.italics().weak() } else { label });
+                            match self.folder_scans.get(folder) {
+                                Some(scan) => {
+                                    let when = match scan.scanned_at.date_naive() == today {
+                                        true => scan.scanned_at.format("%H:%M"),
+                                        false => scan.scanned_at.format("%Y-%m-%d %H:%M"),
+                                    };
+                                    let count = ui.weak(tr_count("{count} images", scan.images));
+                                    count.on_hover_text(tr_args("Scanned {when}", &[("when", &when)]));
+                                    if scan.reachable && scan.images == 0 {
+                                        ui.colored_label(egui::Color32::from_rgb(220, 120, 0), "⚠")
+                                            .on_hover_text(tr("No PNG, JPEG or BMP images directly in this folder."));
+                                    }
+                                }
+                                None => {
+                                    ui.weak(tr("scanning..."));
+                                }
+                            }
+                            if ui.checkbox(&mut temporary, tr("Temporary")).on_hover_text(tr("Not saved, so gone next time")).changed() {
+                                if temporary {
+                                    self.temporary_folders.insert(folder.clone());
+                                } else {
+                                    self.temporary_folders.remove(folder);
+                                }
+                                temporary_changed = true;
+                            }
+                            if !folder.is_dir() {
+                                ui.colored_label(egui::Color32::from_rgb(220, 120, 0), tr("Folder missing"));
+                                if ui.button(tr("Relocate...")).clicked() {
+                                    relocate = Some(folder.clone());
+                                }
+                            }
+                            if self.confirm_remove_folder.as_ref() == Some(folder) {
+                                ui.label(tr("Remove?"));
+                                if ui.button(tr("Yes")).clicked() {
+                                    remove = Some(folder.clone());
+                                }
+                                if ui.button(tr("No")).clicked() {
+                                    self.confirm_remove_folder = None;
+                                }
+                            } else if ui.button(tr("Remove")).clicked() {
+                                self.confirm_remove_folder = Some(folder.clone());
+                            }
+                        });
+                        row_rects.push(row.response.rect);
+                    }
+
+                    if let Some(from) = self.dragged_folder {
+                        ui.ctx().set_cursor_icon(egui::CursorIcon::Grabbing);
+                        // The gap between rows nearest the pointer, where the folder goes.
+                        let pointer_y = ui.ctx().pointer_interact_pos().map(|p| p.y);
+                        let to = pointer_y
+                            .map(|y| row_rects.iter().position(|r| y < r.center().y).unwrap_or(row_rects.len()))
+                            .unwrap_or(from);
+                        let gap_y = match row_rects.get(to) {
+                            Some(rect) => rect.top(),
+                            None => row_rects.last().map_or(0.0, |rect| rect.bottom()),
+                        };
+                        ui.painter().hline(ui.min_rect().x_range(), gap_y, ui.visuals().selection.stroke);
+                        if !ui.input(|i| i.pointer.any_down()) {
+                            self.dragged_folder = None;
+                            let to = if to > from { to - 1 } else { to };
+                            if to != from && to < self.folder_map.len() {
+                                self.folder_map.move_index(from, to);
+                                order_changed = true;
+                            }
+                        }
                     }
 
-                    if ui.button("Apply Changes").clicked() {
-                        apply_changes = true;
+                    let enabled_images: usize = self
+                        .folder_map
+                        .iter()
+                        .filter(|(_, on)| **on)
+                        .filter_map(|(folder, _)| self.folder_scans.get(folder))
+                        .map(|scan| scan.images)
+                        .sum();
+                    ui.label(tr_count("Total: {count} images in enabled folders", enabled_images));
+                    if self.pending_image_list.is_some() {
+                        ui.weak(tr("Scanning folders..."));
                     }
+
+                    ui.horizontal(|ui| {
+                        if ui.button(tr("Apply Changes")).clicked() {
+                            apply_changes = true;
+                        }
+                        if ui.button(tr("Sort by Path")).on_hover_text(tr("Put the folders in alphabetical order")).clicked() {
+                            self.folder_map.sort_keys();
+                            order_changed = true;
+                        }
+                    });
+
+                    ui.separator();
+                    privacy_changed = ui
+                        .checkbox(&mut self.settings.general.private_folders, tr("Don't remember folders on this computer"))
+                        .on_hover_text(tr("Folders added are kept for this session only. Other settings are still saved."))
+                        .changed();
                 });
         }
 
         if apply_changes {
-            self.refresh_image_list();
-            self.load_image(ctx);
+            self.start_scan(ctx, ScanThen::ReplaceAndShow);
+        }
+        if let Some(folder) = remove {
+            self.confirm_remove_folder = None;
+            self.remove_folder(ctx, &folder);
+        }
+        if let Some(old) = relocate {
+            let picked = FileDialog::new()
+                .set_title(tr_args("Where is {folder} now?", &[("folder", &old.file_name().unwrap_or_default().to_string_lossy())]))
+                .pick_folder();
+            if let Some(new) = picked {
+                self.relocate_folder(ctx, &old, new);
+            }
+        }
+        if temporary_changed || order_changed {
+            self.save_config();
+        }
+        if privacy_changed {
+            // Takes the folders out of the file, or puts them back.
+            self.save_config();
+            if self.settings.general.private_folders {
+                self.forget_opened_images();
+                // Again, so the backup of the previous version loses them too.
+                self.save_config();
+            }
+        }
+
+        self.hide_idle_cursor(ctx);
+    }
+
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        self.awake.set(Wake::Off);
+        self.finish_view();
+        let tracked: Duration = self.exe_times.values().sum();
+        // Too late for a toast, so these only go to the log.
+        if let Err(e) = self.stats.record_session(self.started_at, tracked, self.view_log.len()) {
+            log::warn!("Couldn't record the session: {}", e);
+        }
+        if let Err(e) = self.stats.flush() {
+            log::warn!("Couldn't save the statistics: {}", e);
+        }
+        if self.config_dirty {
+            self.save_config();
+        }
+
+        if let Some(dir) = &self.settings.general.auto_export_dir {
+            if !self.view_log.is_empty() {
+                match session::auto_export(&self.view_log, dir) {
+                    Ok(path) => log::info!("Session log exported to {:?}", path),
+                    Err(e) => log::error!("Failed to export session log: {}", e),
+                }
+            }
+        }
+    }
+}
+
+/// Moves `folder` to the front of the recent folders, dropping the oldest past the limit.
+fn remember_folder(recent_folders: &mut Vec<PathBuf>, folder: &Path) {
+    recent_folders.retain(|f| f != folder);
+    recent_folders.insert(0, folder.to_path_buf());
+    recent_folders.truncate(MAX_RECENT_FOLDERS);
+}
+
+/// The first key pressed this frame, with the modifiers held, for recording a
+/// new binding.
+fn pressed_combination(ui: &egui::Ui) -> Option<(egui::Key, egui::Modifiers)> {
+    ui.input(|i| {
+        i.events.iter().find_map(|event| match event {
+            egui::Event::Key { key, pressed: true, modifiers, .. } => Some((*key, *modifiers)),
+            _ => None,
+        })
+    })
+}
+
+/// A small tag in the corner of the image so a mirrored image isn't forgotten.
+/// Room around the image in a window sized to it. The system draws the frame, so
+/// it doesn't grow with the UI scale.
+fn frame_margin(ctx: &egui::Context) -> egui::Vec2 {
+    egui::vec2(16.0, 56.0) / ctx.zoom_factor()
+}
+
+fn flip_badge(painter: &egui::Painter, image_rect: egui::Rect, label: &str) {
+    let galley = painter.layout_no_wrap(label.to_string(), egui::FontId::proportional(12.0), egui::Color32::WHITE);
+    let size = galley.size() + egui::vec2(8.0, 4.0);
+    let badge = egui::Rect::from_min_size(image_rect.left_bottom() + egui::vec2(6.0, -6.0 - size.y), size);
+    painter.rect_filled(badge, 3.0, egui::Color32::from_black_alpha(160));
+    painter.galley(badge.min + egui::vec2(4.0, 2.0), galley, egui::Color32::WHITE);
+}
+
+/// Opens the image at `path` turned the way its EXIF orientation says, so
+/// photos taken on their side come up upright before any rotation of our own.
+fn open_upright(path: &Path) -> image::ImageResult<DynamicImage> {
+    let mut decoder = image::ImageReader::open(path)?.with_guessed_format()?.into_decoder()?;
+    let orientation = decoder.orientation().unwrap_or(image::metadata::Orientation::NoTransforms);
+    let mut img = DynamicImage::from_decoder(decoder)?;
+    img.apply_orientation(orientation);
+    Ok(img)
+}
+
+/// The image's pixels as egui takes them for a texture.
+fn color_image(img: &DynamicImage) -> egui::ColorImage {
+    let rgba = img.to_rgba8();
+    let size = [img.width() as usize, img.height() as usize];
+    egui::ColorImage::from_rgba_unmultiplied(size, rgba.as_raw())
+}
+
+/// Like `open_upright`, also picking up the details the Image Info window shows
+/// in the same read of the file.
+fn open_with_info(path: &Path) -> image::ImageResult<(DynamicImage, image_info::ImageInfo)> {
+    let reader = image::ImageReader::open(path)?.with_guessed_format()?;
+    let format = reader.format();
+    let mut decoder = reader.into_decoder()?;
+    let raw_exif = decoder.exif_metadata().unwrap_or(None);
+    let orientation = decoder.orientation().unwrap_or(image::metadata::Orientation::NoTransforms);
+    let color = decoder.color_type();
+    let mut img = DynamicImage::from_decoder(decoder)?;
+    img.apply_orientation(orientation);
+    let file_size = fs::metadata(path).ok().map(|m| m.len());
+    let info = image_info::ImageInfo::new(img.width(), img.height(), file_size, format, color, raw_exif);
+    Ok((img, info))
+}
+
+/// Every image in the enabled folders, shuffled, and what was found in each
+/// folder, enabled or not.
+fn scan_folders(folder_map: &FolderMap) -> ScanResult {
+    let mut collected_paths = Vec::new();
+    let mut seen = HashSet::new();
+    let mut scans = HashMap::new();
+
+    for (folder, enabled) in folder_map {
+        let (paths, scan) = scan_folder(folder);
+        scans.insert(folder.clone(), scan);
+        if *enabled {
+            for path in paths {
+                if seen.insert(path.clone()) {
+                    collected_paths.push(path);
+                }
+            }
         }
     }
+    collected_paths.shuffle(&mut rand::thread_rng());
+    (collected_paths, scans)
+}
+
+fn scan_folder(folder: &Path) -> (Vec<PathBuf>, FolderScan) {
+    let paths = get_image_paths(folder);
+    let scan = FolderScan {
+        images: paths.len(),
+        reachable: folder.is_dir(),
+        scanned_at: chrono::Local::now(),
+    };
+    (paths, scan)
 }
 
 fn get_image_paths(folder: &Path) -> Vec<PathBuf> {
@@ -411,107 +6012,423 @@ fn get_image_paths(folder: &Path) -> Vec<PathBuf> {
         .collect()
 }
 
-fn play_alarm_sound(path: PathBuf) {
-    use std::io::BufReader;
-    use rodio::{Decoder, OutputStream, Sink};
-
-    println!("Trying to play {:?}", path);
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
 
-    if let Ok((_stream, stream_handle)) = OutputStream::try_default() {
-        if let Ok(file) = std::fs::File::open(&path) {
-            if let Ok(source) = Decoder::new(BufReader::new(file)) {
-                let sink = Sink::try_new(&stream_handle).unwrap();
-                sink.append(source);
-                sink.sleep_until_end(); // for testing
-            } else {
-                println!("Failed to decode audio");
-            }
-        } else {
-            println!("Failed to open file: {:?}", path);
-        }
+fn format_clock(total_secs: u64) -> String {
+    let hours = total_secs / 3600;
+    let minutes = (total_secs % 3600) / 60;
+    let seconds = total_secs % 60;
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, seconds)
     } else {
-        println!("No audio output stream found");
+        format!("{:02}:{:02}", minutes, seconds)
     }
 }
 
+fn format_clock_tenths(time: Duration) -> String {
+    format!("{}.{}", format_clock(time.as_secs()), time.subsec_millis() / 100)
+}
+
 fn main() {
     use rand::seq::SliceRandom;
     use rand::thread_rng;
 
-    let mut folder_map = HashMap::new();
+    use clap::Parser;
+
+    let args = cli::Args::parse();
+    logging::init();
+    let launch_paths = args.paths;
+    // Made absolute, as the viewer already running may be in another directory.
+    let config_override = args.config.map(|p| std::path::absolute(&p).unwrap_or(p));
+    if let Some(path) = &config_override {
+        paths::set_config_path(path.clone());
+    } else if args.portable {
+        paths::set_portable();
+    }
+
+    // A second launch passes its folders and images to the viewer already running.
+    let lock = if args.new_instance {
+        None
+    } else {
+        match instance::claim(&launch_paths, config_override.as_deref()) {
+            Some(lock) => Some(lock),
+            None => return,
+        }
+    };
+
+    let launched_at_login = args.minimized;
+    let launch_paths: Vec<PathBuf> = launch_paths
+        .into_iter()
+        .map(|p| std::path::absolute(&p).unwrap_or(p))
+        .collect();
+    autostart::refresh();
+
+    let mut folder_map = FolderMap::new();
+    let mut recent_folders = Vec::new();
+    let mut image_rotations = HashMap::new();
+    let mut image_crops = HashMap::new();
     let mut target_exe_name = None;
+    let mut hotkey_bindings = hotkey::default_bindings();
+    let mut shortcuts = shortcut::default_shortcuts();
+    let mut gestures = gesture::default_gestures();
+    let mut window_placement = None;
     let mut current_index = 0;
-    let mut is_pinned = false;
-    let mut alarm_seconds = None;
-    let mut alarm_duration = None;
-    let mut alarm_sound_path = None;
+    let mut pin_mode = PinMode::default();
+    let mut alarms = Vec::new();
+    let mut profiles = Vec::new();
+    let mut active_profile = None;
+    let mut settings = Settings::default();
+    let mut elapsed_time = Duration::ZERO;
+
+    let mut notices = notices::Notices::default();
+    let mut config_error = None;
+    let loaded = match settings_file::read::<ConfigData>(paths::config_path()) {
+        settings_file::Loaded::Read(config) => Some(config),
+        settings_file::Loaded::Restored(config, error) => {
+            i18n::set_language(config.settings.general.language);
+            let warning = tr_args("The settings file was damaged ({error}), so the previous version was restored.", &[("error", &error)]);
+            log::warn!("{}", warning);
+            notices.push(Level::Warning, warning, NOTICE_DURATION);
+            Some(config)
+        }
+        settings_file::Loaded::Failed(error) => {
+            config_error = Some(error);
+            None
+        }
+        settings_file::Loaded::Missing => None,
+    };
+    let loaded = loaded.map(ConfigData::migrate);
+    let config_version = loaded.as_ref().map_or(CONFIG_VERSION, |config| config.version);
+    let mut unknown_config = serde_json::Map::new();
+    if let Some(config) = loaded {
+        unknown_config = config.unknown;
+        folder_map = config.folder_map;
+        recent_folders = config.recent_folders;
+        image_rotations = config.image_rotations;
+        image_crops = config.image_crops;
+        target_exe_name = config.target_exe_name;
+        // Commands added since the config was written get their default keys.
+        hotkey_bindings.extend(config.hotkey_bindings);
+        shortcuts.extend(config.shortcuts);
+        gestures = config.gestures;
+        window_placement = config.window_placement;
+        current_index = config.current_index;
+        pin_mode = config.pin_mode.unwrap_or_default();
+        alarms = config.alarms;
+        active_profile = config
+            .active_profile
+            .and_then(|name| config.profiles.iter().position(|p| p.name == name));
+        profiles = config.profiles;
+        settings = config.settings;
+        if let Some(saved) = config.saved_elapsed.filter(|s| settings.general.persist_elapsed && stats::is_today(s.saved_at)) {
+            elapsed_time = Duration::from_secs_f64(saved.seconds.max(0.0));
+        }
+    }
+
+    i18n::set_language(settings.general.language);
 
-    if let Ok(data) = std::fs::read_to_string("viewer_config.json") {
-        if let Ok(config) = serde_json::from_str::<ConfigData>(&data) {
-            folder_map = config.folder_map;
-            target_exe_name = config.target_exe_name;
-            current_index = config.current_index;
-            is_pinned = config.is_pinned;
-            alarm_seconds = config.alarm_seconds;
-            alarm_sound_path = config.alarm_sound_path.clone();
-            alarm_duration = alarm_seconds.map(Duration::from_secs);
+    // Alarms the restored time is already past stay quiet unless asked to replay them.
+    if !settings.general.replay_missed_alarms {
+        for alarm in &mut alarms {
+            alarm.catch_up(elapsed_time);
         }
     }
 
-    let folder = if folder_map.is_empty() {
+    // A folder given on the command line stands in for the first-run picker. With a
+    // damaged config there's a choice to make in the viewer first.
+    // In private mode the viewer asks for folders itself.
+    if folder_map.is_empty() && config_error.is_none() && !settings.general.private_folders && !launch_paths.iter().any(|p| p.is_dir()) {
         let folder = FileDialog::new()
             .set_title("Select an image folder")
             .pick_folder()
             .expect("No folder selected");
 
-        folder_map.insert(folder.clone(), true);
-        folder
-    } else {
-        folder_map.keys().next().unwrap().clone()
+        remember_folder(&mut recent_folders, &folder);
+        folder_map.insert(folder, true);
+    }
+
+    let tracking = &settings.tracking;
+    let (title_filter, title_filter_error) = match TitleFilter::new(&tracking.target_title_pattern, tracking.target_title_regex) {
+        Ok(filter) => (filter, None),
+        Err(e) => (None, Some(e)),
+    };
+    let (document_pattern, document_pattern_error) = match DocumentPattern::new(&tracking.document_title_pattern) {
+        Ok(pattern) => (Some(pattern), None),
+        Err(e) => (None, Some(e)),
     };
 
-    let mut image_paths = get_image_paths(&folder);
-    image_paths.shuffle(&mut thread_rng());
+    let (image_paths, current_index, folder_scans) = match deck::load().filter(|_| settings.general.persist_deck && !settings.general.private_folders) {
+        Some(saved) => {
+            let (current, scans) = scan_folders(&folder_map);
+            let (image_paths, current_index) = deck::reconcile(saved, current);
+            (image_paths, current_index, scans)
+        }
+        None => {
+            let mut image_paths = folder_map.keys().next().map(|folder| get_image_paths(folder)).unwrap_or_default();
+            image_paths.shuffle(&mut thread_rng());
+            (image_paths, current_index, HashMap::new())
+        }
+    };
 
+    let [min_width, min_height] = settings.display.min_window_size.map(|side| side.max(MIN_WINDOW_SIDE) as f32);
+    let mut viewport = egui::ViewportBuilder::default()
+        .with_inner_size(egui::vec2(800.0, 600.0))
+        .with_min_inner_size(egui::vec2(min_width, min_height))
+        .with_resizable(true);
+    if let Some(placement) = window_placement {
+        viewport = placement.apply(viewport);
+    }
     let native_options = eframe::NativeOptions {
-        viewport: egui::ViewportBuilder::default()
-            .with_inner_size(egui::vec2(800.0, 600.0))
-            .with_resizable(true),
+        viewport,
+        // Keeps the system theme up to date in `frame.info()` for Follow System.
+        follow_system_theme: true,
         ..Default::default()
     };
 
 
+    let mut stats = StatsStore::load(paths::stats_path());
+    if settings.general.stats_database {
+        if let Err(e) = stats.open_database(paths::stats_database_path()) {
+            let message = tr_args("Couldn't open the statistics database: {error}", &[("error", &e)]);
+            log::error!("{}", message);
+            notices.push(Level::Error, message, NOTICE_DURATION);
+        }
+    }
+    match stats.take_damaged() {
+        Some(stats::Damaged::BackedUp(backup, error)) => {
+            let backup = backup.display().to_string();
+            let warning = tr_args("The statistics file was damaged ({error}), so they start over. The old file was kept as {backup}.", &[("error", &error), ("backup", &backup)]);
+            log::warn!("{}", warning);
+            notices.push(Level::Warning, warning, NOTICE_DURATION);
+        }
+        Some(stats::Damaged::Kept(error)) => {
+            let message = tr_args("The statistics file couldn't be read ({error}), so nothing is saved to it until the viewer is restarted.", &[("error", &error)]);
+            log::error!("{}", message);
+            notices.push(Level::Error, message, NOTICE_DURATION);
+        }
+        None => {}
+    }
+
     let _ = eframe::run_native(
         "Germi Board",
         native_options,
         
-        Box::new(move |_cc| {
+        Box::new(move |cc| {
+            // Ctrl+Plus, Minus and 0 are the viewer's own shortcuts, which keep the UI scale.
+            cc.egui_ctx.options_mut(|o| o.zoom_with_keyboard = false);
             Box::new(ImageViewerApp {
                 image_timer: Instant::now(),
                 image_paths,
                 current_index,
                 current_image: None,
                 texture: None,
+                grayscale: false,
+                gray_texture: None,
+                value_filter: posterize::ValueFilter::Off,
+                value_textures: HashMap::new(),
+                pending_values: None,
+                texture_options: egui::TextureOptions::LINEAR,
+                sharp_pixels: false,
+                image_rect: None,
+                crossfade: None,
+                board_slots: Vec::new(),
+                board_dealt: 0,
+                board_cells: Vec::new(),
+                second_view: second_view::SecondView::new(),
+                upcoming: None,
+                letterbox: None,
+                pending_letterbox: None,
+                histogram: None,
+                pending_histogram: None,
+                show_grid_settings: false,
+                eyedropper: eyedropper::Eyedropper::default(),
+                measure: measure::Measure::default(),
+                crop_tool: crop::CropTool::default(),
+                info_bar_shown_at: Instant::now(),
+                current_info: None,
+                show_image_info: false,
+                view: zoom::View::default(),
+                image_rotations,
+                image_crops,
                 last_size: None,
-                last_hover: Instant::now(),
+                pointer: pointer::PointerActivity::new(),
                 decorations_visible: true,
+                decorations_toggled_at: None,
+                frame_offset: egui::Vec2::ZERO,
                 folder_map,
+                recent_folders,
+                temporary_folders: HashSet::new(),
                 show_folder_manager: false,
-                show_context_menu: false,
-                context_menu_pos: egui::pos2(100.0, 100.0),
+                confirm_remove_folder: None,
+                dragged_folder: None,
+                context_menu_open: false,
                 target_exe_name,
+                title_filter,
+                title_filter_error,
+                document_pattern,
+                document_pattern_error,
+                current_document: None,
                 target_is_active: false,
                 target_is_hovered: false,
-                elapsed_time: Duration::ZERO,
+                tracker: Tracker::spawn(cc.egui_ctx.clone()),
+                foreground_exe: None,
+                exe_times: HashMap::new(),
+                window_opacity_pending: true,
+                fullscreen: false,
+                placement_before_fullscreen: None,
+                hover_faded: false,
+                other_app_fullscreen: false,
+                fullscreen_hidden: false,
+                click_through: false,
+                hotkeys: hotkey::Hotkeys::spawn(cc.egui_ctx.clone(), &hotkey_bindings),
+                shortcut_conflicts: shortcut::conflicts(&shortcuts, &hotkey_bindings),
+                hotkey_bindings,
+                recording_hotkey: None,
+                hotkey_record_hint: None,
+                shortcuts,
+                recording_shortcut: None,
+                show_shortcuts: false,
+                gestures,
+                touch: gesture::TouchGestures::default(),
+                tray: tray::Tray::new(cc.egui_ctx.clone()),
+                in_tray: false,
+                quit_requested: false,
+                start_with_windows: autostart::is_enabled(),
+                hide_on_start: launched_at_login && settings.general.start_minimized,
+                window_placement,
+                profiles,
+                active_profile,
+                auto_switch: AutoSwitch::default(),
+                show_profiles: false,
+                new_profile_name: String::new(),
+                renaming_profile: None,
+                instance: lock.map(|lock| lock.listen(cc.egui_ctx.clone())),
+                launch_paths,
+                pending_image_list: None,
+                folder_scans,
+                taskbar_progress: platform::taskbar::TaskbarProgress::new(),
+                awake: platform::power::KeepAwake::new(),
+                placement_changed_at: None,
+                snapped_rect: None,
+                hover_pending_since: None,
+                user_idle: false,
+                elapsed_time,
                 last_timer_check: Instant::now(),
-                is_pinned,
+                pin_mode,
+                pinned_on_top: false,
                 pin_state_changed: true,
-                alarm_seconds,
-                alarm_duration,
-                alarm_triggered: false,
-                alarm_sound_path,
+                pin_pending_since: None,
+                alarms,
                 show_alarm_config: false,
+                overtime_escalated: false,
+                view_overtime: Duration::ZERO,
+                toaster: None,
+                timer_paused: false,
+                last_schedule_check: chrono::Local::now(),
+                speaker: None,
+                pomodoro: PomodoroState::new(),
+                stats,
+                stats_unsaved: false,
+                show_stats: false,
+                chart_metric: ChartMetric::TrackedTime,
+                current_path: None,
+                view_started_at: 0,
+                view_tracked: Duration::ZERO,
+                view_alarms_fired: 0,
+                view_adjustment: 0,
+                view_log: Vec::new(),
+                show_summary: false,
+                exit_confirmed: false,
+                started_at: unix_now(),
+                config_dirty: false,
+                last_config_save: Instant::now(),
+                export_status: None,
+                timer_overlay_hovered: false,
+                timer_overlay_size: egui::Vec2::ZERO,
+                timer_drag: None,
+                sounds: SoundChecks::default(),
+                notices,
+                show_settings_transfer: false,
+                show_about: false,
+                show_browser: false,
+                thumbnails: None,
+                browser_scrolled_to: None,
+                show_reset: false,
+                reset_scope: ResetScope::default(),
+                confirm_reset: false,
+                export_folders: true,
+                pending_import: None,
+                transfer_status: None,
+                config_version,
+                unknown_config,
+                config_error,
+                config_error_dismissed: false,
+                applied_theme: None,
+                applied_min_window_size: None,
+                show_settings: false,
+                settings_tab: settings::Tab::General,
+                settings,
             })
         }),
     );
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clock_rolls_minutes_over_into_hours() {
+        assert_eq!(format_clock(0), "00:00");
+        assert_eq!(format_clock(59 * 60 + 59), "59:59");
+        assert_eq!(format_clock(3600), "1:00:00");
+        assert_eq!(format_clock(4500), "1:15:00");
+        assert_eq!(format_clock(10 * 3600 + 5), "10:00:05");
+
+        assert_eq!(format_clock_tenths(Duration::from_millis(9_870)), "00:09.8");
+        assert_eq!(format_clock_tenths(Duration::from_millis(60_050)), "01:00.0");
+        assert_eq!(format_clock_tenths(Duration::from_millis(3_599_999)), "59:59.9");
+    }
+
+    #[test]
+    fn long_gap_between_updates_is_replayed() {
+        // Ten minutes without an update: the target gets focus 200 s in and loses
+        // it again at 500 s.
+        let since = Instant::now();
+        let now = since + Duration::from_secs(600);
+        let focused = |target_active| TrackState { target_active, ..TrackState::default() };
+        let transitions = vec![
+            (since + Duration::from_secs(200), focused(true)),
+            (since + Duration::from_secs(500), focused(false)),
+        ];
+
+        let mut active = false;
+        let mut elapsed = Duration::ZERO;
+        let mut stretches = Vec::new();
+        tracker::replay(since, now, transitions, |delta, ended_ago, state| {
+            if active {
+                elapsed += delta;
+            }
+            stretches.push((delta.as_secs(), ended_ago.as_secs()));
+            if let Some(state) = state {
+                active = state.target_active;
+            }
+        });
+        assert_eq!(elapsed, Duration::from_secs(300));
+        assert_eq!(stretches, [(200, 400), (300, 100), (100, 0)]);
+
+        // The alarm fires once for the whole gap, and a repeating one moves on to
+        // the next deadline still ahead rather than firing for each one missed.
+        let mut alarm = Alarm::new("Pose".to_string(), 240, None);
+        assert!(alarm.due(elapsed));
+        assert!(!alarm.due(elapsed));
+        let mut repeating = Alarm { repeating: true, ..Alarm::new("Lap".to_string(), 60, None) };
+        assert!(repeating.due(elapsed));
+        assert!(!repeating.due(elapsed));
+        assert_eq!(repeating.duration(), Duration::from_secs(360));
+    }
+}
@@ -0,0 +1,137 @@
+//! Lines dragged across the image to check proportions and angles. The first
+//! line is the unit, such as a head, and every later one is labelled with how
+//! many of those it is long and its slant from horizontal. The ends are kept in
+//! image coordinates, so the lines stay on the same features through zooming,
+//! panning and turning the image.
+
+use eframe::egui::{self, vec2, Align2, Color32, FontId, Painter, Pos2, Rect, Stroke, Vec2};
+
+use crate::{i18n::tr, zoom::View};
+
+/// Drags shorter than this on screen are taken for clicks.
+const MIN_DRAG: f32 = 4.0;
+const UNIT_COLOR: Color32 = Color32::from_rgb(255, 200, 60);
+const LINE_COLOR: Color32 = Color32::from_rgb(90, 200, 255);
+
+#[derive(Default)]
+pub struct Measure {
+    pub active: bool,
+    /// Keeps the lines when moving on to another image.
+    pub pinned: bool,
+    /// From end to end in texture coordinates, 0..1; the first is the unit.
+    lines: Vec<[Pos2; 2]>,
+    /// Where the line being dragged out started.
+    start: Option<Pos2>,
+}
+
+impl Measure {
+    pub fn has_lines(&self) -> bool {
+        !self.lines.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.lines.clear();
+        self.start = None;
+    }
+
+    /// Drags out a new line over the image drawn through `view` in `rect`.
+    pub fn update(&mut self, response: &egui::Response, view: &View) {
+        let rect = response.rect;
+        if response.drag_started_by(egui::PointerButton::Primary) {
+            self.start = response.interact_pointer_pos().map(|p| view.texture_at(rect, p));
+        }
+        if response.drag_released() {
+            if let (Some(start), Some(end)) = (self.start.take(), response.interact_pointer_pos()) {
+                if (view.screen_point(rect, start) - end).length() >= MIN_DRAG {
+                    self.lines.push([start, view.texture_at(rect, end)]);
+                }
+            }
+        }
+    }
+
+    /// Draws the lines and their labels over an image `image_size` pixels large,
+    /// shown through `view` in `rect`, along with any line being dragged out to
+    /// `pointer`.
+    pub fn paint(&self, painter: &Painter, rect: Rect, view: &View, image_size: Vec2, pointer: Option<Pos2>) {
+        let painter = painter.with_clip_rect(rect.intersect(painter.clip_rect()));
+        let unit = self.lines.first().map(|&[a, b]| length(a, b, image_size));
+        let mut lines = self.lines.clone();
+        if let (Some(start), Some(pointer)) = (self.start, pointer) {
+            lines.push([start, view.texture_at(rect, pointer)]);
+        }
+
+        for (index, [start, end]) in lines.into_iter().enumerate() {
+            let (a, b) = (view.screen_point(rect, start), view.screen_point(rect, end));
+            let color = if index == 0 { UNIT_COLOR } else { LINE_COLOR };
+            painter.line_segment([a, b], Stroke::new(3.0, Color32::from_black_alpha(160)));
+            painter.line_segment([a, b], Stroke::new(1.5, color));
+            for point in [a, b] {
+                painter.circle_filled(point, 3.0, color);
+            }
+            let size = if index == 0 {
+                "1 unit".to_string()
+            } else {
+                unit.filter(|&unit| unit > 0.0).map_or_else(String::new, |unit| format!("{:.2} units", length(start, end, image_size) / unit))
+            };
+            // Adding zero turns -0 into 0.
+            let label = format!("{}  {}°", size, angle(a, b).round() + 0.0);
+            let galley = painter.layout_no_wrap(label, FontId::proportional(13.0), Color32::WHITE);
+            let at = a.lerp(b, 0.5) + vec2(8.0, -8.0);
+            let text_rect = Align2::LEFT_BOTTOM.anchor_rect(Rect::from_min_size(at, galley.size()));
+            painter.rect_filled(text_rect.expand(3.0), 3.0, Color32::from_black_alpha(170));
+            painter.galley(text_rect.min, galley, Color32::WHITE);
+        }
+    }
+
+    /// The tool's buttons, in the top-middle of the window while it's on.
+    pub fn toolbar(&mut self, ctx: &egui::Context) {
+        egui::Area::new("measure_toolbar").anchor(Align2::CENTER_TOP, [0.0, 10.0]).show(ctx, |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    let hint = if self.has_lines() { tr("Drag to compare with the first line.") } else { tr("Drag across a length to use as the unit.") };
+                    ui.label(hint);
+                    if ui.add_enabled(self.has_lines(), egui::Button::new(tr("Clear"))).clicked() {
+                        self.clear();
+                    }
+                    ui.checkbox(&mut self.pinned, tr("Keep for Next Images"));
+                });
+            });
+        });
+    }
+}
+
+/// How long the line from `a` to `b`, in texture coordinates, is in image pixels.
+fn length(a: Pos2, b: Pos2, image_size: Vec2) -> f32 {
+    ((b - a) * image_size).length()
+}
+
+/// The slant of the line from `a` to `b` on screen from horizontal, in degrees:
+/// positive rising to the right, negative falling, so either end can be first.
+fn angle(a: Pos2, b: Pos2) -> f32 {
+    let (from, to) = if a.x <= b.x { (a, b) } else { (b, a) };
+    let degrees = (from.y - to.y).atan2(to.x - from.x).to_degrees();
+    // Straight up is 90° whichever way it was dragged.
+    if degrees == -90.0 {
+        90.0
+    } else {
+        degrees
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use eframe::egui::pos2;
+
+    #[test]
+    fn lengths_and_angles() {
+        let size = vec2(400.0, 200.0);
+        assert_eq!(length(pos2(0.0, 0.0), pos2(0.5, 0.0), size), 200.0);
+        assert_eq!(length(pos2(0.0, 0.0), pos2(0.0, 1.0), size), 200.0);
+
+        assert_eq!(angle(pos2(0.0, 10.0), pos2(10.0, 0.0)).round(), 45.0);
+        assert_eq!(angle(pos2(10.0, 0.0), pos2(0.0, 10.0)).round(), 45.0);
+        assert_eq!(angle(pos2(0.0, 0.0), pos2(10.0, 10.0)).round(), -45.0);
+        assert_eq!(angle(pos2(5.0, 0.0), pos2(5.0, 10.0)), 90.0);
+    }
+}
@@ -0,0 +1,127 @@
+//! Short messages stacked in the corner of the window that go away by themselves,
+//! for telling the user something without stopping them. Unlike `toast`, which
+//! goes through the system, these stay inside the viewer.
+
+use eframe::egui;
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+/// At most this many show at once; older ones make way for newer.
+const MAX_SHOWN: usize = 4;
+/// How long a notice takes to fade out at the end.
+const FADE: Duration = Duration::from_millis(500);
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Level {
+    Info,
+    Warning,
+    Error,
+}
+
+impl Level {
+    fn icon(self) -> &'static str {
+        match self {
+            Self::Info => "ℹ",
+            Self::Warning => "⚠",
+            Self::Error => "⛔",
+        }
+    }
+
+    fn color(self, visuals: &egui::Visuals) -> Option<egui::Color32> {
+        match self {
+            Self::Info => None,
+            Self::Warning => Some(visuals.warn_fg_color),
+            Self::Error => Some(visuals.error_fg_color),
+        }
+    }
+}
+
+struct Notice {
+    level: Level,
+    text: String,
+    /// How much longer it shows, which doesn't run down while hovered.
+    left: Duration,
+    /// How many times it came up in a row, shown rather than stacking copies.
+    repeats: usize,
+}
+
+#[derive(Default)]
+pub struct Notices {
+    queue: VecDeque<Notice>,
+    last_shown: Option<Instant>,
+    /// Whether the pointer was over them last frame, in which case no time passed
+    /// for them since.
+    hovered: bool,
+}
+
+impl Notices {
+    /// Adds a notice that shows for `how_long`. The same message again restarts
+    /// the one already showing instead of adding another.
+    pub fn push(&mut self, level: Level, text: String, how_long: Duration) {
+        if let Some(notice) = self.queue.iter_mut().find(|n| n.level == level && n.text == text) {
+            notice.left = how_long;
+            notice.repeats += 1;
+            return;
+        }
+        self.queue.push_back(Notice {
+            level,
+            text,
+            left: how_long,
+            repeats: 1,
+        });
+        while self.queue.len() > MAX_SHOWN {
+            self.queue.pop_front();
+        }
+    }
+
+    /// Draws the notices in the bottom-left corner, newest at the bottom, and
+    /// runs down their time unless the pointer is over them.
+    pub fn show(&mut self, ctx: &egui::Context) {
+        if self.queue.is_empty() {
+            self.last_shown = None;
+            return;
+        }
+        let now = Instant::now();
+        let passed = self.last_shown.filter(|_| !self.hovered).map_or(Duration::ZERO, |at| now - at);
+        self.last_shown = Some(now);
+
+        let area = egui::Area::new("notice").anchor(egui::Align2::LEFT_BOTTOM, [10.0, -10.0]).show(ctx, |ui| {
+            for notice in &self.queue {
+                let opacity = (notice.left.as_secs_f32() / FADE.as_secs_f32()).min(1.0);
+                let visuals = ui.visuals();
+                let text_color = visuals.text_color().gamma_multiply(opacity);
+                let color = notice.level.color(visuals).map_or(text_color, |c| c.gamma_multiply(opacity));
+                let mut frame = egui::Frame::popup(ui.style()).multiply_with_opacity(opacity);
+                if notice.level != Level::Info {
+                    frame.stroke.color = color;
+                }
+                frame.show(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.colored_label(color, notice.level.icon());
+                        ui.colored_label(text_color, &notice.text);
+                        if notice.repeats > 1 {
+                            ui.colored_label(color, format!("×{}", notice.repeats));
+                        }
+                    });
+                });
+            }
+        });
+        self.hovered = ctx.input(|i| i.pointer.hover_pos()).is_some_and(|pos| area.response.rect.contains(pos));
+
+        if !self.hovered {
+            for notice in &mut self.queue {
+                notice.left = notice.left.saturating_sub(passed);
+            }
+        }
+        self.queue.retain(|n| !n.left.is_zero());
+        match self.queue.iter().map(|n| n.left).min() {
+            Some(left) if !self.hovered => match left.checked_sub(FADE) {
+                Some(until_fade) => ctx.request_repaint_after(until_fade),
+                None => ctx.request_repaint(),
+            },
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,88 @@
+use eframe::egui;
+
+#[cfg(windows)]
+use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
+#[cfg(windows)]
+use windows::{
+    Win32::Foundation::{COLORREF, HWND},
+    Win32::System::Com::{CoCreateInstance, CLSCTX_INPROC_SERVER},
+    Win32::UI::Shell::{ITaskbarList, TaskbarList},
+    Win32::UI::WindowsAndMessaging::{
+        GetWindowLongPtrW, SetLayeredWindowAttributes, SetWindowLongPtrW, GWL_EXSTYLE, LWA_ALPHA, WS_EX_LAYERED,
+        WS_EX_TRANSPARENT,
+    },
+};
+
+/// Sets the viewer window's opacity (0 to 1). While `click_through` is set the
+/// window lets the mouse through, so the app underneath keeps getting hovered and
+/// clicked. Returns false where window opacity isn't supported.
+#[cfg(windows)]
+pub fn set_alpha(frame: &eframe::Frame, opacity: f32, click_through: bool) -> bool {
+    let RawWindowHandle::Win32(handle) = frame.raw_window_handle() else {
+        return false;
+    };
+    let hwnd = HWND(handle.hwnd as isize);
+
+    unsafe {
+        let style = GetWindowLongPtrW(hwnd, GWL_EXSTYLE);
+        let style = if click_through {
+            style | (WS_EX_LAYERED.0 | WS_EX_TRANSPARENT.0) as isize
+        } else {
+            (style | WS_EX_LAYERED.0 as isize) & !(WS_EX_TRANSPARENT.0 as isize)
+        };
+        SetWindowLongPtrW(hwnd, GWL_EXSTYLE, style);
+
+        let alpha = (opacity.clamp(0.0, 1.0) * 255.0) as u8;
+        SetLayeredWindowAttributes(hwnd, COLORREF(0), alpha, LWA_ALPHA).is_ok()
+    }
+}
+
+#[cfg(not(windows))]
+pub fn set_alpha(_frame: &eframe::Frame, _opacity: f32, _click_through: bool) -> bool {
+    false
+}
+
+/// Fades the viewer window to `opacity` (0 to 1), or restores it with `None`. While
+/// faded, or whenever `click_through` is set, the window lets the mouse through.
+/// Returns false where window opacity isn't supported.
+pub fn set_faded(frame: &eframe::Frame, opacity: Option<f32>, click_through: bool) -> bool {
+    set_alpha(frame, opacity.unwrap_or(1.0), opacity.is_some() || click_through)
+}
+
+/// Adds or removes the viewer's taskbar button without hiding the window itself.
+/// Returns false where that isn't supported.
+#[cfg(windows)]
+pub fn set_taskbar_button(frame: &eframe::Frame, shown: bool) -> bool {
+    let RawWindowHandle::Win32(handle) = frame.raw_window_handle() else {
+        return false;
+    };
+    let hwnd = HWND(handle.hwnd as isize);
+
+    // COM is already initialized on the UI thread by winit.
+    unsafe {
+        let Ok(taskbar) = CoCreateInstance::<_, ITaskbarList>(&TaskbarList, None, CLSCTX_INPROC_SERVER) else {
+            return false;
+        };
+        if taskbar.HrInit().is_err() {
+            return false;
+        }
+        if shown {
+            taskbar.AddTab(hwnd).is_ok()
+        } else {
+            taskbar.DeleteTab(hwnd).is_ok()
+        }
+    }
+}
+
+#[cfg(not(windows))]
+pub fn set_taskbar_button(_frame: &eframe::Frame, _shown: bool) -> bool {
+    false
+}
+
+/// Hides or shows the viewer. Window opacity is used where available, since a
+/// hidden window may stop receiving repaints and never get the chance to come back.
+pub fn set_hidden(ctx: &egui::Context, frame: &eframe::Frame, hidden: bool, click_through: bool) {
+    if !set_faded(frame, hidden.then_some(0.0), click_through) {
+        ctx.send_viewport_cmd(egui::ViewportCommand::Visible(!hidden));
+    }
+}
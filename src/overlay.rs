@@ -0,0 +1,235 @@
+//! Where the timer sits over the image and how it's drawn. It keeps its distance
+//! from the window corner it was dropped nearest to, so it stays with that corner
+//! as the window is resized.
+
+use eframe::egui::{pos2, vec2, Color32, FontId, Pos2, Rect, Response, Sense, Ui, Vec2};
+use serde::{Deserialize, Serialize};
+
+use crate::{i18n::tr, theme};
+
+/// How far a timer snapped into a corner is from the window's edges.
+pub const MARGIN: f32 = 10.0;
+
+#[derive(Serialize, Deserialize, Default, Clone, Copy, PartialEq)]
+pub enum Corner {
+    #[default]
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+impl Corner {
+    pub const ALL: [Corner; 4] = [Self::TopLeft, Self::TopRight, Self::BottomLeft, Self::BottomRight];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::TopLeft => tr("Top Left"),
+            Self::TopRight => tr("Top Right"),
+            Self::BottomLeft => tr("Bottom Left"),
+            Self::BottomRight => tr("Bottom Right"),
+        }
+    }
+
+    fn is_left(self) -> bool {
+        matches!(self, Self::TopLeft | Self::BottomLeft)
+    }
+
+    fn is_top(self) -> bool {
+        matches!(self, Self::TopLeft | Self::TopRight)
+    }
+
+    /// Where something `size` large goes in this corner of `screen`, the usual
+    /// distance from its edges.
+    pub fn place(self, size: Vec2, screen: Rect) -> Rect {
+        let left = if self.is_left() { screen.left() + MARGIN } else { screen.right() - MARGIN - size.x };
+        let top = if self.is_top() { screen.top() + MARGIN } else { screen.bottom() - MARGIN - size.y };
+        Rect::from_min_size(pos2(left, top), size)
+    }
+
+    /// The corner of `screen` closest to `point`.
+    fn nearest(screen: Rect, point: Pos2) -> Self {
+        match (point.x < screen.center().x, point.y < screen.center().y) {
+            (true, true) => Self::TopLeft,
+            (false, true) => Self::TopRight,
+            (true, false) => Self::BottomLeft,
+            (false, false) => Self::BottomRight,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct OverlaySettings {
+    pub corner: Corner,
+    /// From the window's corner to the same corner of the timer, in points.
+    pub offset: [f32; 2],
+    /// Puts the timer right into the nearest corner when it's dropped.
+    pub snap: bool,
+    pub font_size: f32,
+}
+
+impl Default for OverlaySettings {
+    fn default() -> Self {
+        Self {
+            corner: Corner::TopLeft,
+            offset: [MARGIN, MARGIN],
+            snap: false,
+            font_size: 28.0,
+        }
+    }
+}
+
+impl OverlaySettings {
+    /// Where the top left of a timer `size` large goes in `screen`, kept on
+    /// screen if the window has shrunk since it was placed.
+    pub fn top_left(&self, size: Vec2, screen: Rect) -> Pos2 {
+        let [x, y] = self.offset;
+        let left = if self.corner.is_left() { screen.left() + x } else { screen.right() - x - size.x };
+        let top = if self.corner.is_top() { screen.top() + y } else { screen.bottom() - y - size.y };
+        clamp_into(pos2(left, top), size, screen)
+    }
+
+    /// Takes on where the timer was dropped, at `rect`, measuring from the
+    /// nearest corner from now on.
+    pub fn dropped(&mut self, rect: Rect, screen: Rect) {
+        self.corner = Corner::nearest(screen, rect.center());
+        self.offset = if self.snap {
+            [MARGIN, MARGIN]
+        } else {
+            let x = if self.corner.is_left() { rect.left() - screen.left() } else { screen.right() - rect.right() };
+            let y = if self.corner.is_top() { rect.top() - screen.top() } else { screen.bottom() - rect.bottom() };
+            [x.max(0.0), y.max(0.0)]
+        };
+    }
+
+    /// Puts the timer into `corner`, the usual distance from the edges.
+    pub fn snap_to(&mut self, corner: Corner) {
+        self.corner = corner;
+        self.offset = [MARGIN, MARGIN];
+    }
+}
+
+/// `top_left` moved so something `size` large from there fits in `screen`, or
+/// at least its top left does.
+pub fn clamp_into(top_left: Pos2, size: Vec2, screen: Rect) -> Pos2 {
+    pos2(
+        top_left.x.clamp(screen.left(), (screen.right() - size.x).max(screen.left())),
+        top_left.y.clamp(screen.top(), (screen.bottom() - size.y).max(screen.top())),
+    )
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Preset {
+    Classic,
+    HighContrast,
+    Subtle,
+}
+
+impl Preset {
+    pub const ALL: [Preset; 3] = [Self::Classic, Self::HighContrast, Self::Subtle];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Classic => tr("Classic Red"),
+            Self::HighContrast => tr("High Contrast"),
+            Self::Subtle => tr("Subtle"),
+        }
+    }
+
+    pub fn style(self) -> TimerStyle {
+        match self {
+            Self::Classic => TimerStyle::default(),
+            Self::HighContrast => TimerStyle {
+                text_color: [255, 255, 255],
+                detail_color: [255, 255, 255],
+                background_color: [0, 0, 0],
+                background_opacity: 1.0,
+                outline: false,
+            },
+            Self::Subtle => TimerStyle {
+                text_color: [235, 235, 235],
+                detail_color: [200, 200, 200],
+                background_color: [0, 0, 0],
+                background_opacity: 0.6,
+                outline: true,
+            },
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct TimerStyle {
+    pub text_color: [u8; 3],
+    /// For the lines under the timer, such as "paused".
+    pub detail_color: [u8; 3],
+    pub background_color: [u8; 3],
+    pub background_opacity: f32,
+    /// Text with a contrasting edge and no box behind it. The box still shows
+    /// for warnings and overtime.
+    pub outline: bool,
+}
+
+impl Default for TimerStyle {
+    fn default() -> Self {
+        Self {
+            text_color: [255, 0, 0],
+            detail_color: [160, 160, 160],
+            background_color: [30, 0, 0],
+            background_opacity: 1.0,
+            outline: false,
+        }
+    }
+}
+
+impl TimerStyle {
+    /// Text in the `accent` color on a deep shade of it.
+    pub fn accented(accent: [u8; 3]) -> Self {
+        Self {
+            text_color: accent,
+            detail_color: [160, 160, 160],
+            background_color: accent.map(|c| c / 8),
+            background_opacity: 1.0,
+            outline: false,
+        }
+    }
+
+    pub fn text(&self) -> Color32 {
+        let [r, g, b] = self.text_color;
+        Color32::from_rgb(r, g, b)
+    }
+
+    pub fn detail(&self) -> Color32 {
+        let [r, g, b] = self.detail_color;
+        Color32::from_rgb(r, g, b)
+    }
+
+    /// The box behind the text. `emphasis`, from 0 to 1, shades it toward the
+    /// text color for the warning pulse and overtime.
+    pub fn background(&self, emphasis: f32) -> Color32 {
+        let shade = |i: usize| {
+            let (from, to) = (self.background_color[i] as f32, self.text_color[i] as f32);
+            (from + (to - from) * 0.25 * emphasis) as u8
+        };
+        let opacity = if self.outline { self.background_opacity * emphasis } else { self.background_opacity };
+        Color32::from_rgba_unmultiplied(shade(0), shade(1), shade(2), (255.0 * opacity.clamp(0.0, 1.0)) as u8)
+    }
+
+    /// Adds `text` in `font` and `color`, on its box or outlined.
+    pub fn label(&self, ui: &mut Ui, text: String, font: FontId, color: Color32, emphasis: f32, sense: Sense) -> Response {
+        let galley = ui.painter().layout_no_wrap(text, font, color);
+        let (rect, response) = ui.allocate_exact_size(galley.size(), sense);
+        let painter = ui.painter();
+        painter.rect_filled(rect, 0.0, self.background(emphasis));
+        if self.outline {
+            // Black or white, whichever stands out more from the text.
+            let edge = if theme::is_light(color) { Color32::from_black_alpha(220) } else { Color32::from_white_alpha(220) };
+            for (x, y) in [(-1.0, 0.0), (1.0, 0.0), (0.0, -1.0), (0.0, 1.0), (-1.0, -1.0), (1.0, -1.0), (-1.0, 1.0), (1.0, 1.0)] {
+                painter.galley_with_override_text_color(rect.min + vec2(x, y), galley.clone(), edge);
+            }
+        }
+        painter.galley(rect.min, galley, color);
+        response
+    }
+}
@@ -0,0 +1,213 @@
+//! Where the settings file lives.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+const FILE_NAME: &str = "config.toml";
+/// What the file is called when it was written before TOML was the default.
+const JSON_FILE_NAME: &str = "config.json";
+/// What the file was called when it was kept in the working directory.
+const LEGACY_FILE_NAME: &str = "viewer_config.json";
+/// Tracked time per day, kept in the working directory by older versions.
+const STATS_FILE_NAME: &str = "stats.json";
+/// Placed next to the exe to keep the settings there instead, e.g. on a USB stick.
+const PORTABLE_FLAG: &str = "portable.flag";
+const APP_DIR: &str = "timerdrawing";
+
+/// Why the settings file is where it is.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Origin {
+    /// Given with `--config`.
+    CommandLine,
+    /// Next to the exe, from `--portable` or a `portable.flag`.
+    Portable,
+    Default,
+}
+
+static CONFIG: OnceLock<(PathBuf, Origin)> = OnceLock::new();
+
+/// Uses `path` as the settings file. Only takes effect before the first `config_path`.
+pub fn set_config_path(path: PathBuf) {
+    let _ = CONFIG.set((path, Origin::CommandLine));
+}
+
+/// Keeps the settings next to the exe as if `portable.flag` were there. Only takes
+/// effect before the first `config_path`.
+pub fn set_portable() {
+    if let Some(dir) = exe_dir() {
+        let _ = CONFIG.set((keep_json(dir.join(FILE_NAME)), Origin::Portable));
+    }
+}
+
+/// The settings file, resolved on first use. An existing `viewer_config.json` in
+/// the working directory or next to the exe is copied there the first time.
+pub fn config_path() -> &'static Path {
+    &config().0
+}
+
+pub fn config_origin() -> Origin {
+    config().1
+}
+
+fn config() -> &'static (PathBuf, Origin) {
+    CONFIG.get_or_init(|| {
+        let exe_dir = exe_dir();
+        let path = choose(exe_dir.as_deref(), dirs::config_dir());
+        let json = path.with_file_name(JSON_FILE_NAME);
+        if !path.exists() && legacy_paths(LEGACY_FILE_NAME).any(|legacy| migrate(&legacy, &json)) {
+            log::info!("Copied {} to {}", LEGACY_FILE_NAME, json.display());
+        }
+        let path = keep_json(path);
+        let origin = match exe_dir {
+            Some(dir) if dir.join(PORTABLE_FLAG).exists() => Origin::Portable,
+            _ => Origin::Default,
+        };
+        (path, origin)
+    })
+}
+
+/// New settings are written as TOML, but an existing `config.json` stays in use.
+fn keep_json(path: PathBuf) -> PathBuf {
+    let json = path.with_file_name(JSON_FILE_NAME);
+    if !path.exists() && json.exists() {
+        json
+    } else {
+        path
+    }
+}
+
+/// Where older versions kept `file_name`: the working directory, which login
+/// launches used to move next to the exe, or next to the exe itself.
+fn legacy_paths(file_name: &'static str) -> impl Iterator<Item = PathBuf> {
+    std::env::current_dir().ok().into_iter().chain(exe_dir()).map(move |dir| dir.join(file_name))
+}
+
+fn exe_dir() -> Option<PathBuf> {
+    std::env::current_exe().ok().and_then(|exe| exe.parent().map(Path::to_path_buf))
+}
+
+/// The statistics file, beside the settings file. One an older version left in
+/// the working directory or next to the exe is copied there the first time.
+pub fn stats_path() -> PathBuf {
+    let path = config_path().with_file_name(STATS_FILE_NAME);
+    if let Some(legacy) = legacy_paths(STATS_FILE_NAME).find(|legacy| migrate(legacy, &path)) {
+        log::info!("Copied {} to {}", legacy.display(), path.display());
+    }
+    path
+}
+
+/// The statistics database, beside the settings file.
+pub fn stats_database_path() -> PathBuf {
+    config_path().with_file_name("stats.sqlite")
+}
+
+/// Where thumbnails for the browser are cached: the system cache folder, or
+/// beside the settings when those were put somewhere on purpose.
+pub fn thumbnail_dir() -> PathBuf {
+    match (config_origin(), dirs::cache_dir()) {
+        (Origin::Default, Some(cache)) => cache.join(APP_DIR).join("thumbnails"),
+        _ => config_path().with_file_name("thumbnails"),
+    }
+}
+
+/// Opens `dir` in the system file manager.
+pub fn reveal(dir: &Path) -> std::io::Result<()> {
+    #[cfg(windows)]
+    let program = "explorer";
+    #[cfg(target_os = "macos")]
+    let program = "open";
+    #[cfg(not(any(windows, target_os = "macos")))]
+    let program = "xdg-open";
+    std::process::Command::new(program).arg(dir).spawn().map(|_| ())
+}
+
+/// Next to the exe in portable mode, otherwise in the platform config directory.
+/// Without either, the working directory is used as before.
+fn choose(exe_dir: Option<&Path>, config_dir: Option<PathBuf>) -> PathBuf {
+    match (exe_dir, config_dir) {
+        (Some(dir), _) if dir.join(PORTABLE_FLAG).exists() => dir.join(FILE_NAME),
+        (_, Some(dir)) => dir.join(APP_DIR).join(FILE_NAME),
+        (Some(dir), None) => dir.join(FILE_NAME),
+        (None, None) => PathBuf::from(LEGACY_FILE_NAME),
+    }
+}
+
+/// Copies the old config to `path` unless there is already a file there. The old
+/// one is left in place, so an older build run from the same folder still finds it.
+fn migrate(legacy: &Path, path: &Path) -> bool {
+    if legacy == path || path.exists() || !legacy.is_file() {
+        return false;
+    }
+    if let Some(parent) = path.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+    fs::copy(legacy, path).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("timerdrawing-paths-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn migrate_copies_legacy_config_into_new_dir() {
+        let dir = scratch("copy");
+        let legacy = dir.join(LEGACY_FILE_NAME);
+        fs::write(&legacy, "{\"current_index\": 3}").unwrap();
+        let path = dir.join("config").join(APP_DIR).join(JSON_FILE_NAME);
+
+        assert!(migrate(&legacy, &path));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "{\"current_index\": 3}");
+        assert!(legacy.exists());
+    }
+
+    #[test]
+    fn migrate_keeps_existing_config() {
+        let dir = scratch("existing");
+        let legacy = dir.join(LEGACY_FILE_NAME);
+        let path = dir.join(FILE_NAME);
+        fs::write(&legacy, "old").unwrap();
+        fs::write(&path, "new").unwrap();
+
+        assert!(!migrate(&legacy, &path));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "new");
+    }
+
+    #[test]
+    fn migrate_without_legacy_config_does_nothing() {
+        let dir = scratch("missing");
+        let path = dir.join(APP_DIR).join(FILE_NAME);
+
+        assert!(!migrate(&dir.join(LEGACY_FILE_NAME), &path));
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn existing_json_config_stays_in_use() {
+        let dir = scratch("json");
+        assert_eq!(keep_json(dir.join(FILE_NAME)), dir.join(FILE_NAME));
+        fs::write(dir.join(JSON_FILE_NAME), "{}").unwrap();
+        assert_eq!(keep_json(dir.join(FILE_NAME)), dir.join(JSON_FILE_NAME));
+        fs::write(dir.join(FILE_NAME), "").unwrap();
+        assert_eq!(keep_json(dir.join(FILE_NAME)), dir.join(FILE_NAME));
+    }
+
+    #[test]
+    fn portable_flag_keeps_config_next_to_exe() {
+        let exe_dir = scratch("portable");
+        let config_dir = exe_dir.join("appdata");
+
+        assert_eq!(choose(Some(&exe_dir), Some(config_dir.clone())), config_dir.join(APP_DIR).join(FILE_NAME));
+        fs::write(exe_dir.join(PORTABLE_FLAG), "").unwrap();
+        assert_eq!(choose(Some(&exe_dir), Some(config_dir)), exe_dir.join(FILE_NAME));
+    }
+}
@@ -0,0 +1,178 @@
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+
+#[cfg(windows)]
+use windows::Win32::{
+    Foundation::{BOOL, LPARAM, POINT, RECT},
+    Graphics::Gdi::{
+        EnumDisplayMonitors, GetMonitorInfoW, MonitorFromPoint, MonitorFromRect, HDC, HMONITOR, MONITORINFO,
+        MONITOR_DEFAULTTONEAREST, MONITOR_DEFAULTTONULL, MONITOR_DEFAULTTOPRIMARY,
+    },
+    UI::HiDpi::{
+        GetDpiForMonitor, SetProcessDpiAwarenessContext, DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2, MDT_EFFECTIVE_DPI,
+    },
+};
+
+/// Where the viewer was on screen, in physical pixels so it means the same thing
+/// whichever monitor's scaling applies when it's restored.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct WindowPlacement {
+    /// Top-left corner of the outer frame.
+    pub x: i32,
+    pub y: i32,
+    /// Size of the content area, which is what the viewport builder takes.
+    pub width: u32,
+    pub height: u32,
+    /// The scaling the window had when this was taken.
+    pub pixels_per_point: f32,
+}
+
+impl WindowPlacement {
+    /// The current placement, or `None` while minimized, maximized or fullscreen,
+    /// since those shouldn't replace the remembered normal placement.
+    pub fn capture(ctx: &egui::Context) -> Option<Self> {
+        let pixels_per_point = ctx.pixels_per_point();
+        ctx.input(|i| {
+            let viewport = i.viewport();
+            if viewport.minimized == Some(true) || viewport.maximized == Some(true) || viewport.fullscreen == Some(true)
+            {
+                return None;
+            }
+            let outer = viewport.outer_rect?;
+            let inner = viewport.inner_rect?;
+            Some(Self {
+                x: (outer.min.x * pixels_per_point).round() as i32,
+                y: (outer.min.y * pixels_per_point).round() as i32,
+                width: (inner.width() * pixels_per_point).round() as u32,
+                height: (inner.height() * pixels_per_point).round() as u32,
+                pixels_per_point,
+            })
+        })
+    }
+
+    /// Applies the placement to the builder for the initial window, moved onto a
+    /// visible monitor first in case the one it was on is gone.
+    pub fn apply(self, builder: egui::ViewportBuilder) -> egui::ViewportBuilder {
+        let placement = self.clamped_to_screen();
+        // The builder takes points, which egui converts back using the primary
+        // monitor's scaling.
+        let scale = primary_scale().unwrap_or(self.pixels_per_point).max(0.1);
+        builder
+            .with_position(egui::pos2(placement.x as f32 / scale, placement.y as f32 / scale))
+            .with_inner_size(egui::vec2(placement.width as f32 / scale, placement.height as f32 / scale))
+    }
+
+    /// Keeps the placement if the top strip of the window, where it can be dragged,
+    /// is on some monitor. Otherwise moves it inside the nearest monitor's work area,
+    /// shrinking it if it doesn't fit.
+    #[cfg(windows)]
+    fn clamped_to_screen(self) -> Self {
+        // winit does this too once the event loop starts; doing it first makes the
+        // monitor rectangles below come back in real pixels.
+        let _ = unsafe { SetProcessDpiAwarenessContext(DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2) };
+
+        let title_strip = RECT {
+            left: self.x,
+            top: self.y,
+            right: self.x + (self.width as i32).min(200),
+            bottom: self.y + 40,
+        };
+        if !unsafe { MonitorFromRect(&title_strip, MONITOR_DEFAULTTONULL) }.is_invalid() {
+            return self;
+        }
+
+        let monitor = unsafe { MonitorFromRect(&title_strip, MONITOR_DEFAULTTONEAREST) };
+        let mut info = MONITORINFO {
+            cbSize: std::mem::size_of::<MONITORINFO>() as u32,
+            ..Default::default()
+        };
+        if !unsafe { GetMonitorInfoW(monitor, &mut info) }.as_bool() {
+            return self;
+        }
+        let work = info.rcWork;
+        let width = self.width.min((work.right - work.left).max(1) as u32);
+        let height = self.height.min((work.bottom - work.top).max(1) as u32);
+        log::info!("Saved window position is off-screen, moving it onto the nearest monitor");
+        Self {
+            x: self.x.clamp(work.left, work.right - width as i32),
+            y: self.y.clamp(work.top, work.bottom - height as i32),
+            width,
+            height,
+            ..self
+        }
+    }
+
+    /// Elsewhere the window manager keeps windows on screen by itself.
+    #[cfg(not(windows))]
+    fn clamped_to_screen(self) -> Self {
+        self
+    }
+}
+
+/// The size of the window's monitor less the taskbar, in points.
+pub fn work_area(ctx: &egui::Context) -> Option<egui::Vec2> {
+    work_rect(ctx).map(|rect| rect.size())
+}
+
+/// Where the window's monitor is less the taskbar, in points.
+#[cfg(windows)]
+pub fn work_rect(ctx: &egui::Context) -> Option<egui::Rect> {
+    let pixels_per_point = ctx.pixels_per_point();
+    let center = ctx.input(|i| i.viewport().outer_rect)?.center();
+    let point = POINT {
+        x: (center.x * pixels_per_point).round() as i32,
+        y: (center.y * pixels_per_point).round() as i32,
+    };
+    let monitor = unsafe { MonitorFromPoint(point, MONITOR_DEFAULTTONEAREST) };
+    let mut info = MONITORINFO {
+        cbSize: std::mem::size_of::<MONITORINFO>() as u32,
+        ..Default::default()
+    };
+    if !unsafe { GetMonitorInfoW(monitor, &mut info) }.as_bool() {
+        return None;
+    }
+    let work = info.rcWork;
+    let min = egui::pos2(work.left as f32, work.top as f32) / pixels_per_point;
+    let max = egui::pos2(work.right as f32, work.bottom as f32) / pixels_per_point;
+    Some(egui::Rect::from_min_max(min, max))
+}
+
+/// Elsewhere the whole monitor has to do, taken to be the one at the origin.
+#[cfg(not(windows))]
+pub fn work_rect(ctx: &egui::Context) -> Option<egui::Rect> {
+    ctx.input(|i| i.viewport().monitor_size).map(|size| egui::Rect::from_min_size(egui::Pos2::ZERO, size))
+}
+
+#[cfg(windows)]
+fn primary_scale() -> Option<f32> {
+    let primary = unsafe { MonitorFromPoint(POINT::default(), MONITOR_DEFAULTTOPRIMARY) };
+    let (mut dpi_x, mut dpi_y) = (0, 0);
+    unsafe { GetDpiForMonitor(primary, MDT_EFFECTIVE_DPI, &mut dpi_x, &mut dpi_y) }.ok()?;
+    Some(dpi_x as f32 / 96.0)
+}
+
+#[cfg(not(windows))]
+fn primary_scale() -> Option<f32> {
+    None
+}
+
+/// The middle of each monitor in physical pixels, for moving the window onto one.
+#[cfg(windows)]
+pub fn monitor_centers() -> Vec<(i32, i32)> {
+    unsafe extern "system" fn collect(_monitor: HMONITOR, _dc: HDC, rect: *mut RECT, data: LPARAM) -> BOOL {
+        let centers = &mut *(data.0 as *mut Vec<(i32, i32)>);
+        let rect = *rect;
+        centers.push(((rect.left + rect.right) / 2, (rect.top + rect.bottom) / 2));
+        true.into()
+    }
+
+    let mut centers: Vec<(i32, i32)> = Vec::new();
+    let _ = unsafe { EnumDisplayMonitors(HDC::default(), None, Some(collect), LPARAM(&mut centers as *mut _ as isize)) };
+    centers
+}
+
+/// Elsewhere the window goes fullscreen on the monitor it's on.
+#[cfg(not(windows))]
+pub fn monitor_centers() -> Vec<(i32, i32)> {
+    Vec::new()
+}
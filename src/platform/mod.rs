@@ -0,0 +1,4 @@
+//! Thin wrappers over OS features that only some platforms have.
+
+pub mod power;
+pub mod taskbar;
@@ -0,0 +1,51 @@
+//! Keeping the machine from going to sleep during a session.
+
+#[cfg(windows)]
+use windows::Win32::System::Power::{
+    SetThreadExecutionState, ES_CONTINUOUS, ES_DISPLAY_REQUIRED, ES_SYSTEM_REQUIRED, EXECUTION_STATE,
+};
+
+/// How much has to stay awake.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Wake {
+    Off,
+    System,
+    /// The screen as well, so it doesn't blank either.
+    SystemAndDisplay,
+}
+
+/// Holds the execution state request for the UI thread. The request belongs to
+/// the thread that made it, so this has to be driven from the same thread each time.
+pub struct KeepAwake {
+    current: Wake,
+}
+
+impl KeepAwake {
+    pub fn new() -> Self {
+        Self { current: Wake::Off }
+    }
+
+    /// Whether the machine is currently being kept awake.
+    pub fn is_active(&self) -> bool {
+        self.current != Wake::Off
+    }
+
+    #[cfg(windows)]
+    pub fn set(&mut self, wake: Wake) {
+        if wake == self.current {
+            return;
+        }
+        let flags = match wake {
+            Wake::Off => ES_CONTINUOUS,
+            Wake::System => ES_CONTINUOUS | ES_SYSTEM_REQUIRED,
+            Wake::SystemAndDisplay => ES_CONTINUOUS | ES_SYSTEM_REQUIRED | ES_DISPLAY_REQUIRED,
+        };
+        // Returns the previous state, or zero if the call failed.
+        if unsafe { SetThreadExecutionState(flags) } != EXECUTION_STATE(0) {
+            self.current = wake;
+        }
+    }
+
+    #[cfg(not(windows))]
+    pub fn set(&mut self, _wake: Wake) {}
+}
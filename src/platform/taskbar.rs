@@ -0,0 +1,89 @@
+//! The progress fill on the viewer's taskbar button.
+
+#[cfg(windows)]
+use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
+#[cfg(windows)]
+use windows::{
+    Win32::Foundation::HWND,
+    Win32::System::Com::{CoCreateInstance, CLSCTX_INPROC_SERVER},
+    Win32::UI::Shell::{
+        ITaskbarList3, TaskbarList, TBPF_ERROR, TBPF_NOPROGRESS, TBPF_NORMAL, TBPF_PAUSED, TBPFLAG,
+    },
+};
+
+/// What the taskbar button shows. Fractions run from 0 to 1.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Progress {
+    None,
+    Normal(f32),
+    /// Drawn yellow.
+    Paused(f32),
+    /// Drawn red.
+    Error(f32),
+}
+
+/// Keeps the taskbar progress in sync, only calling into the shell when it
+/// changes. If the taskbar interface isn't available, this does nothing.
+pub struct TaskbarProgress {
+    #[cfg(windows)]
+    taskbar: Option<Option<ITaskbarList3>>,
+    /// What was last sent, in thousandths, so tiny changes don't cause calls.
+    #[cfg(windows)]
+    last: Option<(u8, u16)>,
+}
+
+impl TaskbarProgress {
+    pub fn new() -> Self {
+        Self {
+            #[cfg(windows)]
+            taskbar: None,
+            #[cfg(windows)]
+            last: None,
+        }
+    }
+
+    #[cfg(windows)]
+    pub fn set(&mut self, frame: &eframe::Frame, progress: Progress) {
+        let (kind, fraction) = match progress {
+            Progress::None => (0, 0.0),
+            Progress::Normal(f) => (1, f),
+            Progress::Paused(f) => (2, f),
+            Progress::Error(f) => (3, f),
+        };
+        let key = (kind, (fraction.clamp(0.0, 1.0) * 1000.0) as u16);
+        if self.last == Some(key) {
+            return;
+        }
+
+        let RawWindowHandle::Win32(handle) = frame.raw_window_handle() else {
+            return;
+        };
+        let hwnd = HWND(handle.hwnd as isize);
+
+        // Created on first use; a failure is remembered so it isn't retried every frame.
+        let taskbar = self.taskbar.get_or_insert_with(|| unsafe {
+            let taskbar = CoCreateInstance::<_, ITaskbarList3>(&TaskbarList, None, CLSCTX_INPROC_SERVER).ok()?;
+            taskbar.HrInit().ok()?;
+            Some(taskbar)
+        });
+        let Some(taskbar) = taskbar else { return };
+
+        let state: TBPFLAG = match progress {
+            Progress::None => TBPF_NOPROGRESS,
+            Progress::Normal(_) => TBPF_NORMAL,
+            Progress::Paused(_) => TBPF_PAUSED,
+            Progress::Error(_) => TBPF_ERROR,
+        };
+        let ok = unsafe {
+            // Setting a value switches the state to normal, so the state goes last.
+            (progress == Progress::None || taskbar.SetProgressValue(hwnd, key.1 as u64, 1000).is_ok())
+                && taskbar.SetProgressState(hwnd, state).is_ok()
+        };
+        if ok {
+            self.last = Some(key);
+        }
+    }
+
+    #[cfg(not(windows))]
+    pub fn set(&mut self, _frame: &eframe::Frame, _progress: Progress) {}
+}
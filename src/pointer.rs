@@ -0,0 +1,47 @@
+//! When the pointer was last over the window and last used, for hiding the title
+//! bar once it has gone and the cursor once it has been left still.
+
+use eframe::egui::{InputState, Vec2};
+use std::time::{Duration, Instant};
+
+pub struct PointerActivity {
+    last_inside: Instant,
+    last_active: Instant,
+    inside: bool,
+}
+
+impl PointerActivity {
+    pub fn new() -> Self {
+        Self {
+            last_inside: Instant::now(),
+            last_active: Instant::now(),
+            inside: false,
+        }
+    }
+
+    /// Takes in this frame's input. Moving, clicking, scrolling or coming into
+    /// the window all count as activity.
+    pub fn update(&mut self, input: &InputState) {
+        let now = Instant::now();
+        let inside = input.pointer.hover_pos().is_some();
+        if inside {
+            self.last_inside = now;
+        }
+        let pointer = &input.pointer;
+        let used = pointer.delta() != Vec2::ZERO || pointer.any_down() || pointer.any_released() || input.scroll_delta != Vec2::ZERO;
+        if used || inside != self.inside {
+            self.last_active = now;
+        }
+        self.inside = inside;
+    }
+
+    /// How long since the pointer was last over the window.
+    pub fn away_for(&self) -> Duration {
+        self.last_inside.elapsed()
+    }
+
+    /// How long the pointer has been left where it is, over the window.
+    pub fn still_for(&self) -> Option<Duration> {
+        self.inside.then(|| self.last_active.elapsed())
+    }
+}
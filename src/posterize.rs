@@ -0,0 +1,202 @@
+//! Simplifying the image to a few values, the way a notan or value sketch does,
+//! to see the big shapes of light and dark. Each pixel's luminance is put into
+//! one of two, three or five bands of flat gray. Where the bands split is found
+//! from the image's own tones by Otsu's method, or set by hand.
+
+use eframe::egui;
+use image::DynamicImage;
+use serde::{Deserialize, Serialize};
+
+use crate::i18n::tr;
+
+/// The image in each value filter but `Off`.
+pub type Filtered = Vec<(ValueFilter, egui::ColorImage)>;
+
+#[derive(Serialize, Deserialize, Default, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum ValueFilter {
+    #[default]
+    Off,
+    /// Black and white.
+    Notan,
+    ThreeValues,
+    FiveValues,
+}
+
+impl ValueFilter {
+    pub const ALL: [ValueFilter; 4] = [Self::Off, Self::Notan, Self::ThreeValues, Self::FiveValues];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Off => tr("Full Tones"),
+            Self::Notan => tr("Notan (2 Values)"),
+            Self::ThreeValues => tr("3 Values"),
+            Self::FiveValues => tr("5 Values"),
+        }
+    }
+
+    /// The one after this, back to `Off` after the last.
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|&f| f == self).unwrap_or_default();
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    fn levels(self) -> usize {
+        match self {
+            Self::Off => 256,
+            Self::Notan => 2,
+            Self::ThreeValues => 3,
+            Self::FiveValues => 5,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct PosterizeSettings {
+    /// Splits the values where each image's own tones divide best, rather than
+    /// at the thresholds below.
+    pub automatic: bool,
+    /// Lightness, 0 to 255, at which each band starts, after the darkest.
+    pub notan: [u8; 1],
+    pub three_values: [u8; 2],
+    pub five_values: [u8; 4],
+}
+
+impl Default for PosterizeSettings {
+    fn default() -> Self {
+        Self {
+            automatic: true,
+            notan: [128],
+            three_values: [85, 170],
+            five_values: [51, 102, 153, 204],
+        }
+    }
+}
+
+impl PosterizeSettings {
+    /// The thresholds set by hand for `filter`, lowest first.
+    fn thresholds(&self, filter: ValueFilter) -> Vec<u8> {
+        let mut thresholds = self.clone().thresholds_mut(filter).to_vec();
+        thresholds.sort_unstable();
+        thresholds
+    }
+
+    pub fn thresholds_mut(&mut self, filter: ValueFilter) -> &mut [u8] {
+        match filter {
+            ValueFilter::Off => &mut [],
+            ValueFilter::Notan => &mut self.notan,
+            ValueFilter::ThreeValues => &mut self.three_values,
+            ValueFilter::FiveValues => &mut self.five_values,
+        }
+    }
+
+    /// `img` in every filter but `Off`, made together so the luminance is only
+    /// worked out once and switching between them afterwards is instant.
+    pub fn apply_all(&self, img: &DynamicImage) -> Filtered {
+        let gray = img.to_luma_alpha8();
+        let mut histogram = [0u32; 256];
+        for pixel in gray.pixels().filter(|p| p[1] > 0) {
+            histogram[pixel[0] as usize] += 1;
+        }
+        let size = [img.width() as usize, img.height() as usize];
+        ValueFilter::ALL[1..]
+            .iter()
+            .map(|&filter| {
+                let thresholds = if self.automatic { otsu(&histogram, filter.levels()) } else { self.thresholds(filter) };
+                let shades = shades(&thresholds);
+                let rgba: Vec<u8> = gray
+                    .pixels()
+                    .flat_map(|p| {
+                        let band = thresholds.iter().filter(|&&t| p[0] >= t).count();
+                        let shade = shades[band];
+                        [shade, shade, shade, p[1]]
+                    })
+                    .collect();
+                (filter, egui::ColorImage::from_rgba_unmultiplied(size, &rgba))
+            })
+            .collect()
+    }
+}
+
+/// The gray each band between `thresholds` is drawn in, spread evenly from
+/// black to white.
+fn shades(thresholds: &[u8]) -> Vec<u8> {
+    let bands = thresholds.len() + 1;
+    (0..bands).map(|band| (band * 255 / (bands - 1).max(1)) as u8).collect()
+}
+
+/// Where to split the tones counted in `histogram` into `levels` bands so they
+/// differ from each other as much as possible (Otsu's method), as the lightness
+/// each band after the darkest starts at.
+fn otsu(histogram: &[u32; 256], levels: usize) -> Vec<u8> {
+    // Running totals, so any band's pixel count and summed tone are a subtraction.
+    let mut count = [0f64; 257];
+    let mut sum = [0f64; 257];
+    for (tone, &n) in histogram.iter().enumerate() {
+        count[tone + 1] = count[tone] + n as f64;
+        sum[tone + 1] = sum[tone] + n as f64 * tone as f64;
+    }
+    // With the overall mean fixed, the variance between bands grows with this.
+    let score = |from: usize, to: usize| {
+        let n = count[to] - count[from];
+        if n > 0.0 {
+            (sum[to] - sum[from]).powi(2) / n
+        } else {
+            0.0
+        }
+    };
+
+    // best[band][end] is the best score for tones below `end` split into
+    // `band + 1` bands, and start[band][end] where the last of them starts.
+    let mut best = vec![[f64::MIN; 257]; levels];
+    let mut start = vec![[0usize; 257]; levels];
+    for (end, best) in best[0].iter_mut().enumerate().skip(1) {
+        *best = score(0, end);
+    }
+    for band in 1..levels {
+        for end in band + 1..=256 {
+            for from in band..end {
+                let total = best[band - 1][from] + score(from, end);
+                if total > best[band][end] {
+                    best[band][end] = total;
+                    start[band][end] = from;
+                }
+            }
+        }
+    }
+
+    let mut thresholds = Vec::with_capacity(levels - 1);
+    let mut end = 256;
+    for band in (1..levels).rev() {
+        end = start[band][end];
+        thresholds.push(end as u8);
+    }
+    thresholds.reverse();
+    thresholds
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn otsu_splits_between_clusters() {
+        let mut histogram = [0u32; 256];
+        for (tone, n) in [(20, 100), (30, 80), (120, 90), (130, 60), (220, 70), (235, 50)] {
+            histogram[tone] = n;
+        }
+        let [split] = otsu(&histogram, 2)[..] else { panic!() };
+        assert!((31..=120).contains(&split) || (131..=220).contains(&split));
+        let thresholds = otsu(&histogram, 3);
+        assert!((31..=120).contains(&thresholds[0]), "{:?}", thresholds);
+        assert!((131..=220).contains(&thresholds[1]), "{:?}", thresholds);
+        assert_eq!(otsu(&histogram, 5).len(), 4);
+    }
+
+    #[test]
+    fn cycles_and_shades() {
+        assert!(ValueFilter::FiveValues.next() == ValueFilter::Off);
+        assert_eq!(shades(&[128]), [0, 255]);
+        assert_eq!(shades(&[10, 20, 30, 40]), [0, 63, 127, 191, 255]);
+    }
+}
@@ -0,0 +1,107 @@
+use std::{ffi::c_void, time::Duration};
+
+use core_foundation::{
+    base::{CFType, TCFType},
+    dictionary::{CFDictionary, CFDictionaryRef},
+    number::{CFNumber, CFNumberRef},
+    string::{CFString, CFStringRef},
+};
+use core_graphics::{
+    event::CGEvent,
+    event_source::{CGEventSource, CGEventSourceStateID},
+    geometry::{CGPoint, CGRect},
+    window::{
+        copy_window_info, kCGNullWindowID, kCGWindowBounds, kCGWindowLayer, kCGWindowListExcludeDesktopElements,
+        kCGWindowListOptionOnScreenOnly, kCGWindowName, kCGWindowOwnerName,
+    },
+};
+
+use super::{Window, WindowProbe};
+
+#[link(name = "CoreGraphics", kind = "framework")]
+extern "C" {
+    fn CGEventSourceSecondsSinceLastEventType(state: CGEventSourceStateID, event_type: u32) -> f64;
+}
+
+const ANY_INPUT_EVENT: u32 = !0;
+
+/// Reads the on-screen window list, which comes ordered front to back. Window
+/// titles are only filled in once the app has been granted screen recording access.
+pub struct MacProbe;
+
+struct WindowInfo {
+    owner: Option<String>,
+    title: Option<String>,
+}
+
+impl MacProbe {
+    fn window(&self, window: Window) -> Option<WindowInfo> {
+        let cursor = match window {
+            Window::Foreground => None,
+            Window::UnderCursor => Some(cursor_location()?),
+        };
+
+        let list = copy_window_info(
+            kCGWindowListOptionOnScreenOnly | kCGWindowListExcludeDesktopElements,
+            kCGNullWindowID,
+        )?;
+        list.iter().find_map(|item| {
+            let dict: CFDictionary<*const c_void, *const c_void> =
+                unsafe { CFDictionary::wrap_under_get_rule(*item as CFDictionaryRef) };
+
+            // Layer 0 holds normal app windows; menus, the dock and overlays sit above it.
+            if number(&dict, unsafe { kCGWindowLayer })? != 0 {
+                return None;
+            }
+            if let Some(point) = cursor {
+                let bounds = dict.find(unsafe { kCGWindowBounds } as *const c_void)?;
+                let bounds: CFDictionary = unsafe { CFDictionary::wrap_under_get_rule(*bounds as CFDictionaryRef) };
+                if !CGRect::from_dict_representation(&bounds)?.contains(&point) {
+                    return None;
+                }
+            }
+
+            Some(WindowInfo {
+                owner: string(&dict, unsafe { kCGWindowOwnerName }),
+                title: string(&dict, unsafe { kCGWindowName }).filter(|t| !t.is_empty()),
+            })
+        })
+    }
+}
+
+impl WindowProbe for MacProbe {
+    fn foreground_exe(&mut self) -> Option<String> {
+        self.window(Window::Foreground)?.owner.map(|name| name.to_lowercase())
+    }
+
+    fn exe_under_cursor(&mut self) -> Option<String> {
+        self.window(Window::UnderCursor)?.owner.map(|name| name.to_lowercase())
+    }
+
+    fn window_title(&mut self, window: Window) -> Option<String> {
+        self.window(window)?.title
+    }
+
+    fn idle_time(&mut self) -> Duration {
+        let secs = unsafe {
+            CGEventSourceSecondsSinceLastEventType(CGEventSourceStateID::CombinedSessionState, ANY_INPUT_EVENT)
+        };
+        Duration::try_from_secs_f64(secs).unwrap_or_default()
+    }
+}
+
+fn cursor_location() -> Option<CGPoint> {
+    let source = CGEventSource::new(CGEventSourceStateID::CombinedSessionState).ok()?;
+    Some(CGEvent::new(source).ok()?.location())
+}
+
+fn number(dict: &CFDictionary<*const c_void, *const c_void>, key: CFStringRef) -> Option<i64> {
+    let value = dict.find(key as *const c_void)?;
+    unsafe { CFNumber::wrap_under_get_rule(*value as CFNumberRef) }.to_i64()
+}
+
+fn string(dict: &CFDictionary<*const c_void, *const c_void>, key: CFStringRef) -> Option<String> {
+    let value = dict.find(key as *const c_void)?;
+    let value = unsafe { CFType::wrap_under_get_rule(*value) };
+    value.downcast::<CFString>().map(|s| s.to_string())
+}
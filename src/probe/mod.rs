@@ -0,0 +1,83 @@
+//! Platform backends for finding out which app the user is working in.
+
+use std::time::Duration;
+
+#[cfg(target_os = "macos")]
+mod macos;
+#[cfg(windows)]
+mod windows;
+#[cfg(target_os = "linux")]
+mod x11;
+
+/// Which window a title is wanted for.
+#[derive(Clone, Copy)]
+pub enum Window {
+    Foreground,
+    UnderCursor,
+}
+
+/// Answers the questions the tracker asks about the desktop. Exe names are
+/// lowercased so they compare equal to the configured target.
+pub trait WindowProbe {
+    fn foreground_exe(&mut self) -> Option<String>;
+    fn exe_under_cursor(&mut self) -> Option<String>;
+    /// The window's caption, or `None` if it has none or won't give it out.
+    fn window_title(&mut self, window: Window) -> Option<String>;
+
+    /// Exe names of the window's parent process, its parent and so on, nearest first.
+    fn ancestor_exes(&mut self, _window: Window) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Time since the last keyboard or mouse input anywhere on the system.
+    fn idle_time(&mut self) -> Duration {
+        Duration::ZERO
+    }
+
+    /// Whether the foreground window, not counting the viewer itself or the desktop,
+    /// covers a whole monitor, as games and fullscreen video do.
+    fn foreground_is_fullscreen(&mut self) -> bool {
+        false
+    }
+}
+
+/// A probe that never sees anything, for platforms without a backend. The app
+/// still runs, it just never counts the target as active.
+#[cfg(not(any(windows, target_os = "macos")))]
+pub struct NoopProbe;
+
+#[cfg(not(any(windows, target_os = "macos")))]
+impl WindowProbe for NoopProbe {
+    fn foreground_exe(&mut self) -> Option<String> {
+        None
+    }
+
+    fn exe_under_cursor(&mut self) -> Option<String> {
+        None
+    }
+
+    fn window_title(&mut self, _window: Window) -> Option<String> {
+        None
+    }
+}
+
+/// The backend for the platform the app was built for.
+pub fn new() -> Box<dyn WindowProbe> {
+    #[cfg(windows)]
+    return Box::new(windows::WindowsProbe::new());
+
+    #[cfg(target_os = "linux")]
+    return match x11::X11Probe::connect() {
+        Some(probe) => Box::new(probe),
+        None => {
+            log::warn!("No X11 display, app tracking is disabled");
+            Box::new(NoopProbe)
+        }
+    };
+
+    #[cfg(target_os = "macos")]
+    return Box::new(macos::MacProbe);
+
+    #[cfg(not(any(windows, target_os = "linux", target_os = "macos")))]
+    return Box::new(NoopProbe);
+}
@@ -0,0 +1,323 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use windows::{
+    Win32::Foundation::{BOOL, CloseHandle, FALSE, FILETIME, HANDLE, HWND, LPARAM, POINT, RECT, TRUE, WAIT_OBJECT_0},
+    Win32::Graphics::Gdi::{GetMonitorInfoW, MonitorFromWindow, MONITORINFO, MONITOR_DEFAULTTONULL},
+    Win32::System::Diagnostics::ToolHelp::{
+        CreateToolhelp32Snapshot, Process32FirstW, Process32NextW, PROCESSENTRY32W, TH32CS_SNAPPROCESS,
+    },
+    Win32::System::ProcessStatus::K32GetModuleBaseNameW,
+    Win32::System::SystemInformation::GetTickCount,
+    Win32::System::Threading::{
+        GetProcessTimes, OpenProcess, WaitForSingleObject, PROCESS_QUERY_INFORMATION,
+        PROCESS_QUERY_LIMITED_INFORMATION, PROCESS_SYNCHRONIZE, PROCESS_VM_READ,
+    },
+    Win32::UI::Input::KeyboardAndMouse::{GetLastInputInfo, LASTINPUTINFO},
+    Win32::UI::WindowsAndMessaging::{
+        EnumChildWindows, GetAncestor, GetClassNameW, GetCursorPos, GetForegroundWindow, GetWindowRect, GetWindowTextW,
+        GetWindowThreadProcessId, WindowFromPoint, GA_ROOT,
+    },
+};
+
+use super::{Window, WindowProbe};
+
+/// Store apps are shown inside a frame window that belongs to this exe, whatever
+/// the app itself is called.
+const FRAME_HOST_EXE: &str = "applicationframehost.exe";
+
+pub struct WindowsProbe {
+    names: ProcessNameCache,
+}
+
+impl WindowsProbe {
+    pub fn new() -> Self {
+        Self {
+            names: ProcessNameCache::new(),
+        }
+    }
+
+    fn window(&self, window: Window) -> HWND {
+        match window {
+            Window::Foreground => unsafe { GetForegroundWindow() },
+            Window::UnderCursor => {
+                let mut pt = POINT::default();
+                let _ = unsafe { GetCursorPos(&mut pt) };
+                // The point usually lands on a child control; the title lives on the top-level window.
+                unsafe { GetAncestor(WindowFromPoint(pt), GA_ROOT) }
+            }
+        }
+    }
+
+    /// The window whose process the app really runs in. For Store apps that's the
+    /// hosted child window rather than the frame, so names and ancestry come out
+    /// right; titles are still read from `window`, as the frame carries the caption.
+    fn process_window(&mut self, window: Window) -> HWND {
+        let hwnd = self.window(window);
+        if self.names.exe_name(hwnd).as_deref() == Some(FRAME_HOST_EXE) {
+            return hosted_window(hwnd).unwrap_or(hwnd);
+        }
+        hwnd
+    }
+}
+
+impl WindowProbe for WindowsProbe {
+    fn foreground_exe(&mut self) -> Option<String> {
+        let hwnd = self.process_window(Window::Foreground);
+        self.names.exe_name(hwnd)
+    }
+
+    fn exe_under_cursor(&mut self) -> Option<String> {
+        let hwnd = self.process_window(Window::UnderCursor);
+        self.names.exe_name(hwnd)
+    }
+
+    fn window_title(&mut self, window: Window) -> Option<String> {
+        let mut buffer = [0u16; 512];
+        let len = unsafe { GetWindowTextW(self.window(window), &mut buffer) };
+        if len <= 0 {
+            return None;
+        }
+        Some(String::from_utf16_lossy(&buffer[..len as usize]))
+    }
+
+    fn ancestor_exes(&mut self, window: Window) -> Vec<String> {
+        let hwnd = self.process_window(window);
+        self.names.ancestors(hwnd)
+    }
+
+    fn idle_time(&mut self) -> Duration {
+        let mut info = LASTINPUTINFO {
+            cbSize: std::mem::size_of::<LASTINPUTINFO>() as u32,
+            dwTime: 0,
+        };
+        if !unsafe { GetLastInputInfo(&mut info) }.as_bool() {
+            return Duration::ZERO;
+        }
+        // Both tick counts wrap around together every ~49 days.
+        let idle_ms = unsafe { GetTickCount() }.wrapping_sub(info.dwTime);
+        Duration::from_millis(idle_ms as u64)
+    }
+
+    fn foreground_is_fullscreen(&mut self) -> bool {
+        let hwnd = unsafe { GetForegroundWindow() };
+        if hwnd.0 == 0 || window_pid(hwnd) == std::process::id() {
+            return false;
+        }
+        // The desktop's own windows span the monitor too.
+        let mut class = [0u16; 64];
+        let len = unsafe { GetClassNameW(hwnd, &mut class) };
+        if matches!(String::from_utf16_lossy(&class[..len.max(0) as usize]).as_str(), "Progman" | "WorkerW") {
+            return false;
+        }
+
+        let mut rect = RECT::default();
+        if unsafe { GetWindowRect(hwnd, &mut rect) }.is_err() {
+            return false;
+        }
+        let monitor = unsafe { MonitorFromWindow(hwnd, MONITOR_DEFAULTTONULL) };
+        let mut info = MONITORINFO {
+            cbSize: std::mem::size_of::<MONITORINFO>() as u32,
+            ..Default::default()
+        };
+        if monitor.is_invalid() || !unsafe { GetMonitorInfoW(monitor, &mut info) }.as_bool() {
+            return false;
+        }
+        let screen = info.rcMonitor;
+        rect.left <= screen.left && rect.top <= screen.top && rect.right >= screen.right && rect.bottom >= screen.bottom
+    }
+}
+
+/// Remembers pid -> exe name lookups, since opening the process is the expensive
+/// part. Each entry keeps its process handle open, which stops Windows from handing
+/// the pid to a new process and tells us when the process has exited.
+struct ProcessNameCache {
+    entries: HashMap<u32, CachedProcess>,
+}
+
+impl ProcessNameCache {
+    const MAX_ENTRIES: usize = 64;
+
+    fn new() -> Self {
+        Self {
+            entries: HashMap::new(),
+        }
+    }
+
+    fn exe_name(&mut self, hwnd: HWND) -> Option<String> {
+        self.entry(hwnd)?.name.clone()
+    }
+
+    /// The ancestry is looked up once per process. That's safe to cache because the
+    /// open handle keeps the pid from being reused while the entry exists.
+    fn ancestors(&mut self, hwnd: HWND) -> Vec<String> {
+        let Some(entry) = self.entry(hwnd) else {
+            return Vec::new();
+        };
+        entry.ancestors.get_or_insert_with(|| ancestor_names(entry.pid)).clone()
+    }
+
+    fn entry(&mut self, hwnd: HWND) -> Option<&mut CachedProcess> {
+        let pid = window_pid(hwnd);
+        if pid == 0 {
+            return None;
+        }
+
+        if self.entries.get(&pid).is_some_and(CachedProcess::is_stale) {
+            self.entries.remove(&pid);
+        }
+        if self.entries.len() >= Self::MAX_ENTRIES {
+            self.entries.retain(|_, entry| !entry.is_stale());
+            if self.entries.len() >= Self::MAX_ENTRIES {
+                self.entries.clear();
+            }
+        }
+        Some(self.entries.entry(pid).or_insert_with(|| CachedProcess::open(pid)))
+    }
+}
+
+struct CachedProcess {
+    pid: u32,
+    /// `None` for processes that can't be opened, such as elevated ones.
+    handle: Option<HANDLE>,
+    name: Option<String>,
+    /// Filled in the first time it's asked for.
+    ancestors: Option<Vec<String>>,
+    cached_at: Instant,
+}
+
+impl CachedProcess {
+    /// How long a process that couldn't be opened is remembered before retrying.
+    const RETRY_AFTER: Duration = Duration::from_secs(5);
+
+    fn open(pid: u32) -> Self {
+        let access = PROCESS_QUERY_INFORMATION | PROCESS_VM_READ | PROCESS_SYNCHRONIZE;
+        let handle = unsafe { OpenProcess(access, false, pid) }.ok();
+        let name = handle.and_then(|handle| {
+            let mut buffer = [0u16; 260];
+            let len = unsafe { K32GetModuleBaseNameW(handle, None, &mut buffer) };
+            (len > 0).then(|| String::from_utf16_lossy(&buffer[..len as usize]).to_lowercase())
+        });
+
+        Self {
+            pid,
+            handle,
+            name,
+            ancestors: None,
+            cached_at: Instant::now(),
+        }
+    }
+
+    fn is_stale(&self) -> bool {
+        match self.handle {
+            // The process handle is signaled once the process has exited.
+            Some(handle) => unsafe { WaitForSingleObject(handle, 0) == WAIT_OBJECT_0 },
+            None => self.cached_at.elapsed() > Self::RETRY_AFTER,
+        }
+    }
+}
+
+impl Drop for CachedProcess {
+    fn drop(&mut self) {
+        if let Some(handle) = self.handle {
+            let _ = unsafe { CloseHandle(handle) };
+        }
+    }
+}
+
+/// Walks up the parent pids recorded in a Toolhelp snapshot. Windows doesn't
+/// clear a child's parent pid when the parent exits, so that pid may since have
+/// gone to an unrelated process; the walk stops at any "parent" that started
+/// after its child, or whose start time can't be read.
+fn ancestor_names(pid: u32) -> Vec<String> {
+    const MAX_DEPTH: usize = 8;
+
+    let processes = process_snapshot();
+    let mut names = Vec::new();
+    let mut child = pid;
+    let mut child_started = creation_time(pid);
+
+    while names.len() < MAX_DEPTH {
+        let Some(&(parent, _)) = processes.get(&child) else { break };
+        let Some((_, name)) = processes.get(&parent).filter(|_| parent != 0 && parent != child) else {
+            break;
+        };
+        let parent_started = creation_time(parent);
+        match (parent_started, child_started) {
+            (Some(parent_time), Some(child_time)) if parent_time <= child_time => {}
+            _ => break,
+        }
+        names.push(name.clone());
+        child = parent;
+        child_started = parent_started;
+    }
+    names
+}
+
+/// Every running process as pid -> (parent pid, lowercased exe name).
+fn process_snapshot() -> HashMap<u32, (u32, String)> {
+    let mut processes = HashMap::new();
+    let Ok(snapshot) = (unsafe { CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS, 0) }) else {
+        return processes;
+    };
+
+    let mut entry = PROCESSENTRY32W {
+        dwSize: std::mem::size_of::<PROCESSENTRY32W>() as u32,
+        ..Default::default()
+    };
+    let mut more = unsafe { Process32FirstW(snapshot, &mut entry) }.is_ok();
+    while more {
+        let len = entry.szExeFile.iter().position(|&c| c == 0).unwrap_or(entry.szExeFile.len());
+        let name = String::from_utf16_lossy(&entry.szExeFile[..len]).to_lowercase();
+        processes.insert(entry.th32ProcessID, (entry.th32ParentProcessID, name));
+        more = unsafe { Process32NextW(snapshot, &mut entry) }.is_ok();
+    }
+
+    let _ = unsafe { CloseHandle(snapshot) };
+    processes
+}
+
+/// When the process started, in 100ns ticks since 1601.
+fn creation_time(pid: u32) -> Option<u64> {
+    let handle = unsafe { OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid) }.ok()?;
+    let (mut created, mut exited, mut kernel, mut user) =
+        (FILETIME::default(), FILETIME::default(), FILETIME::default(), FILETIME::default());
+    let result = unsafe { GetProcessTimes(handle, &mut created, &mut exited, &mut kernel, &mut user) };
+    let _ = unsafe { CloseHandle(handle) };
+    result.ok()?;
+    Some((created.dwHighDateTime as u64) << 32 | created.dwLowDateTime as u64)
+}
+
+fn window_pid(hwnd: HWND) -> u32 {
+    let mut pid = 0;
+    unsafe { GetWindowThreadProcessId(hwnd, Some(&mut pid)) };
+    pid
+}
+
+/// The first child of a Store app frame that belongs to another process, which is
+/// the app's own window. A suspended or still-starting app may not have one yet.
+fn hosted_window(frame: HWND) -> Option<HWND> {
+    struct Search {
+        frame_pid: u32,
+        found: Option<HWND>,
+    }
+
+    unsafe extern "system" fn visit(child: HWND, lparam: LPARAM) -> BOOL {
+        let search = &mut *(lparam.0 as *mut Search);
+        let pid = window_pid(child);
+        if pid != 0 && pid != search.frame_pid {
+            search.found = Some(child);
+            return FALSE;
+        }
+        TRUE
+    }
+
+    let mut search = Search {
+        frame_pid: window_pid(frame),
+        found: None,
+    };
+    let _ = unsafe { EnumChildWindows(frame, Some(visit), LPARAM(&mut search as *mut Search as isize)) };
+    search.found
+}
@@ -0,0 +1,139 @@
+use std::time::Duration;
+
+use x11rb::{
+    connection::Connection,
+    protocol::screensaver::ConnectionExt as _,
+    protocol::xproto::{Atom, AtomEnum, ConnectionExt as _, Window as XWindow},
+    rust_connection::RustConnection,
+};
+
+use super::{Window, WindowProbe};
+
+pub struct X11Probe {
+    conn: RustConnection,
+    root: XWindow,
+    net_wm_pid: Atom,
+    net_wm_name: Atom,
+    utf8_string: Atom,
+}
+
+impl X11Probe {
+    /// Connects to `$DISPLAY`, or returns `None` if there isn't an X server.
+    pub fn connect() -> Option<Self> {
+        let (conn, screen) = x11rb::connect(None).ok()?;
+        let root = conn.setup().roots.get(screen)?.root;
+        let atom = |name: &[u8]| Some(conn.intern_atom(false, name).ok()?.reply().ok()?.atom);
+        let net_wm_pid = atom(b"_NET_WM_PID")?;
+        let net_wm_name = atom(b"_NET_WM_NAME")?;
+        let utf8_string = atom(b"UTF8_STRING")?;
+
+        Some(Self {
+            conn,
+            root,
+            net_wm_pid,
+            net_wm_name,
+            utf8_string,
+        })
+    }
+
+    fn window(&self, window: Window) -> Option<XWindow> {
+        let window = match window {
+            Window::Foreground => self.conn.get_input_focus().ok()?.reply().ok()?.focus,
+            Window::UnderCursor => self.conn.query_pointer(self.root).ok()?.reply().ok()?.child,
+        };
+        // 0 is None and 1 is PointerRoot, neither of which is a real window.
+        (window > 1 && window != self.root).then_some(window)
+    }
+
+    fn pid(&self, window: XWindow) -> Option<u32> {
+        let reply = self
+            .conn
+            .get_property(false, window, self.net_wm_pid, AtomEnum::CARDINAL, 0, 1)
+            .ok()?
+            .reply()
+            .ok()?;
+        let pid = reply.value32()?.next();
+        pid
+    }
+
+    /// The client window that owns `window` and its pid. Focus is often on a child of
+    /// the client, and reparenting window managers wrap the client in a frame, so
+    /// both directions are searched.
+    fn client(&self, window: XWindow) -> Option<(XWindow, u32)> {
+        let mut current = window;
+        for _ in 0..8 {
+            if let Some(pid) = self.pid(current) {
+                return Some((current, pid));
+            }
+            let parent = self.conn.query_tree(current).ok()?.reply().ok()?.parent;
+            if parent == 0 || parent == self.root {
+                break;
+            }
+            current = parent;
+        }
+        self.client_below(window, 2)
+    }
+
+    fn client_below(&self, window: XWindow, depth: u32) -> Option<(XWindow, u32)> {
+        let children = self.conn.query_tree(window).ok()?.reply().ok()?.children;
+        children.iter().find_map(|&child| {
+            self.pid(child)
+                .map(|pid| (child, pid))
+                .or_else(|| (depth > 1).then(|| self.client_below(child, depth - 1)).flatten())
+        })
+    }
+
+    fn exe(&self, window: Window) -> Option<String> {
+        let (_, pid) = self.client(self.window(window)?)?;
+        exe_name_for_pid(pid)
+    }
+}
+
+impl WindowProbe for X11Probe {
+    fn foreground_exe(&mut self) -> Option<String> {
+        self.exe(Window::Foreground)
+    }
+
+    fn exe_under_cursor(&mut self) -> Option<String> {
+        self.exe(Window::UnderCursor)
+    }
+
+    fn window_title(&mut self, window: Window) -> Option<String> {
+        let (client, _) = self.client(self.window(window)?)?;
+        let reply = self
+            .conn
+            .get_property(false, client, self.net_wm_name, self.utf8_string, 0, 1024)
+            .ok()?
+            .reply()
+            .ok()?;
+        let reply = if reply.value.is_empty() {
+            // Older clients only set the Latin-1 WM_NAME.
+            self.conn
+                .get_property(false, client, AtomEnum::WM_NAME, AtomEnum::STRING, 0, 1024)
+                .ok()?
+                .reply()
+                .ok()?
+        } else {
+            reply
+        };
+        (!reply.value.is_empty()).then(|| String::from_utf8_lossy(&reply.value).into_owned())
+    }
+
+    fn idle_time(&mut self) -> Duration {
+        self.conn
+            .screensaver_query_info(self.root)
+            .ok()
+            .and_then(|cookie| cookie.reply().ok())
+            .map_or(Duration::ZERO, |info| Duration::from_millis(info.ms_since_user_input as u64))
+    }
+}
+
+/// The file name of the process's executable. `comm` is the fallback for processes
+/// whose `exe` link can't be read, though it is cut off at 15 characters.
+fn exe_name_for_pid(pid: u32) -> Option<String> {
+    let name = std::fs::read_link(format!("/proc/{}/exe", pid))
+        .ok()
+        .and_then(|path| path.file_name().map(|name| name.to_string_lossy().into_owned()))
+        .or_else(|| std::fs::read_to_string(format!("/proc/{}/comm", pid)).ok())?;
+    Some(name.trim().to_lowercase())
+}
@@ -0,0 +1,95 @@
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+use crate::{Alarm, FolderMap};
+
+/// A named set of folders, alarms and target exe, such as one per drawing app.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Profile {
+    pub name: String,
+    pub target_exe_name: Option<String>,
+    pub folder_map: FolderMap,
+    pub alarms: Vec<Alarm>,
+}
+
+/// Picks the profile whose target is in the foreground, once it has stayed there
+/// for a moment, so alt-tabbing past an app doesn't switch to its profile.
+#[derive(Default)]
+pub struct AutoSwitch {
+    /// The profile the foreground app matches and since when.
+    candidate: Option<(usize, Instant)>,
+}
+
+impl AutoSwitch {
+    const HOLD_OFF: Duration = Duration::from_secs(2);
+
+    /// Returns the profile to switch to, if the foreground app has matched one other
+    /// than `active` for long enough.
+    pub fn poll(&mut self, profiles: &[Profile], active: Option<usize>, foreground_exe: Option<&str>) -> Option<usize> {
+        self.poll_at(Instant::now(), profiles, active, foreground_exe)
+    }
+
+    fn poll_at(&mut self, now: Instant, profiles: &[Profile], active: Option<usize>, foreground_exe: Option<&str>) -> Option<usize> {
+        let matching = foreground_exe
+            .and_then(|exe| profiles.iter().position(|p| p.target_exe_name.as_deref() == Some(exe)))
+            .filter(|&i| Some(i) != active);
+        let Some(index) = matching else {
+            self.candidate = None;
+            return None;
+        };
+
+        let since = match self.candidate {
+            Some((candidate, since)) if candidate == index => since,
+            _ => self.candidate.insert((index, now)).1,
+        };
+        if now.saturating_duration_since(since) < Self::HOLD_OFF {
+            return None;
+        }
+        self.candidate = None;
+        Some(index)
+    }
+
+    /// How long until a pending switch is due, so the UI can wake up for it.
+    pub fn wait(&self) -> Option<Duration> {
+        self.candidate.map(|(_, since)| Self::HOLD_OFF.saturating_sub(since.elapsed()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profiles() -> Vec<Profile> {
+        ["krita.exe", "photoshop.exe"]
+            .into_iter()
+            .map(|exe| Profile {
+                name: exe.to_string(),
+                target_exe_name: Some(exe.to_string()),
+                folder_map: FolderMap::new(),
+                alarms: Vec::new(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn switches_only_once_focus_is_held() {
+        let profiles = profiles();
+        let start = Instant::now();
+        let at = |ms| start + Duration::from_millis(ms);
+        let mut auto = AutoSwitch::default();
+
+        // Alt-tabbing past Photoshop and back doesn't switch.
+        assert_eq!(auto.poll_at(at(0), &profiles, Some(0), Some("photoshop.exe")), None);
+        assert_eq!(auto.poll_at(at(500), &profiles, Some(0), Some("krita.exe")), None);
+        assert_eq!(auto.poll_at(at(2500), &profiles, Some(0), Some("photoshop.exe")), None);
+        assert_eq!(auto.poll_at(at(3000), &profiles, Some(0), Some("explorer.exe")), None);
+        assert!(auto.wait().is_none());
+
+        // Staying on it does, once.
+        assert_eq!(auto.poll_at(at(4000), &profiles, Some(0), Some("photoshop.exe")), None);
+        assert_eq!(auto.poll_at(at(5000), &profiles, Some(0), Some("photoshop.exe")), None);
+        assert_eq!(auto.poll_at(at(6000), &profiles, Some(0), Some("photoshop.exe")), Some(1));
+        assert_eq!(auto.poll_at(at(6500), &profiles, Some(1), Some("photoshop.exe")), None);
+        assert!(auto.wait().is_none());
+    }
+}
@@ -0,0 +1,104 @@
+//! A thin frame around the window that runs down as the alarm nears, so the time
+//! can be kept an eye on without reading the timer.
+
+use eframe::egui::{ecolor::Hsva, pos2, Color32, Painter, Pos2, Rect, Shape, Stroke};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct RingSettings {
+    pub enabled: bool,
+    pub thickness: f32,
+    /// While the countdown has just started.
+    pub start_color: [u8; 3],
+    /// As the alarm is reached.
+    pub end_color: [u8; 3],
+}
+
+impl Default for RingSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            thickness: 4.0,
+            start_color: [0, 200, 0],
+            end_color: [255, 0, 0],
+        }
+    }
+}
+
+impl RingSettings {
+    /// The color `progress` of the way from the start color to the end one,
+    /// going round the hue circle the short way, so green to red passes yellow.
+    pub fn color(&self, progress: f32) -> Color32 {
+        let [r, g, b] = self.start_color;
+        let from = Hsva::from(Color32::from_rgb(r, g, b));
+        let [r, g, b] = self.end_color;
+        let to = Hsva::from(Color32::from_rgb(r, g, b));
+        let t = progress.clamp(0.0, 1.0);
+        let mut hue_step = to.h - from.h;
+        if hue_step > 0.5 {
+            hue_step -= 1.0;
+        } else if hue_step < -0.5 {
+            hue_step += 1.0;
+        }
+        Hsva::new(
+            (from.h + hue_step * t).rem_euclid(1.0),
+            from.s + (to.s - from.s) * t,
+            from.v + (to.v - from.v) * t,
+            1.0,
+        )
+        .into()
+    }
+
+    /// Draws the frame just inside `rect`, the part still to go lit and the rest
+    /// faint. What's lit ends at the top middle and shrinks toward it clockwise.
+    /// Once the alarm is reached the whole frame pulses with `pulse`, from 0 to 1.
+    pub fn paint(&self, painter: &Painter, rect: Rect, progress: f32, pulse: f32) {
+        let rect = rect.shrink(self.thickness / 2.0);
+        let color = self.color(progress);
+        let track = Stroke::new(self.thickness, color.gamma_multiply(0.2));
+        painter.rect_stroke(rect, 0.0, track);
+
+        let (from, lit) = if progress >= 1.0 {
+            (0.0, color.gamma_multiply(0.4 + 0.6 * pulse))
+        } else {
+            (progress.max(0.0), color)
+        };
+        let points = outline(rect, from, 1.0);
+        if points.len() > 1 {
+            painter.add(Shape::line(points, Stroke::new(self.thickness, lit)));
+        }
+    }
+}
+
+/// The points along the edge of `rect` between two fractions of the way round,
+/// starting from the top middle and going clockwise.
+fn outline(rect: Rect, from: f32, to: f32) -> Vec<Pos2> {
+    let corners = [
+        pos2(rect.center().x, rect.top()),
+        rect.right_top(),
+        rect.right_bottom(),
+        rect.left_bottom(),
+        rect.left_top(),
+        pos2(rect.center().x, rect.top()),
+    ];
+    let total: f32 = corners.windows(2).map(|pair| pair[0].distance(pair[1])).sum();
+    let (from, to) = (from * total, to * total);
+
+    let mut points = Vec::new();
+    let mut walked = 0.0;
+    for pair in corners.windows(2) {
+        let length = pair[0].distance(pair[1]);
+        let (start, end) = (walked, walked + length);
+        walked = end;
+        if end < from || start > to || length == 0.0 {
+            continue;
+        }
+        let at = |distance: f32| pair[0].lerp(pair[1], (distance - start) / length);
+        if points.is_empty() {
+            points.push(at(from.max(start)));
+        }
+        points.push(at(to.min(end)));
+    }
+    points
+}
@@ -0,0 +1,185 @@
+//! A second window for the reference, for keeping it on another monitor while
+//! the main window stays where it is, such as beside a pen display. It shows the
+//! same image or the next one in the deck, drawn from textures the main window
+//! already has, so nothing is decoded twice.
+
+use eframe::egui::{self, Color32, Rect, TextureId, Vec2, ViewportBuilder, ViewportId, WindowLevel};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+
+use crate::{i18n::tr, placement::WindowPlacement, zoom::View};
+
+#[derive(Serialize, Deserialize, Default, Clone, Copy, PartialEq)]
+pub enum SecondViewMode {
+    #[default]
+    Mirror,
+    NextImage,
+}
+
+impl SecondViewMode {
+    pub const ALL: [SecondViewMode; 2] = [Self::Mirror, Self::NextImage];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Mirror => tr("Same Image"),
+            Self::NextImage => tr("Next Image"),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct SecondViewSettings {
+    pub open: bool,
+    pub mode: SecondViewMode,
+    /// Leaves the title bar off; the window is moved by dragging the image.
+    pub borderless: bool,
+    pub always_on_top: bool,
+    pub opacity_percent: u8,
+    /// Where it was last, kept apart from the main window's placement.
+    pub placement: Option<WindowPlacement>,
+}
+
+impl Default for SecondViewSettings {
+    fn default() -> Self {
+        Self {
+            open: false,
+            mode: SecondViewMode::Mirror,
+            borderless: true,
+            always_on_top: false,
+            opacity_percent: 100,
+            placement: None,
+        }
+    }
+}
+
+/// The image to draw: its texture, its size in pixels and how it's turned and
+/// mirrored.
+#[derive(Clone, Copy)]
+pub struct Picture {
+    pub texture: TextureId,
+    pub size: Vec2,
+    pub view: View,
+}
+
+/// What the main window hands over each frame and what the second window hands
+/// back, since the second one is drawn on its own whenever it needs repainting.
+#[derive(Default)]
+struct Shared {
+    picture: Option<Picture>,
+    opacity: f32,
+    borderless: bool,
+    /// Closed from its own title bar or menu, which leaves the viewer running.
+    closed: bool,
+    placement: Option<WindowPlacement>,
+}
+
+pub struct SecondView {
+    shared: Arc<Mutex<Shared>>,
+    /// The builder it was opened with. Only what changes in it afterwards is
+    /// applied, so keeping it keeps the window from being moved back.
+    opened_with: Option<ViewportBuilder>,
+}
+
+impl SecondView {
+    pub fn new() -> Self {
+        Self {
+            shared: Arc::default(),
+            opened_with: None,
+        }
+    }
+
+    /// Keeps the window open for this frame showing `picture`, opening it where
+    /// it last was if it wasn't open. It closes once this stops being called.
+    pub fn show(&mut self, ctx: &egui::Context, settings: &SecondViewSettings, picture: Option<Picture>) {
+        {
+            let mut shared = self.shared.lock().unwrap();
+            shared.picture = picture;
+            shared.opacity = settings.opacity_percent as f32 / 100.0;
+            shared.borderless = settings.borderless;
+        }
+        let opened_with = self.opened_with.get_or_insert_with(|| {
+            let builder = ViewportBuilder::default()
+                .with_title(tr("GermiBoard - Reference"))
+                .with_inner_size(egui::vec2(600.0, 450.0))
+                .with_transparent(true);
+            match settings.placement {
+                Some(placement) => placement.apply(builder),
+                None => builder,
+            }
+        });
+        let level = if settings.always_on_top { WindowLevel::AlwaysOnTop } else { WindowLevel::Normal };
+        let builder = opened_with.clone().with_decorations(!settings.borderless).with_window_level(level);
+
+        let shared = self.shared.clone();
+        ctx.show_viewport_deferred(ViewportId::from_hash_of("second_view"), builder, move |ctx, class| {
+            draw(ctx, class, &shared);
+        });
+        // Repainted along with the main window, so it follows along.
+        ctx.request_repaint_of(ViewportId::from_hash_of("second_view"));
+    }
+
+    /// Forgets the window once it's no longer shown, so it opens afresh.
+    pub fn hide(&mut self) {
+        self.opened_with = None;
+    }
+
+    /// Whether the window was closed by itself since this was last asked.
+    pub fn take_closed(&self) -> bool {
+        std::mem::take(&mut self.shared.lock().unwrap().closed)
+    }
+
+    /// Where the window is, if it's open and not minimized or maximized.
+    pub fn placement(&self) -> Option<WindowPlacement> {
+        self.shared.lock().unwrap().placement
+    }
+}
+
+fn draw(ctx: &egui::Context, class: egui::ViewportClass, shared: &Mutex<Shared>) {
+    let mut shared = shared.lock().unwrap();
+    if ctx.input(|i| i.viewport().close_requested()) {
+        shared.closed = true;
+    }
+    if class != egui::ViewportClass::Embedded {
+        shared.placement = WindowPlacement::capture(ctx);
+    }
+    let fill = Color32::from_black_alpha((shared.opacity * 255.0) as u8);
+
+    let mut show = |ui: &mut egui::Ui| {
+        let room = ui.available_rect_before_wrap();
+        let response = ui.interact(room, ui.id().with("second_view"), egui::Sense::click_and_drag());
+        if let Some(picture) = shared.picture {
+            let shown = picture.view.shown_size(picture.size.x, picture.size.y);
+            let size = shown * (room.size() / shown).min_elem();
+            let rect = Rect::from_center_size(room.center(), size);
+            picture.view.paint_faded(ui.painter(), rect, picture.texture, shared.opacity);
+        } else {
+            ui.centered_and_justified(|ui| ui.weak(tr("No image")));
+        }
+        if shared.borderless && response.drag_started_by(egui::PointerButton::Primary) {
+            ui.ctx().send_viewport_cmd(egui::ViewportCommand::StartDrag);
+        }
+        response.context_menu(|ui| {
+            if ui.button(tr("Close")).clicked() {
+                shared.closed = true;
+                ui.close_menu();
+            }
+        });
+    };
+
+    match class {
+        // Where the platform can't open another window it shows in the main one.
+        egui::ViewportClass::Embedded => {
+            let mut open = true;
+            egui::Window::new(tr("Reference")).open(&mut open).show(ctx, |ui| show(ui));
+            shared.closed |= !open;
+        }
+        _ => {
+            egui::CentralPanel::default().frame(egui::Frame::none().fill(fill)).show(ctx, |ui| show(ui));
+        }
+    }
+    if shared.closed {
+        // The main window stops showing it once it sees it was closed.
+        ctx.request_repaint_of(ViewportId::ROOT);
+    }
+}
@@ -0,0 +1,98 @@
+use chrono::{Local, TimeZone};
+use serde::Serialize;
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+/// One entry of the per-image view log.
+#[derive(Serialize, Clone)]
+pub struct ImageView {
+    pub path: PathBuf,
+    pub started_at: u64,
+    pub shown_for: Duration,
+    pub tracked: Duration,
+    pub alarms_fired: usize,
+    /// Tracked time after an alarm had already fired.
+    pub overtime: Duration,
+    /// Net seconds added or removed by hand while this image was shown.
+    pub adjusted_seconds: i64,
+}
+
+#[derive(Serialize)]
+struct ExportRow {
+    path: String,
+    started_at: String,
+    shown_seconds: f64,
+    tracked_seconds: f64,
+    alarms_fired: usize,
+    overtime_seconds: f64,
+    adjusted_seconds: i64,
+}
+
+impl From<&ImageView> for ExportRow {
+    fn from(view: &ImageView) -> Self {
+        let started_at = Local
+            .timestamp_opt(view.started_at as i64, 0)
+            .single()
+            .map(|t| t.to_rfc3339())
+            .unwrap_or_default();
+
+        Self {
+            path: view.path.display().to_string(),
+            started_at,
+            shown_seconds: view.shown_for.as_secs_f64(),
+            tracked_seconds: view.tracked.as_secs_f64(),
+            alarms_fired: view.alarms_fired,
+            overtime_seconds: view.overtime.as_secs_f64(),
+            adjusted_seconds: view.adjusted_seconds,
+        }
+    }
+}
+
+/// Writes the log as JSON if `path` ends in `.json`, CSV otherwise.
+pub fn export(views: &[ImageView], path: &Path) -> std::io::Result<()> {
+    let rows: Vec<ExportRow> = views.iter().map(ExportRow::from).collect();
+    let is_json = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case("json"));
+
+    if is_json {
+        let json = serde_json::to_string_pretty(&rows)?;
+        return std::fs::write(path, json);
+    }
+
+    let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+    writeln!(file, "path,started_at,shown_seconds,tracked_seconds,alarms_fired,overtime_seconds,adjusted_seconds")?;
+    for row in &rows {
+        writeln!(
+            file,
+            "{},{},{:.1},{:.1},{},{:.1},{}",
+            csv_field(&row.path),
+            csv_field(&row.started_at),
+            row.shown_seconds,
+            row.tracked_seconds,
+            row.alarms_fired,
+            row.overtime_seconds,
+            row.adjusted_seconds,
+        )?;
+    }
+    file.flush()
+}
+
+/// Writes the log into `dir` under a timestamped file name and returns that path.
+pub fn auto_export(views: &[ImageView], dir: &Path) -> std::io::Result<PathBuf> {
+    let path = dir.join(format!("session-{}.csv", Local::now().format("%Y%m%d-%H%M%S")));
+    export(views, &path)?;
+    Ok(path)
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
@@ -0,0 +1,336 @@
+//! The options of the Settings window, grouped the way its tabs are. They're
+//! saved under `settings` in the config file; older configs kept them at the top
+//! level and are moved over by `Settings::from_flat`.
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::path::PathBuf;
+
+use crate::{
+    backdrop::Backdrop, board::BoardSettings, grid::GridSettings, histogram::HistogramSettings, i18n::{tr, Language}, info_bar::InfoBarSettings, overlay::{OverlaySettings, TimerStyle}, posterize::PosterizeSettings, ring::RingSettings, second_view::SecondViewSettings, snap::SnapSettings, theme::Theme, tracker::DocumentPattern, zoom::FitMode, FullscreenTimer, HoverBehavior, ImageFit,
+    PomodoroSettings, SpeechSettings, TimerDisplayMode, TrackingMode,
+};
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Tab {
+    General,
+    Timer,
+    Tracking,
+    Display,
+    Hotkeys,
+}
+
+impl Tab {
+    pub const ALL: [Tab; 5] = [Self::General, Self::Timer, Self::Tracking, Self::Display, Self::Hotkeys];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::General => tr("General"),
+            Self::Timer => tr("Timer & Alarms"),
+            Self::Tracking => tr("Tracking"),
+            Self::Display => tr("Display"),
+            Self::Hotkeys => tr("Hotkeys"),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct Settings {
+    pub general: General,
+    pub timer: Timer,
+    pub tracking: Tracking,
+    pub display: Display,
+}
+
+impl Settings {
+    /// Takes the settings out of a config from before they were grouped, where
+    /// they sat at the top level among `flat`. Keys used are removed, so only
+    /// what this version doesn't know is left.
+    pub fn from_flat(flat: &mut Map<String, Value>) -> Self {
+        Self {
+            general: take_group(flat),
+            timer: take_group(flat),
+            tracking: take_group(flat),
+            display: take_group(flat),
+        }
+    }
+}
+
+/// Reads one group from the keys in `flat` named like its fields and removes them.
+fn take_group<T: Serialize + DeserializeOwned + Default>(flat: &mut Map<String, Value>) -> T {
+    let group = match serde_json::from_value(Value::Object(flat.clone())) {
+        Ok(group) => group,
+        Err(e) => {
+            log::warn!("Couldn't carry over older settings ({}), using the defaults", e);
+            T::default()
+        }
+    };
+    if let Ok(Value::Object(fields)) = serde_json::to_value(&group) {
+        for key in fields.keys() {
+            flat.remove(key);
+        }
+    }
+    group
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct General {
+    pub language: Language,
+    /// Leaves folders and the current image out of the saved config.
+    pub private_folders: bool,
+    pub close_to_tray: bool,
+    pub start_minimized: bool,
+    pub show_exit_summary: bool,
+    pub auto_export_dir: Option<PathBuf>,
+    pub persist_elapsed: bool,
+    pub replay_missed_alarms: bool,
+    /// Keeps the shuffled order and position in deck_state.json.
+    pub persist_deck: bool,
+    /// Keeps statistics in stats.sqlite, along with sessions and image views.
+    pub stats_database: bool,
+    /// How many days "Clean Up Statistics" keeps.
+    pub stats_retention_days: u32,
+    pub keep_awake: bool,
+    pub keep_display_on: bool,
+    pub auto_switch_profiles: bool,
+}
+
+impl Default for General {
+    fn default() -> Self {
+        Self {
+            language: Language::System,
+            private_folders: false,
+            close_to_tray: false,
+            start_minimized: false,
+            show_exit_summary: true,
+            auto_export_dir: None,
+            persist_elapsed: false,
+            replay_missed_alarms: false,
+            persist_deck: false,
+            stats_database: false,
+            stats_retention_days: 365,
+            keep_awake: false,
+            keep_display_on: false,
+            auto_switch_profiles: false,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct Timer {
+    pub timer_display_mode: TimerDisplayMode,
+    pub countdown_tenths: bool,
+    pub pre_alarm_seconds: Option<u64>,
+    pub pre_alarm_sound_path: Option<PathBuf>,
+    pub pre_alarm_volume: f32,
+    pub alarm_volume: f32,
+    pub speech: SpeechSettings,
+    pub pre_alarm_pulse: bool,
+    pub alarm_toast: bool,
+    pub muted: bool,
+    pub show_progress_bar: bool,
+    /// The frame around the window that runs down toward the alarm.
+    pub ring: RingSettings,
+    /// Where the timer sits and how large it is.
+    pub overlay: OverlaySettings,
+    pub overtime_color: [u8; 3],
+    pub overtime_escalation_seconds: Option<u64>,
+    pub overtime_sound_path: Option<PathBuf>,
+    pub pomodoro: PomodoroSettings,
+}
+
+impl Default for Timer {
+    fn default() -> Self {
+        Self {
+            timer_display_mode: TimerDisplayMode::default(),
+            countdown_tenths: true,
+            pre_alarm_seconds: None,
+            pre_alarm_sound_path: None,
+            pre_alarm_volume: 0.5,
+            alarm_volume: 1.0,
+            speech: SpeechSettings::default(),
+            pre_alarm_pulse: false,
+            alarm_toast: false,
+            muted: false,
+            show_progress_bar: true,
+            ring: RingSettings::default(),
+            overlay: OverlaySettings::default(),
+            overtime_color: [255, 170, 0],
+            overtime_escalation_seconds: None,
+            overtime_sound_path: None,
+            pomodoro: PomodoroSettings::default(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct Tracking {
+    pub target_title_pattern: String,
+    pub target_title_regex: bool,
+    pub include_child_processes: bool,
+    pub tracked_exes: Vec<String>,
+    pub show_focused_exe_counter: bool,
+    pub tracking_mode: TrackingMode,
+    pub idle_pause_enabled: bool,
+    pub idle_threshold_seconds: u64,
+    pub poll_interval_ms: u64,
+    pub hide_over_fullscreen: bool,
+    /// Exes that may go fullscreen without the viewer hiding.
+    pub fullscreen_exclusions: Vec<String>,
+    pub track_documents: bool,
+    pub document_title_pattern: String,
+}
+
+impl Default for Tracking {
+    fn default() -> Self {
+        Self {
+            target_title_pattern: String::new(),
+            target_title_regex: false,
+            include_child_processes: false,
+            tracked_exes: Vec::new(),
+            show_focused_exe_counter: false,
+            tracking_mode: TrackingMode::default(),
+            idle_pause_enabled: false,
+            idle_threshold_seconds: 120,
+            poll_interval_ms: 250,
+            hide_over_fullscreen: false,
+            fullscreen_exclusions: Vec::new(),
+            track_documents: false,
+            document_title_pattern: DocumentPattern::DEFAULT.to_string(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct Display {
+    pub theme: Theme,
+    /// Tints selections and pressed buttons instead of egui's blue.
+    pub accent_color: Option<[u8; 3]>,
+    /// Brings back how each image was turned the next time it's shown.
+    pub remember_rotation: bool,
+    /// Keeps the image mirrored when moving on to the next one.
+    pub sticky_flip: bool,
+    /// Keeps showing images in grayscale when moving on to the next one.
+    pub sticky_grayscale: bool,
+    /// Where the notan and 3 and 5 value filters split the image's tones.
+    pub posterize: PosterizeSettings,
+    /// Keeps the last colors picked with the eyedropper in a strip while picking.
+    pub keep_swatches: bool,
+    pub grid: GridSettings,
+    /// The spread of the image's tones in a corner of it.
+    pub histogram: HistogramSettings,
+    /// Behind transparent images.
+    pub backdrop: Backdrop,
+    pub info_bar: InfoBarSettings,
+    pub timer_style: TimerStyle,
+    /// Hides the timer, file name bar and tags over the image.
+    pub hide_hud: bool,
+    pub reveal_hud_before_alarm: bool,
+    pub hover_behavior: HoverBehavior,
+    pub hover_fade_percent: u8,
+    /// How see-through the whole viewer is when not faded, so strokes underneath show.
+    pub window_opacity_percent: u8,
+    /// How large menus and windows are drawn, on top of the system's DPI scaling.
+    pub ui_scale_percent: u16,
+    pub fullscreen_timer: FullscreenTimer,
+    pub image_fit: ImageFit,
+    /// The smallest the window gets, whether sized to an image or by hand, in points.
+    pub min_window_size: [u32; 2],
+    /// Pulling the window flush with screen edges, and where the throw hotkeys send it.
+    pub snap: SnapSettings,
+    /// How the image is sized within the window.
+    pub fit_mode: FitMode,
+    /// Draws images this many pixels or fewer along their long edge with sharp,
+    /// unsmoothed pixels, taking them for pixel art.
+    pub sharp_pixels_up_to: Option<u32>,
+    /// The reference window for another monitor.
+    pub second_view: SecondViewSettings,
+    /// Two or four images shown at once.
+    pub board: BoardSettings,
+    /// Fills the room the image leaves with a blurred, darkened copy of it.
+    pub blurred_letterbox: bool,
+    /// How long moving on fades from one image into the next; 0 cuts straight to it.
+    pub crossfade_ms: u64,
+    /// Leaves the title bar off; the window is moved by dragging the image instead.
+    pub borderless: bool,
+    /// Hides the title bar while the pointer is away from the window.
+    pub auto_hide_title_bar: bool,
+    /// Hides the cursor after it's been left still over the image this long.
+    pub hide_cursor_after_seconds: Option<u64>,
+}
+
+impl Default for Display {
+    fn default() -> Self {
+        Self {
+            theme: Theme::default(),
+            accent_color: None,
+            remember_rotation: false,
+            sticky_flip: false,
+            sticky_grayscale: false,
+            posterize: PosterizeSettings::default(),
+            keep_swatches: true,
+            grid: GridSettings::default(),
+            histogram: HistogramSettings::default(),
+            backdrop: Backdrop::default(),
+            info_bar: InfoBarSettings::default(),
+            timer_style: TimerStyle::default(),
+            hide_hud: false,
+            reveal_hud_before_alarm: true,
+            hover_behavior: HoverBehavior::default(),
+            hover_fade_percent: 30,
+            window_opacity_percent: 100,
+            ui_scale_percent: 100,
+            fullscreen_timer: FullscreenTimer::default(),
+            image_fit: ImageFit::default(),
+            min_window_size: [300, 200],
+            snap: SnapSettings::default(),
+            fit_mode: FitMode::default(),
+            sharp_pixels_up_to: Some(256),
+            second_view: SecondViewSettings::default(),
+            board: BoardSettings::default(),
+            blurred_letterbox: false,
+            crossfade_ms: 0,
+            borderless: false,
+            auto_hide_title_bar: true,
+            hide_cursor_after_seconds: Some(3),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn flat_settings_are_grouped() {
+        let mut flat = json!({
+            "close_to_tray": true,
+            "alarm_volume": 0.25,
+            "poll_interval_ms": 500,
+            "borderless": true,
+            "current_index": 3,
+            "from_a_newer_version": "kept",
+        })
+        .as_object()
+        .cloned()
+        .unwrap();
+
+        let settings = Settings::from_flat(&mut flat);
+        assert!(settings.general.close_to_tray);
+        assert_eq!(settings.timer.alarm_volume, 0.25);
+        assert_eq!(settings.tracking.poll_interval_ms, 500);
+        assert!(settings.display.borderless);
+        // Whatever wasn't set keeps its default.
+        assert_eq!(settings.general.stats_retention_days, 365);
+        assert!(settings.display.auto_hide_title_bar);
+
+        let left: Vec<&str> = flat.keys().map(String::as_str).collect();
+        assert_eq!(left, ["current_index", "from_a_newer_version"]);
+    }
+}
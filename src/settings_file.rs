@@ -0,0 +1,322 @@
+//! Reading and writing the settings file so that a crash or power loss mid-write
+//! can't leave it empty.
+
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+use toml_edit::{DocumentMut, Item, Table};
+
+/// Tables whose keys are in an order of the user's choosing, which a save follows
+/// instead of keeping the order already in the file.
+const ORDERED_TABLES: [&str; 1] = ["folder_map"];
+
+/// How a settings file is written, going by its extension.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Format {
+    Json,
+    /// Easier to edit by hand, and comments added there are kept.
+    Toml,
+}
+
+impl Format {
+    pub fn of(path: &Path) -> Self {
+        match path.extension() {
+            Some(ext) if ext.eq_ignore_ascii_case("toml") => Format::Toml,
+            _ => Format::Json,
+        }
+    }
+}
+
+/// Turns `value` into the text of a settings file. For TOML, `previous` is the
+/// file as it is now, whose comments and key order are carried over.
+pub fn encode<T: Serialize>(format: Format, value: &T, previous: Option<&str>) -> Result<String, String> {
+    match format {
+        Format::Json => serde_json::to_string_pretty(value).map_err(|e| e.to_string()),
+        Format::Toml => {
+            let mut new = toml::to_string(value).map_err(|e| e.to_string())?.parse::<DocumentMut>().map_err(|e| e.to_string())?;
+            let Some(mut document) = previous.and_then(|text| text.parse::<DocumentMut>().ok()) else {
+                return Ok(new.to_string());
+            };
+            merge(document.as_table_mut(), std::mem::take(new.as_table_mut()));
+            Ok(document.to_string())
+        }
+    }
+}
+
+pub fn decode<T: DeserializeOwned>(format: Format, text: &str) -> Result<T, String> {
+    match format {
+        Format::Json => serde_json::from_str(text).map_err(|e| e.to_string()),
+        Format::Toml => toml::from_str(text).map_err(|e| e.to_string()),
+    }
+}
+
+/// Puts the values of `new` into `old`, keeping what surrounds those already there.
+fn merge(old: &mut Table, new: Table) {
+    let gone: Vec<String> = old.iter().map(|(key, _)| key.to_string()).filter(|key| !new.contains_key(key)).collect();
+    for key in gone {
+        old.remove(&key);
+    }
+
+    for (key, item) in new {
+        match (old.get_mut(key.as_str()), item) {
+            (Some(Item::Table(old_table)), Item::Table(new_table)) => {
+                let order: Vec<String> = new_table.iter().map(|(key, _)| key.to_string()).collect();
+                merge(old_table, new_table);
+                if ORDERED_TABLES.contains(&key.as_str()) {
+                    let position = |key: &str| order.iter().position(|k| k == key);
+                    old_table.sort_values_by(|a, _, b, _| position(a.get()).cmp(&position(b.get())));
+                }
+            }
+            (Some(Item::ArrayOfTables(old_tables)), Item::ArrayOfTables(new_tables)) => {
+                let mut new_tables = new_tables.into_iter();
+                let mut kept = 0;
+                for (old_table, new_table) in old_tables.iter_mut().zip(new_tables.by_ref()) {
+                    merge(old_table, new_table);
+                    kept += 1;
+                }
+                while old_tables.len() > kept {
+                    old_tables.remove(old_tables.len() - 1);
+                }
+                for table in new_tables {
+                    old_tables.push(table);
+                }
+            }
+            (Some(Item::Value(old_value)), Item::Value(mut new_value)) => {
+                // A comment after the value belongs to the value.
+                *new_value.decor_mut() = old_value.decor().clone();
+                *old_value = new_value;
+            }
+            (Some(old_item), item) => *old_item = item,
+            (None, item) => {
+                old.insert(key.as_str(), item);
+            }
+        }
+    }
+}
+
+/// What came of reading the settings file.
+pub enum Loaded<T> {
+    /// There is no settings file yet.
+    Missing,
+    Read(T),
+    /// The file couldn't be read or parsed for the given reason, so the backup of
+    /// the previous version was used instead.
+    Restored(T, String),
+    /// Neither the file nor its backup could be used.
+    Failed(String),
+}
+
+fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+pub fn backup_path(path: &Path) -> PathBuf {
+    sibling(path, ".bak")
+}
+
+/// Writes `contents` to a temporary file next to `path` and renames it over the
+/// original, which is first copied to the backup. The file at `path` is always
+/// either the old or the new version in full.
+pub fn write(path: &Path, contents: &[u8]) -> io::Result<()> {
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::create_dir_all(dir)?;
+    }
+
+    let temp = sibling(path, ".tmp");
+    let mut file = fs::File::create(&temp)?;
+    file.write_all(contents)?;
+    file.sync_all()?;
+    drop(file);
+
+    if path.exists() {
+        let _ = fs::copy(path, backup_path(path));
+    }
+    fs::rename(&temp, path)
+}
+
+/// Where the settings are copied before the user resets some of them.
+pub fn reset_backup_path(path: &Path) -> PathBuf {
+    sibling(path, ".before-reset")
+}
+
+/// Where settings go while the file at `path` is damaged and mustn't be overwritten.
+pub fn pending_path(path: &Path) -> PathBuf {
+    sibling(path, ".pending")
+}
+
+/// Moves a damaged settings file out of the way, keeping it for inspection.
+pub fn set_aside(path: &Path) -> io::Result<PathBuf> {
+    let corrupt = sibling(path, ".corrupt");
+    fs::rename(path, &corrupt)?;
+    Ok(corrupt)
+}
+
+/// Reads `path`, falling back to its backup if the file itself is damaged.
+pub fn read<T: DeserializeOwned>(path: &Path) -> Loaded<T> {
+    let format = Format::of(path);
+    let error = match fs::read_to_string(path) {
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Loaded::Missing,
+        Err(e) => e.to_string(),
+        Ok(text) => match decode(format, &text) {
+            Ok(value) => return Loaded::Read(value),
+            Err(e) => e,
+        },
+    };
+
+    match fs::read_to_string(backup_path(path)).ok().and_then(|text| decode(format, &text).ok()) {
+        Some(value) => Loaded::Restored(value, error),
+        None => Loaded::Failed(error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+    use crate::FolderMap;
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Settings {
+        folders: Vec<String>,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct FolderSettings {
+        current_index: usize,
+        folder_map: FolderMap,
+    }
+
+    fn unusual_folders() -> FolderSettings {
+        let folders = [
+            r"C:\Users\Me\Pictures\Refs",
+            r"\\server\share\poses [2024]",
+            "/home/me/refs with spaces/\"quoted\"",
+            "D:/référence/手/é#=.hidden",
+            "it's = a # 'path'.toml",
+            "",
+        ];
+        FolderSettings {
+            current_index: 3,
+            folder_map: folders.iter().enumerate().map(|(i, f)| (PathBuf::from(f), i % 2 == 0)).collect(),
+        }
+    }
+
+    #[test]
+    fn format_follows_extension() {
+        assert_eq!(Format::of(Path::new("config.toml")), Format::Toml);
+        assert_eq!(Format::of(Path::new("work.TOML")), Format::Toml);
+        assert_eq!(Format::of(Path::new("config.json")), Format::Json);
+        assert_eq!(Format::of(Path::new("config")), Format::Json);
+    }
+
+    #[test]
+    fn folder_map_round_trips_through_both_formats() {
+        for format in [Format::Json, Format::Toml] {
+            let text = encode(format, &unusual_folders(), None).unwrap();
+            assert_eq!(decode::<FolderSettings>(format, &text).unwrap(), unusual_folders(), "{:?}", format);
+        }
+    }
+
+    #[test]
+    fn folder_order_is_kept() {
+        for format in [Format::Json, Format::Toml] {
+            let text = encode(format, &unusual_folders(), None).unwrap();
+            let decoded = decode::<FolderSettings>(format, &text).unwrap();
+            assert!(decoded.folder_map.keys().eq(unusual_folders().folder_map.keys()), "{:?}", format);
+        }
+        let old = r#"{"current_index": 0, "folder_map": {"/b": true, "/a": false, "/c": true}}"#;
+        let decoded = decode::<FolderSettings>(Format::Json, old).unwrap();
+        assert!(decoded.folder_map.keys().eq([Path::new("/b"), Path::new("/a"), Path::new("/c")]));
+
+        // Saving over a file with the folders in another order moves them.
+        let mut reordered = unusual_folders();
+        reordered.folder_map.reverse();
+        let previous = encode(Format::Toml, &unusual_folders(), None).unwrap();
+        let text = encode(Format::Toml, &reordered, Some(&previous)).unwrap();
+        let decoded = decode::<FolderSettings>(Format::Toml, &text).unwrap();
+        assert!(decoded.folder_map.keys().eq(reordered.folder_map.keys()));
+    }
+
+    #[test]
+    fn toml_keeps_comments_and_order() {
+        let previous = "# My settings\n\
+                        current_index = 0 # where I got to\n\
+                        \n\
+                        # Folders I draw from\n\
+                        [folder_map]\n\
+                        \"/old\" = true\n";
+        let text = encode(Format::Toml, &unusual_folders(), Some(previous)).unwrap();
+
+        assert!(text.starts_with("# My settings\ncurrent_index = 3 # where I got to\n"), "{}", text);
+        assert!(text.contains("# Folders I draw from\n[folder_map]"), "{}", text);
+        assert!(!text.contains("/old"), "{}", text);
+        assert_eq!(decode::<FolderSettings>(Format::Toml, &text).unwrap(), unusual_folders());
+
+        // A second save over the result changes nothing.
+        assert_eq!(encode(Format::Toml, &unusual_folders(), Some(&text)).unwrap(), text);
+    }
+
+    #[test]
+    fn damaged_toml_is_restored_from_backup() {
+        let path = scratch("toml").with_extension("toml");
+        write(&path, encode(Format::Toml, &unusual_folders(), None).unwrap().as_bytes()).unwrap();
+        write(&path, b"current_index = ").unwrap();
+
+        assert!(matches!(read::<FolderSettings>(&path), Loaded::Restored(s, _) if s == unusual_folders()));
+    }
+
+    fn scratch(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("timerdrawing-settings-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir.join("config.json")
+    }
+
+    fn settings(folder: &str) -> Settings {
+        Settings {
+            folders: vec![folder.to_string()],
+        }
+    }
+
+    #[test]
+    fn write_keeps_previous_version_as_backup() {
+        let path = scratch("rotate");
+        write(&path, br#"{"folders": ["first"]}"#).unwrap();
+        write(&path, br#"{"folders": ["second"]}"#).unwrap();
+
+        assert!(matches!(read::<Settings>(&path), Loaded::Read(s) if s == settings("second")));
+        assert_eq!(fs::read_to_string(backup_path(&path)).unwrap(), r#"{"folders": ["first"]}"#);
+        assert!(!sibling(&path, ".tmp").exists());
+    }
+
+    #[test]
+    fn truncated_file_is_restored_from_backup() {
+        let path = scratch("truncated");
+        write(&path, br#"{"folders": ["first"]}"#).unwrap();
+        write(&path, br#"{"folders": ["second"]}"#).unwrap();
+        // What a crash in the middle of a plain write used to leave behind.
+        fs::write(&path, r#"{"folders": ["sec"#).unwrap();
+
+        assert!(matches!(read::<Settings>(&path), Loaded::Restored(s, _) if s == settings("first")));
+    }
+
+    #[test]
+    fn empty_file_without_backup_fails() {
+        let path = scratch("empty");
+        fs::write(&path, "").unwrap();
+
+        assert!(matches!(read::<Settings>(&path), Loaded::Failed(_)));
+    }
+
+    #[test]
+    fn missing_file_is_not_an_error() {
+        let path = scratch("missing");
+
+        assert!(matches!(read::<Settings>(&path), Loaded::Missing));
+    }
+}
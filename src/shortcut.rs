@@ -0,0 +1,203 @@
+//! Keys that work while the viewer has focus. Unlike the global hotkeys in
+//! `hotkey` they can be bare keys, and they're read from egui's input rather than
+//! registered with the system.
+
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::{
+    hotkey::{Binding, Command},
+    i18n::tr,
+};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Action {
+    NextImage,
+    PreviousImage,
+    ToggleBrowser,
+    TogglePause,
+    ToggleMute,
+    AddMinute,
+    SubtractMinute,
+    AddFiveMinutes,
+    SubtractFiveMinutes,
+    RotateClockwise,
+    RotateCounterClockwise,
+    FlipHorizontally,
+    FlipVertically,
+    ToggleGrayscale,
+    CycleValueFilter,
+    ToggleEyedropper,
+    ToggleMeasure,
+    ToggleCrop,
+    ClearCrop,
+    ToggleGrid,
+    ToggleHistogram,
+    ToggleInfoBar,
+    ToggleHud,
+    ResetZoom,
+    CycleFitMode,
+    ToggleFullscreen,
+    UiScaleUp,
+    UiScaleDown,
+    UiScaleReset,
+    ShowShortcuts,
+}
+
+impl Action {
+    pub const ALL: [Action; 30] = [
+        Self::NextImage,
+        Self::PreviousImage,
+        Self::ToggleBrowser,
+        Self::TogglePause,
+        Self::ToggleMute,
+        Self::AddMinute,
+        Self::SubtractMinute,
+        Self::AddFiveMinutes,
+        Self::SubtractFiveMinutes,
+        Self::RotateClockwise,
+        Self::RotateCounterClockwise,
+        Self::FlipHorizontally,
+        Self::FlipVertically,
+        Self::ToggleGrayscale,
+        Self::CycleValueFilter,
+        Self::ToggleEyedropper,
+        Self::ToggleMeasure,
+        Self::ToggleCrop,
+        Self::ClearCrop,
+        Self::ToggleGrid,
+        Self::ToggleHistogram,
+        Self::ToggleInfoBar,
+        Self::ToggleHud,
+        Self::ResetZoom,
+        Self::CycleFitMode,
+        Self::ToggleFullscreen,
+        Self::UiScaleUp,
+        Self::UiScaleDown,
+        Self::UiScaleReset,
+        Self::ShowShortcuts,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::NextImage => tr("Next image"),
+            Self::PreviousImage => tr("Previous image"),
+            Self::ToggleBrowser => tr("Browse images"),
+            Self::TogglePause => tr("Pause / resume timer"),
+            Self::ToggleMute => tr("Mute alarm sounds"),
+            Self::AddMinute => tr("Add a minute"),
+            Self::SubtractMinute => tr("Take off a minute"),
+            Self::AddFiveMinutes => tr("Add five minutes"),
+            Self::SubtractFiveMinutes => tr("Take off five minutes"),
+            Self::RotateClockwise => tr("Rotate clockwise"),
+            Self::RotateCounterClockwise => tr("Rotate counter-clockwise"),
+            Self::FlipHorizontally => tr("Flip horizontally"),
+            Self::FlipVertically => tr("Flip vertically"),
+            Self::ToggleGrayscale => tr("Grayscale"),
+            Self::CycleValueFilter => tr("Next value study (notan, 3, 5 values)"),
+            Self::ToggleEyedropper => tr("Eyedropper (copy a color)"),
+            Self::ToggleMeasure => tr("Measure lengths and angles"),
+            Self::ToggleCrop => tr("Crop to a region"),
+            Self::ClearCrop => tr("Show the whole image"),
+            Self::ToggleGrid => tr("Grid"),
+            Self::ToggleHistogram => tr("Histogram"),
+            Self::ToggleInfoBar => tr("File name bar"),
+            Self::ToggleHud => tr("Hide timer and overlays"),
+            Self::ResetZoom => tr("Reset zoom"),
+            Self::CycleFitMode => tr("Next image size (fit, fill, actual pixels, stretch)"),
+            Self::ToggleFullscreen => tr("Fullscreen"),
+            Self::UiScaleUp => tr("Larger menus and windows"),
+            Self::UiScaleDown => tr("Smaller menus and windows"),
+            Self::UiScaleReset => tr("Menus and windows at normal size"),
+            Self::ShowShortcuts => tr("This list of shortcuts"),
+        }
+    }
+}
+
+pub fn default_shortcuts() -> BTreeMap<Action, Binding> {
+    let none = egui::Modifiers::NONE;
+    let shift = egui::Modifiers::SHIFT;
+    let alt = egui::Modifiers::ALT;
+    let ctrl = egui::Modifiers::CTRL;
+    BTreeMap::from([
+        (Action::NextImage, Binding::new(none, egui::Key::ArrowRight)),
+        (Action::PreviousImage, Binding::new(none, egui::Key::ArrowLeft)),
+        (Action::ToggleBrowser, Binding::new(none, egui::Key::Tab)),
+        (Action::TogglePause, Binding::new(none, egui::Key::P)),
+        (Action::ToggleMute, Binding::new(none, egui::Key::M)),
+        (Action::AddMinute, Binding::new(none, egui::Key::Plus)),
+        (Action::SubtractMinute, Binding::new(none, egui::Key::Minus)),
+        (Action::AddFiveMinutes, Binding::new(alt, egui::Key::Plus)),
+        (Action::SubtractFiveMinutes, Binding::new(alt, egui::Key::Minus)),
+        (Action::RotateClockwise, Binding::new(none, egui::Key::R)),
+        (Action::RotateCounterClockwise, Binding::new(shift, egui::Key::R)),
+        (Action::FlipHorizontally, Binding::new(none, egui::Key::H)),
+        (Action::FlipVertically, Binding::new(none, egui::Key::V)),
+        (Action::ToggleGrayscale, Binding::new(none, egui::Key::G)),
+        (Action::CycleValueFilter, Binding::new(none, egui::Key::N)),
+        (Action::ToggleEyedropper, Binding::new(none, egui::Key::C)),
+        (Action::ToggleMeasure, Binding::new(none, egui::Key::L)),
+        (Action::ToggleCrop, Binding::new(none, egui::Key::X)),
+        (Action::ClearCrop, Binding::new(shift, egui::Key::X)),
+        (Action::ToggleGrid, Binding::new(ctrl, egui::Key::G)),
+        (Action::ToggleHistogram, Binding::new(ctrl, egui::Key::H)),
+        (Action::ToggleInfoBar, Binding::new(none, egui::Key::I)),
+        (Action::ToggleHud, Binding::new(none, egui::Key::T)),
+        (Action::ResetZoom, Binding::new(none, egui::Key::Num0)),
+        (Action::CycleFitMode, Binding::new(none, egui::Key::F)),
+        (Action::ToggleFullscreen, Binding::new(none, egui::Key::F11)),
+        (Action::UiScaleUp, Binding::new(ctrl, egui::Key::Plus)),
+        (Action::UiScaleDown, Binding::new(ctrl, egui::Key::Minus)),
+        (Action::UiScaleReset, Binding::new(ctrl, egui::Key::Num0)),
+        (Action::ShowShortcuts, Binding::new(none, egui::Key::F1)),
+    ])
+}
+
+/// The actions whose keys were pressed this frame, leaving out any whose keys
+/// are in `conflicts`.
+pub fn pressed(shortcuts: &BTreeMap<Action, Binding>, conflicts: &BTreeMap<Action, String>, input: &egui::InputState) -> Vec<Action> {
+    shortcuts
+        .iter()
+        .filter(|(action, binding)| !conflicts.contains_key(action) && binding.pressed(input))
+        .map(|(action, _)| *action)
+        .collect()
+}
+
+/// Why each action that can't be used as bound can't be: its keys are already
+/// used by an earlier action, or taken by a global hotkey before the viewer sees
+/// them.
+pub fn conflicts(shortcuts: &BTreeMap<Action, Binding>, hotkeys: &BTreeMap<Command, Binding>) -> BTreeMap<Action, String> {
+    let mut conflicts = BTreeMap::new();
+    for (action, binding) in shortcuts {
+        if let Some((other, _)) = shortcuts.iter().find(|(other, b)| *other < action && *b == binding) {
+            conflicts.insert(*action, format!("Also used for \"{}\"", other.label()));
+        } else if let Some((command, _)) = hotkeys.iter().find(|(_, b)| *b == binding) {
+            conflicts.insert(*action, format!("Taken by the global hotkey for \"{}\"", command.label()));
+        }
+    }
+    conflicts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults_have_no_conflicts() {
+        let conflicts = conflicts(&default_shortcuts(), &crate::hotkey::default_bindings());
+        assert!(conflicts.is_empty());
+    }
+
+    #[test]
+    fn later_duplicates_conflict() {
+        let mut shortcuts = default_shortcuts();
+        shortcuts.insert(Action::ToggleMute, Binding::new(egui::Modifiers::NONE, egui::Key::ArrowRight));
+        let mut hotkeys = BTreeMap::new();
+        hotkeys.insert(Command::NextImage, Binding::new(egui::Modifiers::NONE, egui::Key::G));
+
+        let conflicts = conflicts(&shortcuts, &hotkeys);
+        let actions: Vec<Action> = conflicts.keys().copied().collect();
+        assert_eq!(actions, [Action::ToggleMute, Action::ToggleGrayscale]);
+    }
+}
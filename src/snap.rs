@@ -0,0 +1,124 @@
+//! Keeping the window flush with the edges of the screen, so a small reference
+//! pinned in a corner sits neatly there. A window let go near an edge or corner
+//! of its monitor's work area is pulled flush with it, and the throw hotkeys send
+//! it to a spot on the screen at a set size.
+
+use eframe::egui::{pos2, Pos2, Rect, Vec2};
+use serde::{Deserialize, Serialize};
+
+#[cfg(windows)]
+use windows::Win32::UI::Input::KeyboardAndMouse::{GetAsyncKeyState, VK_LBUTTON};
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct SnapSettings {
+    pub enabled: bool,
+    /// How close to an edge, in points, the window has to be let go to snap to it.
+    pub distance: f32,
+    /// The size a thrown window takes, as a share of the work area's width and height.
+    pub throw_size_percent: u8,
+}
+
+impl Default for SnapSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            distance: 15.0,
+            throw_size_percent: 33,
+        }
+    }
+}
+
+/// Where on the screen a window is thrown to, laid out like the number pad.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Spot {
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+impl Spot {
+    /// The top-left corner for a window frame `size` large at this spot in `work`.
+    pub fn position(self, size: Vec2, work: Rect) -> Pos2 {
+        let (column, row) = match self {
+            Self::TopLeft => (0.0, 0.0),
+            Self::Top => (0.5, 0.0),
+            Self::TopRight => (1.0, 0.0),
+            Self::Left => (0.0, 0.5),
+            Self::Center => (0.5, 0.5),
+            Self::Right => (1.0, 0.5),
+            Self::BottomLeft => (0.0, 1.0),
+            Self::Bottom => (0.5, 1.0),
+            Self::BottomRight => (1.0, 1.0),
+        };
+        let room = (work.size() - size).max(Vec2::ZERO);
+        work.min + room * Vec2::new(column, row)
+    }
+}
+
+/// Where the window frame `frame` goes to be flush with whichever edges of
+/// `work` it's within `distance` of, or `None` if it's near none or already flush.
+pub fn snapped(frame: Rect, work: Rect, distance: f32) -> Option<Pos2> {
+    let snap = |min: f32, max: f32, work_min: f32, work_max: f32| {
+        if (min - work_min).abs() <= distance {
+            work_min
+        } else if (max - work_max).abs() <= distance {
+            work_max - (max - min)
+        } else {
+            min
+        }
+    };
+    let position = pos2(
+        snap(frame.min.x, frame.max.x, work.min.x, work.max.x),
+        snap(frame.min.y, frame.max.y, work.min.y, work.max.y),
+    );
+    (position != frame.min).then_some(position)
+}
+
+/// Whether the left mouse button is down anywhere, as it is while the system
+/// drags the window by its title bar and the viewer sees no pointer.
+#[cfg(windows)]
+pub fn mouse_held() -> bool {
+    // The high bit, which makes it negative, is set while the button is down.
+    let state = unsafe { GetAsyncKeyState(VK_LBUTTON.0 as i32) };
+    state < 0
+}
+
+#[cfg(not(windows))]
+pub fn mouse_held() -> bool {
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use eframe::egui::vec2;
+
+    #[test]
+    fn snaps_only_near_edges() {
+        let work = Rect::from_min_size(pos2(0.0, 0.0), vec2(1920.0, 1040.0));
+        let near_corner = Rect::from_min_size(pos2(1500.0, 10.0), vec2(410.0, 300.0));
+        assert_eq!(snapped(near_corner, work, 15.0), Some(pos2(1510.0, 0.0)));
+
+        let free = Rect::from_min_size(pos2(400.0, 300.0), vec2(410.0, 300.0));
+        assert_eq!(snapped(free, work, 15.0), None);
+
+        let flush = Rect::from_min_size(pos2(0.0, 740.0), vec2(410.0, 300.0));
+        assert_eq!(snapped(flush, work, 15.0), None);
+    }
+
+    #[test]
+    fn spots() {
+        let work = Rect::from_min_size(pos2(100.0, 0.0), vec2(1000.0, 800.0));
+        let size = vec2(200.0, 100.0);
+        assert_eq!(Spot::TopLeft.position(size, work), pos2(100.0, 0.0));
+        assert_eq!(Spot::Center.position(size, work), pos2(500.0, 350.0));
+        assert_eq!(Spot::BottomRight.position(size, work), pos2(900.0, 700.0));
+    }
+}
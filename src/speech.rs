@@ -0,0 +1,55 @@
+use std::{
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
+};
+
+#[cfg(windows)]
+use windows::{
+    core::HSTRING,
+    Win32::Media::Speech::{ISpVoice, SpVoice},
+    Win32::System::Com::{CoCreateInstance, CoInitializeEx, CLSCTX_ALL, COINIT_MULTITHREADED},
+};
+
+/// Speaks phrases through SAPI on a dedicated thread. Phrases queue up behind each
+/// other; if no voice is installed they are silently dropped.
+pub struct Speaker {
+    tx: Sender<(String, f32)>,
+}
+
+impl Speaker {
+    pub fn new() -> Self {
+        let (tx, rx) = mpsc::channel::<(String, f32)>();
+        thread::spawn(move || speak(rx));
+        Self { tx }
+    }
+
+    pub fn say(&self, text: String, volume: f32) {
+        let _ = self.tx.send((text, volume));
+    }
+}
+
+#[cfg(windows)]
+fn speak(rx: Receiver<(String, f32)>) {
+    let voice: Option<ISpVoice> = unsafe {
+        let _ = CoInitializeEx(None, COINIT_MULTITHREADED);
+        CoCreateInstance(&SpVoice, None, CLSCTX_ALL).ok()
+    };
+    if voice.is_none() {
+        log::warn!("No text-to-speech voice available");
+    }
+
+    for (text, volume) in rx {
+        if let Some(voice) = &voice {
+            unsafe {
+                let _ = voice.SetVolume((volume.clamp(0.0, 1.0) * 100.0) as u16);
+                let _ = voice.Speak(&HSTRING::from(text), 0, None);
+            }
+        }
+    }
+}
+
+#[cfg(not(windows))]
+fn speak(rx: Receiver<(String, f32)>) {
+    log::warn!("No text-to-speech voice available");
+    for _ in rx {}
+}
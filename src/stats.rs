@@ -0,0 +1,501 @@
+use chrono::{DateTime, Days, Local, NaiveDate, TimeZone};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io::Write,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    session::ImageView,
+    settings_file,
+    stats_db::{Database, ViewCount},
+};
+
+const FLUSH_INTERVAL: Duration = Duration::from_secs(60);
+/// How far back the most viewed images are counted.
+const MOST_VIEWED_DAYS: u64 = 30;
+const MOST_VIEWED_LIMIT: usize = 10;
+/// Distinct documents kept per day; time in any further ones goes to `OTHER_DOCUMENTS`.
+const MAX_DOCUMENTS_PER_DAY: usize = 100;
+pub const OTHER_DOCUMENTS: &str = "(other documents)";
+
+#[derive(Serialize, Deserialize, Default, Clone)]
+#[serde(default)]
+pub struct DayStats {
+    /// Tracked seconds per target exe name.
+    pub exe_seconds: BTreeMap<String, u64>,
+    pub pomodoros: u32,
+    /// Tracked seconds spent past an alarm that had already fired.
+    pub overtime_seconds: u64,
+    pub images_viewed: u32,
+    /// Tracked seconds per document, as read from the target's window title.
+    pub document_seconds: BTreeMap<String, u64>,
+}
+
+impl DayStats {
+    pub fn total_seconds(&self) -> u64 {
+        self.exe_seconds.values().sum()
+    }
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+struct StatsFile {
+    days: BTreeMap<NaiveDate, DayStats>,
+}
+
+/// What went wrong reading the statistics file.
+pub enum Damaged {
+    /// It couldn't be read for the given reason, so it was copied to the backup
+    /// path and the statistics started over.
+    BackedUp(PathBuf, String),
+    /// It couldn't be read, nor copied aside, so it's left as it is and nothing
+    /// is saved over it this run.
+    Kept(String),
+}
+
+/// Tracked time bucketed by local calendar day, persisted to `stats.json`, or to a
+/// database that also keeps sessions and every image view.
+pub struct StatsStore {
+    path: PathBuf,
+    database: Option<(PathBuf, Database)>,
+    data: StatsFile,
+    /// Sub-second remainders per exe, so frequent small deltas don't get rounded away.
+    pending: BTreeMap<String, Duration>,
+    pending_documents: BTreeMap<String, Duration>,
+    pending_overtime: Duration,
+    /// Days changed since the last flush.
+    dirty_days: BTreeSet<NaiveDate>,
+    /// Image views waiting to go into the database.
+    views: Vec<ImageView>,
+    most_viewed: Vec<ViewCount>,
+    last_flush: Instant,
+    /// Why the file couldn't be used, until it's been reported.
+    damaged: Option<Damaged>,
+    /// Leaves the file alone, as it couldn't be read and there's no copy of it.
+    read_only: bool,
+}
+
+impl StatsStore {
+    pub fn load(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let read = match std::fs::read_to_string(&path) {
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(StatsFile::default()),
+            Err(e) => Err(e.to_string()),
+            Ok(json) => serde_json::from_str(&json).map_err(|e| e.to_string()),
+        };
+        let (data, damaged) = match read {
+            Ok(data) => (data, None),
+            Err(error) => {
+                let backup = settings_file::backup_path(&path);
+                let damaged = match std::fs::copy(&path, &backup) {
+                    Ok(_) => Damaged::BackedUp(backup, error),
+                    Err(_) => Damaged::Kept(error),
+                };
+                (StatsFile::default(), Some(damaged))
+            }
+        };
+        let read_only = matches!(damaged, Some(Damaged::Kept(_)));
+
+        Self {
+            path,
+            database: None,
+            data,
+            pending: BTreeMap::new(),
+            pending_documents: BTreeMap::new(),
+            pending_overtime: Duration::ZERO,
+            dirty_days: BTreeSet::new(),
+            views: Vec::new(),
+            most_viewed: Vec::new(),
+            last_flush: Instant::now(),
+            damaged,
+            read_only,
+        }
+    }
+
+    /// Why the statistics file couldn't be used, the first time it's asked.
+    pub fn take_damaged(&mut self) -> Option<Damaged> {
+        self.damaged.take()
+    }
+
+    /// Keeps the statistics loaded by `load` in the database at `database_path`
+    /// from now on. A new database takes over what's in the JSON file; if it
+    /// can't be opened, the JSON file is used as before.
+    pub fn open_database(&mut self, database_path: PathBuf) -> rusqlite::Result<()> {
+        let mut database = Database::open(&database_path)?;
+        if database.has_days()? {
+            self.data.days = database.read_days()?;
+        } else {
+            database.write_days(&self.data.days, self.data.days.keys())?;
+        }
+        self.database = Some((database_path, database));
+        self.refresh_most_viewed();
+        Ok(())
+    }
+
+    /// Moves the statistics into the database at `database_path`, replacing what
+    /// it held, or with `None` back into the JSON file.
+    pub fn set_database(&mut self, database_path: Option<PathBuf>) -> Result<(), String> {
+        self.flush()?;
+        match database_path {
+            Some(database_path) => {
+                let mut database = Database::open(&database_path).map_err(|e| e.to_string())?;
+                let stored = database.read_days().map_err(|e| e.to_string())?;
+                database
+                    .write_days(&self.data.days, stored.keys().chain(self.data.days.keys()))
+                    .map_err(|e| e.to_string())?;
+                self.database = Some((database_path, database));
+                self.refresh_most_viewed();
+            }
+            None => {
+                self.database = None;
+                self.most_viewed.clear();
+                self.dirty_days.extend(self.data.days.keys().copied());
+                self.flush()?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn uses_database(&self) -> bool {
+        self.database.is_some()
+    }
+
+    /// Credits `delta` of tracked time that ended `ended_ago` before now to `exe`. The
+    /// interval is anchored on the wall clock only at this point, so a session running
+    /// across local midnight is split between both days, and clock or DST jumps can't
+    /// produce negative or duplicated time because `delta` itself comes from a monotonic clock.
+    pub fn record(&mut self, exe: &str, delta: Duration, ended_ago: Duration) {
+        let pending = self.pending.entry(exe.to_string()).or_default();
+        for (day, whole) in split_by_day(pending, delta, ended_ago) {
+            let day_stats = self.data.days.entry(day).or_default();
+            *day_stats.exe_seconds.entry(exe.to_string()).or_default() += whole;
+            self.dirty_days.insert(day);
+        }
+    }
+
+    /// Like `record`, for time spent on a document. Once a day has
+    /// `MAX_DOCUMENTS_PER_DAY` documents, new ones are lumped together.
+    pub fn record_document(&mut self, document: &str, delta: Duration, ended_ago: Duration) {
+        if self.pending_documents.len() >= MAX_DOCUMENTS_PER_DAY && !self.pending_documents.contains_key(document) {
+            // Only sub-second remainders are lost.
+            self.pending_documents.clear();
+        }
+        let pending = self.pending_documents.entry(document.to_string()).or_default();
+        for (day, whole) in split_by_day(pending, delta, ended_ago) {
+            let documents = &mut self.data.days.entry(day).or_default().document_seconds;
+            let name = if documents.len() < MAX_DOCUMENTS_PER_DAY || documents.contains_key(document) {
+                document
+            } else {
+                OTHER_DOCUMENTS
+            };
+            *documents.entry(name.to_string()).or_default() += whole;
+            self.dirty_days.insert(day);
+        }
+    }
+
+    /// Like `record`, for tracked time spent past a fired alarm.
+    pub fn record_overtime(&mut self, delta: Duration, ended_ago: Duration) {
+        for (day, whole) in split_by_day(&mut self.pending_overtime, delta, ended_ago) {
+            self.data.days.entry(day).or_default().overtime_seconds += whole;
+            self.dirty_days.insert(day);
+        }
+    }
+
+    pub fn record_pomodoro(&mut self) {
+        self.data.days.entry(today()).or_default().pomodoros += 1;
+        self.dirty_days.insert(today());
+    }
+
+    pub fn record_image_viewed(&mut self) {
+        self.data.days.entry(today()).or_default().images_viewed += 1;
+        self.dirty_days.insert(today());
+    }
+
+    /// Keeps a finished image view, if there's a database to keep it in.
+    pub fn record_view(&mut self, view: &ImageView) {
+        if self.database.is_some() {
+            self.views.push(view.clone());
+        }
+    }
+
+    /// Deletes the image views kept so far, which list the paths of the images
+    /// shown, along with the most viewed images counted from them.
+    pub fn forget_views(&mut self) -> rusqlite::Result<()> {
+        self.views.clear();
+        self.most_viewed.clear();
+        match &self.database {
+            Some((_, database)) => database.clear_views(),
+            None => Ok(()),
+        }
+    }
+
+    /// Keeps a summary of the viewer's run, if there's a database to keep it in.
+    pub fn record_session(&mut self, started_at: u64, tracked: Duration, images_viewed: usize) -> rusqlite::Result<()> {
+        match &self.database {
+            Some((_, database)) => {
+                let ended_at = Local::now().timestamp().max(0) as u64;
+                database.insert_session(started_at, ended_at, tracked.as_secs(), images_viewed)
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// The images shown most often lately, as of the last flush. Only kept with a database.
+    pub fn most_viewed(&self) -> &[ViewCount] {
+        &self.most_viewed
+    }
+
+    fn refresh_most_viewed(&mut self) {
+        if let Some((_, database)) = &self.database {
+            let since = today().checked_sub_days(Days::new(MOST_VIEWED_DAYS)).unwrap_or(NaiveDate::MIN);
+            self.most_viewed = database.most_viewed(since, MOST_VIEWED_LIMIT).unwrap_or_default();
+        }
+    }
+
+    /// Deletes everything from more than `keep_days` days ago and returns how many
+    /// days of data that was.
+    pub fn prune(&mut self, keep_days: u32) -> Result<usize, String> {
+        let Some(cutoff) = today().checked_sub_days(Days::new(keep_days as u64)) else {
+            return Ok(0);
+        };
+        let old: Vec<NaiveDate> = self.data.days.range(..cutoff).map(|(day, _)| *day).collect();
+        for day in &old {
+            self.data.days.remove(day);
+        }
+        self.dirty_days.extend(old.iter().copied());
+        self.flush()?;
+        if let Some((_, database)) = &self.database {
+            database.prune(cutoff).map_err(|e| e.to_string())?;
+            self.refresh_most_viewed();
+        }
+        Ok(old.len())
+    }
+
+    pub fn day(&self, day: NaiveDate) -> Option<&DayStats> {
+        self.data.days.get(&day)
+    }
+
+    pub fn all_time_seconds(&self) -> u64 {
+        self.data.days.values().map(DayStats::total_seconds).sum()
+    }
+
+    /// Writes to disk if something changed and the flush interval has passed.
+    pub fn maybe_flush(&mut self) -> Result<(), String> {
+        let dirty = !self.dirty_days.is_empty() || !self.views.is_empty();
+        if dirty && self.last_flush.elapsed() >= FLUSH_INTERVAL {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Writes what changed to disk. On failure it's kept, to be tried again on the
+    /// next flush.
+    pub fn flush(&mut self) -> Result<(), String> {
+        self.last_flush = Instant::now();
+        if self.dirty_days.is_empty() && self.views.is_empty() {
+            return Ok(());
+        }
+
+        if let Some((_, database)) = &mut self.database {
+            database
+                .write_days(&self.data.days, &self.dirty_days)
+                .and_then(|()| database.insert_views(&self.views))
+                .map_err(|e| e.to_string())?;
+            self.dirty_days.clear();
+            self.views.clear();
+            self.refresh_most_viewed();
+            return Ok(());
+        }
+
+        if self.read_only {
+            return Ok(());
+        }
+        let json = serde_json::to_string_pretty(&self.data).map_err(|e| e.to_string())?;
+        std::fs::write(&self.path, json).map_err(|e| e.to_string())?;
+        self.dirty_days.clear();
+        self.views.clear();
+        Ok(())
+    }
+
+    /// Forgets all statistics. The old file is kept as `stats.json.bak`, or
+    /// `stats.sqlite.bak` with a database.
+    pub fn reset(&mut self) -> Result<(), String> {
+        let mut backup = self.path().to_path_buf().into_os_string();
+        backup.push(".bak");
+        let _ = std::fs::copy(self.path(), backup);
+        self.data = StatsFile::default();
+        self.pending.clear();
+        self.pending_documents.clear();
+        self.pending_overtime = Duration::ZERO;
+        self.views.clear();
+        self.most_viewed.clear();
+        self.dirty_days.clear();
+        match &self.database {
+            Some((_, database)) => database.clear().map_err(|e| e.to_string()),
+            None => {
+                self.dirty_days.insert(today());
+                self.flush()
+            }
+        }
+    }
+
+    /// The file the statistics are kept in.
+    pub fn path(&self) -> &Path {
+        match &self.database {
+            Some((path, _)) => path,
+            None => &self.path,
+        }
+    }
+
+    /// Writes one CSV row per day in `days`, including days without any data.
+    pub fn export_days(&self, days: &[NaiveDate], path: &Path) -> std::io::Result<()> {
+        let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+        writeln!(file, "date,tracked_seconds,images_viewed,pomodoros,overtime_seconds")?;
+        for day in days {
+            let stats = self.day(*day).cloned().unwrap_or_default();
+            writeln!(
+                file,
+                "{},{},{},{},{}",
+                day,
+                stats.total_seconds(),
+                stats.images_viewed,
+                stats.pomodoros,
+                stats.overtime_seconds,
+            )?;
+        }
+        file.flush()
+    }
+}
+
+/// Splits an interval at local midnights and returns the whole seconds that fall on
+/// each day, carrying sub-second remainders over in `pending`.
+fn split_by_day(pending: &mut Duration, delta: Duration, ended_ago: Duration) -> Vec<(NaiveDate, u64)> {
+    let mut days = Vec::new();
+    if delta.is_zero() {
+        return days;
+    }
+
+    let end = Local::now() - chrono::Duration::from_std(ended_ago).unwrap_or_else(|_| chrono::Duration::zero());
+    let start = end - chrono::Duration::from_std(delta).unwrap_or_else(|_| chrono::Duration::zero());
+    let mut cursor = start;
+
+    while cursor < end {
+        let day = cursor.date_naive();
+        let segment_end = next_local_midnight(day).map_or(end, |midnight| midnight.min(end));
+        let segment = (segment_end - cursor).to_std().unwrap_or_default();
+
+        *pending += segment;
+        let whole = pending.as_secs();
+        if whole > 0 {
+            *pending -= Duration::from_secs(whole);
+            days.push((day, whole));
+        }
+
+        if segment_end <= cursor {
+            break;
+        }
+        cursor = segment_end;
+    }
+    days
+}
+
+pub fn today() -> NaiveDate {
+    Local::now().date_naive()
+}
+
+/// Whether a unix timestamp falls on the current local day.
+pub fn is_today(unix_secs: u64) -> bool {
+    Local.timestamp_opt(unix_secs as i64, 0).single().map(|t| t.date_naive()) == Some(today())
+}
+
+/// The first instant of the day after `day` in local time. On DST transitions where
+/// midnight itself doesn't exist, the earliest valid time after it is used.
+fn next_local_midnight(day: NaiveDate) -> Option<DateTime<Local>> {
+    let next = day.succ_opt()?;
+    (0..4).find_map(|hour| Local.from_local_datetime(&next.and_hms_opt(hour, 0, 0)?).earliest())
+}
+
+pub fn format_hours(total_secs: u64) -> String {
+    let hours = total_secs / 3600;
+    let minutes = (total_secs % 3600) / 60;
+    if hours > 0 {
+        format!("{}h {:02}m", hours, minutes)
+    } else {
+        format!("{}m {:02}s", minutes, total_secs % 60)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn empty_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("timerdrawing-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn damaged_file_is_kept_before_starting_over() {
+        let dir = empty_dir("stats");
+        let path = dir.join("stats.json");
+        std::fs::write(&path, "{\"days\": {").unwrap();
+
+        let mut store = StatsStore::load(&path);
+        assert!(matches!(store.take_damaged(), Some(Damaged::BackedUp(..))));
+        assert!(store.take_damaged().is_none());
+        assert_eq!(std::fs::read_to_string(settings_file::backup_path(&path)).unwrap(), "{\"days\": {");
+        store.record_pomodoro();
+        store.flush().unwrap();
+        assert_eq!(StatsStore::load(&path).day(today()).unwrap().pomodoros, 1);
+    }
+
+    #[test]
+    fn new_database_takes_over_the_file() {
+        let dir = empty_dir("stats-migrate");
+        let mut store = StatsStore::load(dir.join("stats.json"));
+        store.record_pomodoro();
+        store.flush().unwrap();
+
+        store.open_database(dir.join("stats.sqlite")).unwrap();
+        assert!(store.uses_database());
+        assert_eq!(store.path(), dir.join("stats.sqlite"));
+        store.record_pomodoro();
+        store.flush().unwrap();
+
+        // From here on the database is read, not the file, which stays as it was.
+        let mut reopened = StatsStore::load(dir.join("other.json"));
+        reopened.open_database(dir.join("stats.sqlite")).unwrap();
+        assert_eq!(reopened.day(today()).unwrap().pomodoros, 2);
+        assert_eq!(StatsStore::load(dir.join("stats.json")).day(today()).unwrap().pomodoros, 1);
+
+        // Moving back writes the database's days to the file.
+        reopened.set_database(None).unwrap();
+        assert_eq!(StatsStore::load(dir.join("other.json")).day(today()).unwrap().pomodoros, 2);
+    }
+
+    #[test]
+    fn prune_removes_days_past_the_retention() {
+        let dir = empty_dir("stats-prune");
+        let mut store = StatsStore::load(dir.join("stats.json"));
+        store.open_database(dir.join("stats.sqlite")).unwrap();
+        let days = [40, 31, 30, 1].map(|ago| today().checked_sub_days(Days::new(ago)).unwrap());
+        for day in days {
+            store.data.days.insert(day, DayStats { pomodoros: 1, ..Default::default() });
+            store.dirty_days.insert(day);
+        }
+        store.flush().unwrap();
+
+        assert_eq!(store.prune(30), Ok(2));
+        assert_eq!(store.prune(30), Ok(0));
+        let mut reopened = StatsStore::load(dir.join("stats.json"));
+        reopened.open_database(dir.join("stats.sqlite")).unwrap();
+        for (day, kept) in days.into_iter().zip([false, false, true, true]) {
+            assert_eq!(reopened.day(day).is_some(), kept, "{}", day);
+        }
+    }
+}
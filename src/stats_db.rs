@@ -0,0 +1,215 @@
+//! Statistics kept in SQLite, written a day at a time instead of rewriting the
+//! whole history on every flush.
+
+use chrono::{Local, NaiveDate, TimeZone};
+use rusqlite::{params, Connection, Transaction};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use crate::{session::ImageView, stats::DayStats};
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS days (
+        date TEXT PRIMARY KEY,
+        pomodoros INTEGER NOT NULL,
+        overtime_seconds INTEGER NOT NULL,
+        images_viewed INTEGER NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS day_exes (
+        date TEXT NOT NULL,
+        exe TEXT NOT NULL,
+        seconds INTEGER NOT NULL,
+        PRIMARY KEY (date, exe)
+    );
+    CREATE TABLE IF NOT EXISTS day_documents (
+        date TEXT NOT NULL,
+        document TEXT NOT NULL,
+        seconds INTEGER NOT NULL,
+        PRIMARY KEY (date, document)
+    );
+    CREATE TABLE IF NOT EXISTS sessions (
+        id INTEGER PRIMARY KEY,
+        started_at INTEGER NOT NULL,
+        ended_at INTEGER NOT NULL,
+        tracked_seconds INTEGER NOT NULL,
+        images_viewed INTEGER NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS image_views (
+        id INTEGER PRIMARY KEY,
+        path TEXT NOT NULL,
+        started_at INTEGER NOT NULL,
+        shown_seconds REAL NOT NULL,
+        tracked_seconds REAL NOT NULL,
+        alarms_fired INTEGER NOT NULL,
+        overtime_seconds REAL NOT NULL,
+        adjusted_seconds INTEGER NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS image_views_started_at ON image_views (started_at);
+    CREATE INDEX IF NOT EXISTS sessions_started_at ON sessions (started_at);
+";
+
+/// How often an image was shown and for how long in total.
+pub struct ViewCount {
+    pub path: PathBuf,
+    pub views: u32,
+    pub shown_seconds: f64,
+}
+
+pub struct Database {
+    connection: Connection,
+}
+
+impl Database {
+    pub fn open(path: &Path) -> rusqlite::Result<Self> {
+        let connection = Connection::open(path)?;
+        connection.execute_batch(SCHEMA)?;
+        Ok(Self { connection })
+    }
+
+    pub fn has_days(&self) -> rusqlite::Result<bool> {
+        self.connection.query_row("SELECT EXISTS (SELECT 1 FROM days)", [], |row| row.get(0))
+    }
+
+    pub fn read_days(&self) -> rusqlite::Result<BTreeMap<NaiveDate, DayStats>> {
+        let mut days: BTreeMap<NaiveDate, DayStats> = BTreeMap::new();
+
+        let mut statement = self.connection.prepare("SELECT date, pomodoros, overtime_seconds, images_viewed FROM days")?;
+        let rows = statement.query_map([], |row| {
+            Ok((row.get::<_, NaiveDate>(0)?, row.get(1)?, row.get::<_, i64>(2)?, row.get(3)?))
+        })?;
+        for row in rows {
+            let (date, pomodoros, overtime_seconds, images_viewed) = row?;
+            let day = days.entry(date).or_default();
+            day.pomodoros = pomodoros;
+            day.overtime_seconds = overtime_seconds as u64;
+            day.images_viewed = images_viewed;
+        }
+
+        let mut statement = self.connection.prepare("SELECT date, exe, seconds FROM day_exes")?;
+        let rows = statement.query_map([], |row| Ok((row.get::<_, NaiveDate>(0)?, row.get(1)?, row.get::<_, i64>(2)?)))?;
+        for row in rows {
+            let (date, exe, seconds) = row?;
+            days.entry(date).or_default().exe_seconds.insert(exe, seconds as u64);
+        }
+
+        let mut statement = self.connection.prepare("SELECT date, document, seconds FROM day_documents")?;
+        let rows = statement.query_map([], |row| Ok((row.get::<_, NaiveDate>(0)?, row.get(1)?, row.get::<_, i64>(2)?)))?;
+        for row in rows {
+            let (date, document, seconds) = row?;
+            days.entry(date).or_default().document_seconds.insert(document, seconds as u64);
+        }
+
+        Ok(days)
+    }
+
+    /// Replaces what's stored for each of `dates` with its entry in `days`, or
+    /// removes the date if it has none.
+    pub fn write_days<'a>(
+        &mut self,
+        days: &BTreeMap<NaiveDate, DayStats>,
+        dates: impl IntoIterator<Item = &'a NaiveDate>,
+    ) -> rusqlite::Result<()> {
+        let transaction = self.connection.transaction()?;
+        for date in dates {
+            delete_day(&transaction, date)?;
+            let Some(day) = days.get(date) else { continue };
+            transaction.execute(
+                "INSERT INTO days (date, pomodoros, overtime_seconds, images_viewed) VALUES (?1, ?2, ?3, ?4)",
+                params![date, day.pomodoros, day.overtime_seconds as i64, day.images_viewed],
+            )?;
+            for (exe, seconds) in &day.exe_seconds {
+                transaction.execute(
+                    "INSERT INTO day_exes (date, exe, seconds) VALUES (?1, ?2, ?3)",
+                    params![date, exe, *seconds as i64],
+                )?;
+            }
+            for (document, seconds) in &day.document_seconds {
+                transaction.execute(
+                    "INSERT INTO day_documents (date, document, seconds) VALUES (?1, ?2, ?3)",
+                    params![date, document, *seconds as i64],
+                )?;
+            }
+        }
+        transaction.commit()
+    }
+
+    pub fn insert_views(&mut self, views: &[ImageView]) -> rusqlite::Result<()> {
+        let transaction = self.connection.transaction()?;
+        for view in views {
+            transaction.execute(
+                "INSERT INTO image_views (path, started_at, shown_seconds, tracked_seconds, alarms_fired, overtime_seconds, adjusted_seconds)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    view.path.to_string_lossy(),
+                    view.started_at as i64,
+                    view.shown_for.as_secs_f64(),
+                    view.tracked.as_secs_f64(),
+                    view.alarms_fired as i64,
+                    view.overtime.as_secs_f64(),
+                    view.adjusted_seconds,
+                ],
+            )?;
+        }
+        transaction.commit()
+    }
+
+    pub fn insert_session(&self, started_at: u64, ended_at: u64, tracked_seconds: u64, images_viewed: usize) -> rusqlite::Result<()> {
+        self.connection.execute(
+            "INSERT INTO sessions (started_at, ended_at, tracked_seconds, images_viewed) VALUES (?1, ?2, ?3, ?4)",
+            params![started_at as i64, ended_at as i64, tracked_seconds as i64, images_viewed as i64],
+        )?;
+        Ok(())
+    }
+
+    /// The images shown most often since the start of `since`.
+    pub fn most_viewed(&self, since: NaiveDate, limit: usize) -> rusqlite::Result<Vec<ViewCount>> {
+        let mut statement = self.connection.prepare(
+            "SELECT path, COUNT(*), SUM(shown_seconds) FROM image_views WHERE started_at >= ?1
+             GROUP BY path ORDER BY COUNT(*) DESC, SUM(shown_seconds) DESC LIMIT ?2",
+        )?;
+        let rows = statement.query_map(params![start_of(since), limit as i64], |row| {
+            Ok(ViewCount {
+                path: PathBuf::from(row.get::<_, String>(0)?),
+                views: row.get(1)?,
+                shown_seconds: row.get(2)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    /// Deletes sessions and image views from before `before`, then compacts the
+    /// file. Days are removed through `write_days`.
+    pub fn prune(&self, before: NaiveDate) -> rusqlite::Result<()> {
+        let cutoff = start_of(before);
+        self.connection.execute("DELETE FROM image_views WHERE started_at < ?1", [cutoff])?;
+        self.connection.execute("DELETE FROM sessions WHERE started_at < ?1", [cutoff])?;
+        self.connection.execute_batch("VACUUM")
+    }
+
+    /// Deletes every image view, which lists the paths of the images shown.
+    pub fn clear_views(&self) -> rusqlite::Result<()> {
+        self.connection.execute_batch("DELETE FROM image_views; VACUUM;")
+    }
+
+    pub fn clear(&self) -> rusqlite::Result<()> {
+        self.connection.execute_batch(
+            "DELETE FROM days; DELETE FROM day_exes; DELETE FROM day_documents; DELETE FROM sessions; DELETE FROM image_views; VACUUM;",
+        )
+    }
+}
+
+fn delete_day(transaction: &Transaction, date: &NaiveDate) -> rusqlite::Result<()> {
+    transaction.execute("DELETE FROM days WHERE date = ?1", [date])?;
+    transaction.execute("DELETE FROM day_exes WHERE date = ?1", [date])?;
+    transaction.execute("DELETE FROM day_documents WHERE date = ?1", [date])?;
+    Ok(())
+}
+
+/// The unix time of local midnight at the start of `day`.
+fn start_of(day: NaiveDate) -> i64 {
+    day.and_hms_opt(0, 0, 0)
+        .and_then(|midnight| Local.from_local_datetime(&midnight).earliest())
+        .map_or(0, |t| t.timestamp())
+}
@@ -0,0 +1,62 @@
+//! The look of the viewer's own windows and menus: dark, light or following the
+//! system, optionally tinted with an accent color.
+
+use eframe::egui::{Color32, Stroke, Visuals};
+use serde::{Deserialize, Serialize};
+
+use crate::i18n::tr;
+
+#[derive(Serialize, Deserialize, Default, Clone, Copy, PartialEq)]
+pub enum Theme {
+    #[default]
+    Dark,
+    Light,
+    /// Whatever Windows is set to for apps, checked as it changes.
+    System,
+}
+
+impl Theme {
+    pub const ALL: [Theme; 3] = [Self::Dark, Self::Light, Self::System];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Dark => tr("Dark"),
+            Self::Light => tr("Light"),
+            Self::System => tr("Follow System"),
+        }
+    }
+
+    /// Whether it comes out dark, given the system's theme if that's known.
+    pub fn is_dark(self, system: Option<eframe::Theme>) -> bool {
+        match self {
+            Self::Dark => true,
+            Self::Light => false,
+            Self::System => system != Some(eframe::Theme::Light),
+        }
+    }
+}
+
+/// egui's dark or light visuals, with `accent` in place of the blue used for
+/// selections, links, the text cursor and widgets being hovered or pressed.
+pub fn visuals(dark: bool, accent: Option<[u8; 3]>) -> Visuals {
+    let mut visuals = if dark { Visuals::dark() } else { Visuals::light() };
+    if let Some([r, g, b]) = accent {
+        let accent = Color32::from_rgb(r, g, b);
+        let on_accent = if is_light(accent) { Color32::BLACK } else { Color32::WHITE };
+        visuals.selection.bg_fill = accent;
+        visuals.selection.stroke = Stroke::new(visuals.selection.stroke.width, on_accent);
+        visuals.hyperlink_color = accent;
+        visuals.text_cursor.color = accent;
+        visuals.widgets.hovered.bg_stroke.color = accent;
+        visuals.widgets.active.bg_stroke.color = accent;
+        visuals.widgets.active.bg_fill = accent;
+        visuals.widgets.active.weak_bg_fill = accent;
+        visuals.widgets.active.fg_stroke.color = on_accent;
+    }
+    visuals
+}
+
+/// Whether dark text reads better on `color` than light text.
+pub fn is_light(color: Color32) -> bool {
+    color.r() as u32 * 299 + color.g() as u32 * 587 + color.b() as u32 * 114 > 128_000
+}
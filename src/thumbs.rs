@@ -0,0 +1,222 @@
+//! Small previews of the images for the browser. They're made on a worker thread,
+//! only for the rows in view, and kept on disk so reopening the browser is quick,
+//! unless the folders are private.
+
+use eframe::egui;
+use image::ImageFormat;
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    hash::{Hash, Hasher},
+    path::{Path, PathBuf},
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        Arc, Condvar, Mutex,
+    },
+    thread,
+    time::SystemTime,
+};
+
+/// The longer side of a thumbnail, in pixels.
+pub const SIZE: u32 = 160;
+
+/// Past this many loaded thumbnails, those out of view are let go.
+const MAX_LOADED: usize = 600;
+
+/// Past this many bytes on disk, the oldest cached thumbnails are deleted.
+const MAX_CACHED_BYTES: u64 = 200 * 1024 * 1024;
+
+/// `None` once the thumbnails are dropped, which stops the worker.
+type Queue = Arc<(Mutex<Option<Vec<PathBuf>>>, Condvar)>;
+
+pub enum Thumb {
+    Ready(egui::TextureId, egui::Vec2),
+    Loading,
+    Failed,
+}
+
+pub struct Thumbnails {
+    /// What the worker is still to make, most wanted last.
+    queue: Queue,
+    results: Receiver<(PathBuf, Option<egui::ColorImage>)>,
+    /// `None` for images that couldn't be read.
+    loaded: HashMap<PathBuf, Option<egui::TextureHandle>>,
+    /// Asked for this frame, which is what the worker gets next.
+    wanted: Vec<PathBuf>,
+    seen: HashSet<PathBuf>,
+}
+
+impl Thumbnails {
+    /// Makes thumbnails on a new worker thread, caching them in `cache_dir` if
+    /// there is one.
+    pub fn new(ctx: egui::Context, cache_dir: Option<PathBuf>) -> Self {
+        let queue: Queue = Arc::new((Mutex::new(Some(Vec::new())), Condvar::new()));
+        let (tx, results) = mpsc::channel();
+        let worker_queue = queue.clone();
+        thread::spawn(move || work(worker_queue, tx, ctx, cache_dir));
+        Self {
+            queue,
+            results,
+            loaded: HashMap::new(),
+            wanted: Vec::new(),
+            seen: HashSet::new(),
+        }
+    }
+
+    /// Takes in what the worker has finished. Call once a frame before `get`.
+    pub fn receive(&mut self, ctx: &egui::Context) {
+        for (path, image) in self.results.try_iter() {
+            let texture = image.map(|image| ctx.load_texture(path.to_string_lossy(), image, Default::default()));
+            self.loaded.insert(path, texture);
+        }
+    }
+
+    /// The thumbnail of `path`, which is asked for if it isn't there yet.
+    pub fn get(&mut self, path: &Path) -> Thumb {
+        self.seen.insert(path.to_path_buf());
+        match self.loaded.get(path) {
+            Some(Some(texture)) => Thumb::Ready(texture.id(), texture.size_vec2()),
+            Some(None) => Thumb::Failed,
+            None => {
+                self.wanted.push(path.to_path_buf());
+                Thumb::Loading
+            }
+        }
+    }
+
+    /// Hands the worker what this frame asked for, dropping what scrolled out of
+    /// view, and lets go of thumbnails out of view once there are many.
+    pub fn finish_frame(&mut self) {
+        let (queue, ready) = &*self.queue;
+        if let Ok(mut queue) = queue.lock() {
+            *queue = Some(self.wanted.drain(..).rev().collect());
+            ready.notify_one();
+        }
+        if self.loaded.len() > MAX_LOADED {
+            self.loaded.retain(|path, _| self.seen.contains(path));
+        }
+        self.seen.clear();
+    }
+}
+
+impl Drop for Thumbnails {
+    fn drop(&mut self) {
+        let (queue, ready) = &*self.queue;
+        if let Ok(mut queue) = queue.lock() {
+            *queue = None;
+            ready.notify_one();
+        }
+    }
+}
+
+/// Deletes the cached thumbnails, which show every image the browser was opened on.
+pub fn forget(cache_dir: &Path) {
+    let _ = fs::remove_dir_all(cache_dir);
+}
+
+/// Deletes the oldest thumbnails in `cache_dir` until they take up no more than
+/// `max_bytes`.
+fn trim_cache(cache_dir: &Path, max_bytes: u64) {
+    let Ok(entries) = fs::read_dir(cache_dir) else { return };
+    let mut cached: Vec<(SystemTime, u64, PathBuf)> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok()?;
+            Some((metadata.modified().ok()?, metadata.len(), entry.path()))
+        })
+        .collect();
+    let mut total: u64 = cached.iter().map(|(_, len, _)| len).sum();
+    cached.sort();
+    for (_, len, path) in cached {
+        if total <= max_bytes {
+            break;
+        }
+        if let Err(e) = fs::remove_file(&path) {
+            log::warn!("Failed to delete cached thumbnail {}: {}", path.display(), e);
+        }
+        total -= len;
+    }
+}
+
+fn work(queue: Queue, tx: Sender<(PathBuf, Option<egui::ColorImage>)>, ctx: egui::Context, cache_dir: Option<PathBuf>) {
+    if let Some(cache_dir) = &cache_dir {
+        trim_cache(cache_dir, MAX_CACHED_BYTES);
+    }
+    let (queue, ready) = &*queue;
+    loop {
+        let path = {
+            let Ok(mut queue) = queue.lock() else { return };
+            loop {
+                match queue.as_mut().map(Vec::pop) {
+                    Some(Some(path)) => break path,
+                    Some(None) => {}
+                    None => return,
+                }
+                let Ok(next) = ready.wait(queue) else { return };
+                queue = next;
+            }
+        };
+
+        let image = load(&path, cache_dir.as_deref()).map(|thumb| {
+            let rgba = thumb.to_rgba8();
+            egui::ColorImage::from_rgba_unmultiplied([thumb.width() as usize, thumb.height() as usize], rgba.as_raw())
+        });
+        if tx.send((path, image)).is_err() {
+            return;
+        }
+        ctx.request_repaint();
+    }
+}
+
+/// Reads the cached thumbnail of `path`, or makes it and caches it in `cache_dir`.
+fn load(path: &Path, cache_dir: Option<&Path>) -> Option<image::DynamicImage> {
+    let Some(cache_dir) = cache_dir else {
+        return Some(crate::open_upright(path).ok()?.thumbnail(SIZE, SIZE));
+    };
+    let cached = cache_dir.join(format!("{:016x}.jpg", cache_key(path)?));
+    if let Ok(thumb) = image::open(&cached) {
+        return Some(thumb);
+    }
+
+    let thumb = crate::open_upright(path).ok()?.thumbnail(SIZE, SIZE);
+    if fs::create_dir_all(cache_dir).is_ok() {
+        if let Err(e) = thumb.to_rgb8().save_with_format(&cached, ImageFormat::Jpeg) {
+            log::warn!("Failed to cache thumbnail of {}: {}", path.display(), e);
+        }
+    }
+    Some(thumb)
+}
+
+/// Names the cached thumbnail after the image's path and when it was last
+/// changed, so an edited image gets a new one.
+fn cache_key(path: &Path) -> Option<u64> {
+    let modified = fs::metadata(path).ok()?.modified().ok()?;
+    let since_epoch = modified.duration_since(SystemTime::UNIX_EPOCH).unwrap_or_default();
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    path.hash(&mut hasher);
+    since_epoch.as_nanos().hash(&mut hasher);
+    Some(hasher.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trimming_deletes_the_oldest_thumbnails() {
+        let dir = std::env::temp_dir().join(format!("timerdrawing-thumbs-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        for name in ["old", "middle", "new"] {
+            fs::write(dir.join(name), [0; 10]).unwrap();
+            // Apart far enough for any file system's timestamps.
+            thread::sleep(std::time::Duration::from_millis(20));
+        }
+
+        trim_cache(&dir, 30);
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 3);
+        trim_cache(&dir, 25);
+        assert!(!dir.join("old").exists());
+        assert!(dir.join("middle").exists() && dir.join("new").exists());
+    }
+}
@@ -0,0 +1,118 @@
+use eframe::egui;
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, Sender},
+        Arc,
+    },
+    thread,
+};
+
+#[cfg(windows)]
+use windows::{
+    core::{IInspectable, HSTRING},
+    Data::Xml::Dom::XmlDocument,
+    Foundation::TypedEventHandler,
+    UI::Notifications::{NotificationSetting, ToastNotification, ToastNotificationManager, ToastNotifier},
+    Win32::System::Com::{CoInitializeEx, COINIT_MULTITHREADED},
+};
+
+/// Unpackaged apps have no AppUserModelID of their own, so toasts are shown under
+/// PowerShell's, which every Windows install has registered.
+#[cfg(windows)]
+const APP_ID: &str = r"{1AC14E77-02E7-4E5D-B744-2EB1AE5198B7}\WindowsPowerShell\v1.0\powershell.exe";
+
+/// Shows Windows toast notifications from a dedicated thread. If toasts are turned
+/// off or unavailable, they are silently dropped.
+pub struct Toaster {
+    tx: Sender<(String, String)>,
+    activated: Arc<AtomicBool>,
+}
+
+impl Toaster {
+    pub fn new(ctx: egui::Context) -> Self {
+        let (tx, rx) = mpsc::channel::<(String, String)>();
+        let activated = Arc::new(AtomicBool::new(false));
+        let flag = Arc::clone(&activated);
+
+        thread::spawn(move || run(rx, flag, ctx));
+        Self { tx, activated }
+    }
+
+    pub fn show(&self, title: String, body: String) {
+        let _ = self.tx.send((title, body));
+    }
+
+    /// Whether a toast was clicked since the last call.
+    pub fn take_activation(&self) -> bool {
+        self.activated.swap(false, Ordering::Relaxed)
+    }
+}
+
+#[cfg(windows)]
+fn run(rx: Receiver<(String, String)>, flag: Arc<AtomicBool>, ctx: egui::Context) {
+    let _ = unsafe { CoInitializeEx(None, COINIT_MULTITHREADED) };
+    let notifier = ToastNotificationManager::CreateToastNotifierWithId(&HSTRING::from(APP_ID)).ok();
+    if notifier.is_none() {
+        log::warn!("Toast notifications unavailable");
+    }
+
+    // The latest toast is kept alive so its activation handler stays registered.
+    let mut _shown = None;
+    for (title, body) in rx {
+        let Some(notifier) = &notifier else { continue };
+        if notifier.Setting().ok() != Some(NotificationSetting::Enabled) {
+            log::info!("Toast notifications are turned off");
+            continue;
+        }
+        match show(notifier, &title, &body, &flag, &ctx) {
+            Ok(toast) => _shown = Some(toast),
+            Err(e) => log::warn!("Failed to show toast: {}", e),
+        }
+    }
+}
+
+#[cfg(not(windows))]
+fn run(rx: Receiver<(String, String)>, _flag: Arc<AtomicBool>, _ctx: egui::Context) {
+    log::warn!("Toast notifications unavailable");
+    for _ in rx {}
+}
+
+#[cfg(windows)]
+fn show(
+    notifier: &ToastNotifier,
+    title: &str,
+    body: &str,
+    flag: &Arc<AtomicBool>,
+    ctx: &egui::Context,
+) -> windows::core::Result<ToastNotification> {
+    let xml = format!(
+        "<toast activationType=\"foreground\"><visual><binding template=\"ToastGeneric\">\
+         <text>{}</text><text>{}</text></binding></visual><actions>\
+         <action content=\"Show Viewer\" arguments=\"show\" activationType=\"foreground\"/>\
+         </actions></toast>",
+        escape_xml(title),
+        escape_xml(body)
+    );
+    let doc = XmlDocument::new()?;
+    doc.LoadXml(&HSTRING::from(xml))?;
+
+    let toast = ToastNotification::CreateToastNotification(&doc)?;
+    let flag = Arc::clone(flag);
+    let ctx = ctx.clone();
+    toast.Activated(&TypedEventHandler::<ToastNotification, IInspectable>::new(move |_, _| {
+        flag.store(true, Ordering::Relaxed);
+        ctx.request_repaint();
+        Ok(())
+    }))?;
+    notifier.Show(&toast)?;
+    Ok(toast)
+}
+
+#[cfg(windows)]
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,260 @@
+use eframe::egui;
+use std::{
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::probe::{self, Window, WindowProbe};
+
+/// What the poller last saw of the target and the user.
+#[derive(Clone, Default, PartialEq)]
+pub struct TrackState {
+    pub foreground_exe: Option<String>,
+    pub target_active: bool,
+    pub target_hovered: bool,
+    pub idle: bool,
+    /// Some app other than the target, and not on the exclusion list, is fullscreen.
+    pub other_fullscreen: bool,
+    /// The document open in the target, while it has focus.
+    pub document: Option<String>,
+}
+
+/// Narrows the target down to windows whose title matches, for apps like browsers
+/// where the exe name alone says little.
+#[derive(Clone)]
+pub enum TitleFilter {
+    /// Case-insensitive, stored lowercased.
+    Contains(String),
+    Regex(regex::Regex),
+}
+
+impl TitleFilter {
+    /// Builds a filter from the settings; an empty pattern means no filter.
+    pub fn new(pattern: &str, is_regex: bool) -> Result<Option<Self>, String> {
+        if pattern.is_empty() {
+            return Ok(None);
+        }
+        if is_regex {
+            regex::Regex::new(pattern).map(|re| Some(TitleFilter::Regex(re))).map_err(|e| e.to_string())
+        } else {
+            Ok(Some(TitleFilter::Contains(pattern.to_lowercase())))
+        }
+    }
+
+    fn matches(&self, title: &str) -> bool {
+        match self {
+            TitleFilter::Contains(needle) => title.to_lowercase().contains(needle),
+            TitleFilter::Regex(re) => re.is_match(title),
+        }
+    }
+}
+
+/// Pulls the document name out of the target's window title, such as
+/// "portrait_study.kra" from "*portrait_study.kra - Krita".
+#[derive(Clone)]
+pub struct DocumentPattern(regex::Regex);
+
+impl DocumentPattern {
+    /// Everything before the last " - ", which is where most apps put their name.
+    pub const DEFAULT: &'static str = r"^(.+) - .+$";
+
+    /// The document is the pattern's first capture group, or the whole match if it has none.
+    pub fn new(pattern: &str) -> Result<Self, String> {
+        regex::Regex::new(pattern).map(DocumentPattern).map_err(|e| e.to_string())
+    }
+
+    /// Titles the pattern doesn't match are taken whole. Unsaved-changes markers
+    /// are dropped, so editing a document doesn't start a new entry.
+    fn extract(&self, title: &str) -> Option<String> {
+        let document = match self.0.captures(title) {
+            Some(captures) => captures.get(1).or_else(|| captures.get(0)).map_or(title, |m| m.as_str()),
+            None => title,
+        };
+        let document = document.trim_matches(|c: char| c.is_whitespace() || matches!(c, '*' | '•' | '●'));
+        (!document.is_empty()).then(|| document.to_string())
+    }
+}
+
+/// The settings the poller needs from the app.
+#[derive(Clone, Default)]
+pub struct TrackConfig {
+    pub target: Option<String>,
+    pub title_filter: Option<TitleFilter>,
+    /// Also count windows of processes started by the target, such as helper or
+    /// document processes with their own exe name.
+    pub include_child_processes: bool,
+    /// Whether to look up the foreground exe even without a target.
+    pub watch_foreground: bool,
+    pub idle_threshold: Option<Duration>,
+    /// How long the poller sleeps between checks.
+    pub interval: Duration,
+    /// Exes allowed to go fullscreen without counting, or `None` to not watch for
+    /// fullscreen apps at all.
+    pub fullscreen_exclusions: Option<Vec<String>>,
+    /// Set to read the document name from the title while the target has focus.
+    pub document_pattern: Option<DocumentPattern>,
+}
+
+#[derive(Default)]
+struct Shared {
+    config: TrackConfig,
+    /// State changes since the last drain, oldest first.
+    transitions: Vec<(Instant, TrackState)>,
+    /// When the next alarm is due if the state holds, to wake the UI for it.
+    wake_at: Option<Instant>,
+}
+
+/// Polls the foreground window, cursor and idle time on its own thread, so the
+/// tracked time stays right even while the viewer is minimized and egui stops
+/// calling `update`. The UI replays the timestamped changes when it next runs.
+pub struct Tracker {
+    shared: Arc<Mutex<Shared>>,
+}
+
+impl Tracker {
+    pub fn spawn(ctx: egui::Context) -> Self {
+        let shared = Arc::new(Mutex::new(Shared::default()));
+        let poll_shared = Arc::clone(&shared);
+
+        thread::spawn(move || {
+            let mut probe = probe::new();
+            let mut last = TrackState::default();
+            loop {
+                let (config, wake) = match poll_shared.lock() {
+                    Ok(mut shared) => {
+                        let due = shared.wake_at.is_some_and(|at| at <= Instant::now());
+                        if due {
+                            shared.wake_at = None;
+                        }
+                        (shared.config.clone(), due)
+                    }
+                    Err(_) => return,
+                };
+                // The state may never change while an alarm comes due, and egui
+                // might not run `update` on its own until it does.
+                if wake {
+                    ctx.request_repaint();
+                }
+
+                let state = poll(&config, probe.as_mut());
+                if state != last {
+                    if let Ok(mut shared) = poll_shared.lock() {
+                        shared.transitions.push((Instant::now(), state.clone()));
+                    }
+                    last = state;
+                    ctx.request_repaint();
+                }
+
+                thread::sleep(config.interval.max(Duration::from_millis(10)));
+            }
+        });
+
+        Self { shared }
+    }
+
+    pub fn configure(&self, config: TrackConfig) {
+        if let Ok(mut shared) = self.shared.lock() {
+            shared.config = config;
+        }
+    }
+
+    /// Wakes the UI at `at`, for an alarm due then, replacing any earlier request.
+    pub fn wake_at(&self, at: Option<Instant>) {
+        if let Ok(mut shared) = self.shared.lock() {
+            shared.wake_at = at;
+        }
+    }
+
+    /// Takes the state changes recorded since the last call.
+    pub fn drain(&self) -> Vec<(Instant, TrackState)> {
+        self.shared
+            .lock()
+            .map(|mut shared| std::mem::take(&mut shared.transitions))
+            .unwrap_or_default()
+    }
+}
+
+/// Splits the time from `since` to `now` at each of the tracker's `transitions`,
+/// oldest first. `step` gets each stretch's length, how long before `now` it
+/// ended, and the state that took effect at its end, if it's followed by one.
+pub fn replay(since: Instant, now: Instant, transitions: Vec<(Instant, TrackState)>, mut step: impl FnMut(Duration, Duration, Option<TrackState>)) {
+    let mut last = since;
+    for (at, state) in transitions {
+        let at = at.clamp(last, now);
+        step(at.duration_since(last), now.duration_since(at), Some(state));
+        last = at;
+    }
+    step(now.duration_since(last), Duration::ZERO, None);
+}
+
+fn poll(config: &TrackConfig, probe: &mut dyn WindowProbe) -> TrackState {
+    let mut state = TrackState::default();
+
+    if config.target.is_some() || config.watch_foreground {
+        state.foreground_exe = probe.foreground_exe();
+        if state.foreground_exe != config.target && is_child_of_target(config, probe, Window::Foreground) {
+            // Credit the time to the target rather than to its helper process.
+            state.foreground_exe = config.target.clone();
+        }
+        state.target_active = config.target.is_some()
+            && state.foreground_exe == config.target
+            && title_matches(config, probe, Window::Foreground);
+        if let Some(pattern) = config.document_pattern.as_ref().filter(|_| state.target_active) {
+            state.document = probe.window_title(Window::Foreground).and_then(|title| pattern.extract(&title));
+        }
+    }
+
+    if config.target.is_some() {
+        let exe_matches =
+            probe.exe_under_cursor() == config.target || is_child_of_target(config, probe, Window::UnderCursor);
+        state.target_hovered = exe_matches && title_matches(config, probe, Window::UnderCursor);
+    }
+
+    state.idle = config.idle_threshold.is_some_and(|threshold| probe.idle_time() >= threshold);
+
+    if let Some(exclusions) = &config.fullscreen_exclusions {
+        if probe.foreground_is_fullscreen() {
+            let exe = match &state.foreground_exe {
+                Some(exe) => Some(exe.clone()),
+                None => probe.foreground_exe(),
+            };
+            state.other_fullscreen = exe.is_none_or(|exe| config.target.as_ref() != Some(&exe) && !exclusions.contains(&exe));
+        }
+    }
+    state
+}
+
+fn is_child_of_target(config: &TrackConfig, probe: &mut dyn WindowProbe, window: Window) -> bool {
+    match &config.target {
+        Some(target) if config.include_child_processes => probe.ancestor_exes(window).contains(target),
+        _ => false,
+    }
+}
+
+fn title_matches(config: &TrackConfig, probe: &mut dyn WindowProbe, window: Window) -> bool {
+    match &config.title_filter {
+        Some(filter) => probe.window_title(window).is_some_and(|title| filter.matches(&title)),
+        None => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn documents_from_titles() {
+        let default = DocumentPattern::new(DocumentPattern::DEFAULT).unwrap();
+        assert_eq!(default.extract("*portrait_study.kra - Krita").as_deref(), Some("portrait_study.kra"));
+        // Titles the pattern doesn't match are taken whole.
+        assert_eq!(default.extract("Krita").as_deref(), Some("Krita"));
+        assert_eq!(default.extract(" * ").as_deref(), None);
+
+        // Without a group, the whole match is the document.
+        let no_group = DocumentPattern::new(r"\w+\.psd").unwrap();
+        assert_eq!(no_group.extract("Adobe Photoshop - hands.psd @ 50%").as_deref(), Some("hands.psd"));
+
+        assert!(DocumentPattern::new("(unclosed").is_err());
+    }
+}
@@ -0,0 +1,321 @@
+use eframe::egui;
+
+#[cfg(windows)]
+use std::{
+    cell::RefCell,
+    sync::{
+        atomic::{AtomicIsize, Ordering},
+        mpsc::{Receiver, Sender},
+        Arc, Mutex,
+    },
+    thread,
+};
+
+#[cfg(windows)]
+use windows::{
+    core::{w, HSTRING},
+    Win32::Foundation::{HWND, LPARAM, LRESULT, POINT, WPARAM},
+    Win32::Graphics::Gdi::{CreateBitmap, DeleteObject},
+    Win32::System::LibraryLoader::GetModuleHandleW,
+    Win32::UI::Shell::{
+        Shell_NotifyIconW, NIF_ICON, NIF_MESSAGE, NIF_TIP, NIM_ADD, NIM_DELETE, NIM_MODIFY, NOTIFYICONDATAW,
+        NOTIFY_ICON_MESSAGE,
+    },
+    Win32::UI::WindowsAndMessaging::{
+        AppendMenuW, CreateIconIndirect, CreatePopupMenu, CreateWindowExW, DefWindowProcW, DestroyMenu,
+        DispatchMessageW, GetCursorPos, GetMessageW, PostMessageW, RegisterClassW, RegisterWindowMessageW,
+        SetForegroundWindow, TrackPopupMenu, HICON, ICONINFO, MF_SEPARATOR, MF_STRING, MSG, TPM_NONOTIFY,
+        TPM_RETURNCMD, TPM_RIGHTBUTTON, WINDOW_EX_STYLE, WM_APP, WM_CONTEXTMENU, WM_LBUTTONUP, WM_NULL,
+        WM_RBUTTONUP, WNDCLASSW, WS_OVERLAPPED,
+    },
+};
+
+/// Something picked from the tray icon.
+#[cfg(windows)]
+#[derive(Clone, Copy, PartialEq)]
+pub enum TrayCommand {
+    ToggleViewer,
+    TogglePause,
+    NextImage,
+    Quit,
+}
+
+/// What the tray icon shows, sent from the app whenever it changes.
+#[derive(Clone, Default, PartialEq)]
+pub struct TrayStatus {
+    pub tooltip: String,
+    /// Swaps the icon between the tracking and idle dot.
+    pub tracking: bool,
+    pub viewer_visible: bool,
+    pub paused: bool,
+}
+
+/// The notification area icon and its menu, run from a hidden window on a
+/// dedicated thread so it responds while the viewer is hidden.
+pub struct Tray {
+    #[cfg(windows)]
+    rx: Receiver<TrayCommand>,
+    #[cfg(windows)]
+    shared: Arc<Shared>,
+    last_status: TrayStatus,
+}
+
+#[cfg(windows)]
+struct Shared {
+    status: Mutex<TrayStatus>,
+    /// The hidden window's handle, zero until it exists.
+    hwnd: AtomicIsize,
+}
+
+#[cfg(windows)]
+const WM_TRAY: u32 = WM_APP + 1;
+#[cfg(windows)]
+const WM_STATUS: u32 = WM_APP + 2;
+
+impl Tray {
+    /// Returns `None` where there is no tray to put the icon in.
+    #[cfg(windows)]
+    pub fn new(ctx: egui::Context) -> Option<Self> {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let shared = Arc::new(Shared {
+            status: Mutex::new(TrayStatus::default()),
+            hwnd: AtomicIsize::new(0),
+        });
+
+        let listener = Arc::clone(&shared);
+        thread::spawn(move || run(tx, listener, ctx));
+        Some(Self {
+            rx,
+            shared,
+            last_status: TrayStatus::default(),
+        })
+    }
+
+    #[cfg(not(windows))]
+    pub fn new(_ctx: egui::Context) -> Option<Self> {
+        None
+    }
+
+    /// Takes the menu picks and clicks since the last call.
+    #[cfg(windows)]
+    pub fn drain(&self) -> Vec<TrayCommand> {
+        self.rx.try_iter().collect()
+    }
+
+    pub fn set_status(&mut self, status: TrayStatus) {
+        if status == self.last_status {
+            return;
+        }
+        self.last_status = status.clone();
+
+        #[cfg(windows)]
+        {
+            if let Ok(mut shared) = self.shared.status.lock() {
+                *shared = status;
+            }
+            let hwnd = HWND(self.shared.hwnd.load(Ordering::SeqCst));
+            if hwnd.0 != 0 {
+                let _ = unsafe { PostMessageW(hwnd, WM_STATUS, WPARAM(0), LPARAM(0)) };
+            }
+        }
+    }
+}
+
+#[cfg(windows)]
+struct Listener {
+    tx: Sender<TrayCommand>,
+    shared: Arc<Shared>,
+    ctx: egui::Context,
+    tracking_icon: HICON,
+    idle_icon: HICON,
+    /// Sent by Explorer when it restarts, after which the icon has to be added again.
+    taskbar_created: u32,
+}
+
+#[cfg(windows)]
+thread_local! {
+    static LISTENER: RefCell<Option<Listener>> = const { RefCell::new(None) };
+}
+
+#[cfg(windows)]
+fn run(tx: Sender<TrayCommand>, shared: Arc<Shared>, ctx: egui::Context) {
+    let hwnd = unsafe {
+        let Ok(instance) = GetModuleHandleW(None) else { return };
+        let class = WNDCLASSW {
+            lpfnWndProc: Some(window_proc),
+            hInstance: instance.into(),
+            lpszClassName: w!("GermiBoardTray"),
+            ..Default::default()
+        };
+        RegisterClassW(&class);
+        // A window that is never shown; message-only windows don't get the
+        // TaskbarCreated broadcast.
+        CreateWindowExW(
+            WINDOW_EX_STYLE::default(),
+            w!("GermiBoardTray"),
+            w!("GermiBoard"),
+            WS_OVERLAPPED,
+            0,
+            0,
+            0,
+            0,
+            None,
+            None,
+            instance,
+            None,
+        )
+    };
+    if hwnd.0 == 0 {
+        log::warn!("Couldn't create the tray window");
+        return;
+    }
+
+    let listener = Listener {
+        tx,
+        shared: Arc::clone(&shared),
+        ctx,
+        tracking_icon: dot_icon([220, 40, 40]),
+        idle_icon: dot_icon([130, 130, 130]),
+        taskbar_created: unsafe { RegisterWindowMessageW(w!("TaskbarCreated")) },
+    };
+    update_icon(hwnd, &listener, NIM_ADD);
+    LISTENER.with(|slot| *slot.borrow_mut() = Some(listener));
+    shared.hwnd.store(hwnd.0, Ordering::SeqCst);
+
+    let mut msg = MSG::default();
+    while unsafe { GetMessageW(&mut msg, HWND::default(), 0, 0) }.as_bool() {
+        unsafe { DispatchMessageW(&msg) };
+    }
+}
+
+#[cfg(windows)]
+unsafe extern "system" fn window_proc(hwnd: HWND, message: u32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+    let handled = LISTENER.with(|slot| {
+        let slot = slot.borrow();
+        let listener = slot.as_ref()?;
+        match message {
+            WM_STATUS => update_icon(hwnd, listener, NIM_MODIFY),
+            WM_TRAY => match lparam.0 as u32 {
+                WM_LBUTTONUP => listener.send(TrayCommand::ToggleViewer),
+                WM_RBUTTONUP | WM_CONTEXTMENU => {
+                    if let Some(command) = show_menu(hwnd, listener) {
+                        listener.send(command);
+                    }
+                }
+                _ => {}
+            },
+            _ if message == listener.taskbar_created => update_icon(hwnd, listener, NIM_ADD),
+            _ => return None,
+        }
+        Some(LRESULT(0))
+    });
+    handled.unwrap_or_else(|| DefWindowProcW(hwnd, message, wparam, lparam))
+}
+
+#[cfg(windows)]
+impl Listener {
+    fn send(&self, command: TrayCommand) {
+        let _ = self.tx.send(command);
+        self.ctx.request_repaint();
+    }
+}
+
+#[cfg(windows)]
+fn update_icon(hwnd: HWND, listener: &Listener, action: NOTIFY_ICON_MESSAGE) {
+    let status = listener.shared.status.lock().map(|s| s.clone()).unwrap_or_default();
+    let mut data = NOTIFYICONDATAW {
+        cbSize: std::mem::size_of::<NOTIFYICONDATAW>() as u32,
+        hWnd: hwnd,
+        uID: 1,
+        uFlags: NIF_ICON | NIF_MESSAGE | NIF_TIP,
+        uCallbackMessage: WM_TRAY,
+        hIcon: if status.tracking { listener.tracking_icon } else { listener.idle_icon },
+        ..Default::default()
+    };
+    let tooltip = if status.tooltip.is_empty() { "GermiBoard" } else { &status.tooltip };
+    let tip: Vec<u16> = tooltip.encode_utf16().take(data.szTip.len() - 1).collect();
+    data.szTip[..tip.len()].copy_from_slice(&tip);
+    let _ = unsafe { Shell_NotifyIconW(action, &data) };
+}
+
+#[cfg(windows)]
+fn show_menu(hwnd: HWND, listener: &Listener) -> Option<TrayCommand> {
+    let status = listener.shared.status.lock().map(|s| s.clone()).unwrap_or_default();
+    let items = [
+        (TrayCommand::ToggleViewer, if status.viewer_visible { "Hide Viewer" } else { "Show Viewer" }),
+        (TrayCommand::TogglePause, if status.paused { "Resume Timer" } else { "Pause Timer" }),
+        (TrayCommand::NextImage, "Next Image"),
+        (TrayCommand::Quit, "Quit"),
+    ];
+
+    unsafe {
+        let menu = CreatePopupMenu().ok()?;
+        for (i, (command, label)) in items.iter().enumerate() {
+            if *command == TrayCommand::Quit {
+                let _ = AppendMenuW(menu, MF_SEPARATOR, 0, None);
+            }
+            let _ = AppendMenuW(menu, MF_STRING, i + 1, &HSTRING::from(*label));
+        }
+
+        let mut pt = POINT::default();
+        let _ = GetCursorPos(&mut pt);
+        // Without this the menu doesn't close when clicking elsewhere.
+        let _ = SetForegroundWindow(hwnd);
+        let picked = TrackPopupMenu(menu, TPM_RETURNCMD | TPM_NONOTIFY | TPM_RIGHTBUTTON, pt.x, pt.y, 0, hwnd, None);
+        let _ = PostMessageW(hwnd, WM_NULL, WPARAM(0), LPARAM(0));
+        let _ = DestroyMenu(menu);
+
+        (picked.0 as usize).checked_sub(1).and_then(|i| items.get(i)).map(|(command, _)| *command)
+    }
+}
+
+/// A filled 16x16 circle, drawn in code so no icon files need to ship.
+#[cfg(windows)]
+fn dot_icon([r, g, b]: [u8; 3]) -> HICON {
+    const SIZE: i32 = 16;
+    let pixels: Vec<u32> = (0..SIZE * SIZE)
+        .map(|i| {
+            let (x, y) = ((i % SIZE) as f32 - 7.5, (i / SIZE) as f32 - 7.5);
+            if x * x + y * y <= 6.5 * 6.5 {
+                0xff00_0000 | (r as u32) << 16 | (g as u32) << 8 | b as u32
+            } else {
+                0
+            }
+        })
+        .collect();
+    // The color bitmap's alpha does the masking; the mask only has to exist.
+    let mask = [0u8; (SIZE * SIZE / 8) as usize];
+
+    unsafe {
+        let color = CreateBitmap(SIZE, SIZE, 1, 32, Some(pixels.as_ptr().cast()));
+        let mask = CreateBitmap(SIZE, SIZE, 1, 1, Some(mask.as_ptr().cast()));
+        let info = ICONINFO {
+            fIcon: true.into(),
+            hbmMask: mask,
+            hbmColor: color,
+            ..Default::default()
+        };
+        let icon = CreateIconIndirect(&info).unwrap_or_default();
+        let _ = DeleteObject(color);
+        let _ = DeleteObject(mask);
+        icon
+    }
+}
+
+/// Removes the icon when the app exits, rather than leaving it behind until the
+/// user hovers over it.
+#[cfg(windows)]
+impl Drop for Tray {
+    fn drop(&mut self) {
+        let hwnd = HWND(self.shared.hwnd.load(Ordering::SeqCst));
+        if hwnd.0 != 0 {
+            let data = NOTIFYICONDATAW {
+                cbSize: std::mem::size_of::<NOTIFYICONDATAW>() as u32,
+                hWnd: hwnd,
+                uID: 1,
+                ..Default::default()
+            };
+            let _ = unsafe { Shell_NotifyIconW(NIM_DELETE, &data) };
+        }
+    }
+}
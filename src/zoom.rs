@@ -0,0 +1,293 @@
+//! Zooming into part of the image, panning around it, turning and mirroring it.
+//! Only the texture coordinates drawn change, so the texture is never re-uploaded.
+
+use eframe::egui::{epaint::Vertex, pos2, vec2, Color32, Mesh, Painter, Pos2, Rect, Shape, TextureId, Vec2};
+use serde::{Deserialize, Serialize};
+
+use crate::i18n::tr;
+
+const MAX_ZOOM: f32 = 16.0;
+
+/// How the image is sized to the room the window gives it.
+#[derive(Serialize, Deserialize, Default, Clone, Copy, PartialEq)]
+pub enum FitMode {
+    /// As large as fits whole.
+    #[default]
+    Fit,
+    /// As small as covers the room, cutting off what hangs over.
+    Fill,
+    /// One image pixel to one screen pixel, cut off if it doesn't fit.
+    ActualPixels,
+    /// Covering the room, out of proportion.
+    Stretch,
+}
+
+impl FitMode {
+    pub const ALL: [FitMode; 4] = [Self::Fit, Self::Fill, Self::ActualPixels, Self::Stretch];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Fit => tr("Fit"),
+            Self::Fill => tr("Fill"),
+            Self::ActualPixels => tr("Actual Pixels"),
+            Self::Stretch => tr("Stretch"),
+        }
+    }
+
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|m| *m == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    /// The size to draw an image `image` pixels large, as shown, in `room`
+    /// points, and the fraction of its width and height that's drawn there.
+    pub fn layout(self, image: Vec2, room: Vec2, pixels_per_point: f32) -> (Vec2, Vec2) {
+        // A window smaller than its margins leaves no room, which would divide by zero.
+        let room = room.max(Vec2::splat(1.0));
+        match self {
+            Self::Fit => (image * (room / image).min_elem(), Vec2::splat(1.0)),
+            Self::Fill => (room, room / (image * (room / image).max_elem())),
+            Self::ActualPixels => {
+                let natural = image / pixels_per_point;
+                let size = natural.min(room);
+                (size, size / natural)
+            }
+            Self::Stretch => (room, Vec2::splat(1.0)),
+        }
+    }
+}
+
+/// The part of the image on screen. Zoom and pan work on the image as it's shown,
+/// after turning and mirroring it.
+#[derive(Clone, Copy, PartialEq)]
+pub struct View {
+    zoom: f32,
+    /// The point of the turned image at the middle of the view, in 0..1.
+    center: Pos2,
+    /// The fraction of the turned image's width and height on screen before
+    /// zooming in, less than 1 where the fit mode cuts it off.
+    extent: Vec2,
+    /// Clockwise turns by 90°.
+    quarter_turns: u8,
+    /// Mirrored left to right and top to bottom, as shown after turning.
+    flip_x: bool,
+    flip_y: bool,
+    /// The part of the texture shown as the whole image, in 0..1.
+    crop: Rect,
+}
+
+impl Default for View {
+    fn default() -> Self {
+        Self::turned(0)
+    }
+}
+
+impl View {
+    /// The whole image, turned clockwise by `quarter_turns` times 90°.
+    pub fn turned(quarter_turns: u8) -> Self {
+        Self {
+            zoom: 1.0,
+            center: pos2(0.5, 0.5),
+            extent: Vec2::splat(1.0),
+            quarter_turns: quarter_turns % 4,
+            flip_x: false,
+            flip_y: false,
+            crop: Rect::from_min_max(Pos2::ZERO, pos2(1.0, 1.0)),
+        }
+    }
+
+    pub fn quarter_turns(&self) -> u8 {
+        self.quarter_turns
+    }
+
+    /// Turns the image by 90° as shown and shows all of it again.
+    pub fn rotate(&mut self, clockwise: bool) {
+        // A mirrored image turns the other way round underneath the mirror.
+        let clockwise = clockwise != (self.flip_x != self.flip_y);
+        *self = Self {
+            flip_x: self.flip_x,
+            flip_y: self.flip_y,
+            crop: self.crop,
+            ..Self::turned(self.quarter_turns + if clockwise { 1 } else { 3 })
+        };
+    }
+
+    /// Whether the image is mirrored horizontally and vertically.
+    pub fn flips(&self) -> (bool, bool) {
+        (self.flip_x, self.flip_y)
+    }
+
+    pub fn with_flips(self, (flip_x, flip_y): (bool, bool)) -> Self {
+        Self { flip_x, flip_y, ..self }
+    }
+
+    /// Shows only `crop` of the texture, given in 0..1, as if it were the whole
+    /// image, or all of it for `None`.
+    pub fn with_crop(self, crop: Option<Rect>) -> Self {
+        let crop = crop.unwrap_or(Self::default().crop);
+        Self { crop, ..self }
+    }
+
+    /// The part of the texture shown, if not all of it.
+    pub fn crop(&self) -> Option<Rect> {
+        (self.crop != Self::default().crop).then_some(self.crop)
+    }
+
+    /// Mirrors the image left to right, or top to bottom if not `horizontal`,
+    /// keeping the same part of it in view.
+    pub fn flip(&mut self, horizontal: bool) {
+        if horizontal {
+            self.flip_x = !self.flip_x;
+            self.center.x = 1.0 - self.center.x;
+        } else {
+            self.flip_y = !self.flip_y;
+            self.center.y = 1.0 - self.center.y;
+        }
+    }
+
+    /// A reminder that the image isn't as it was drawn, if it's mirrored.
+    pub fn flip_label(&self) -> Option<&'static str> {
+        match (self.flip_x, self.flip_y) {
+            (true, true) => Some(tr("Flipped both ways")),
+            (true, false) => Some(tr("Flipped")),
+            (false, true) => Some(tr("Flipped vertically")),
+            (false, false) => None,
+        }
+    }
+
+    /// Shows the whole image, still turned and mirrored.
+    pub fn reset_zoom(&mut self) {
+        self.zoom = 1.0;
+        self.center = pos2(0.5, 0.5);
+    }
+
+    /// The size of the image as shown, given its `width` and `height`.
+    pub fn shown_size(&self, width: f32, height: f32) -> Vec2 {
+        let (width, height) = (width * self.crop.width(), height * self.crop.height());
+        if self.quarter_turns % 2 == 1 {
+            vec2(height, width)
+        } else {
+            vec2(width, height)
+        }
+    }
+
+    pub fn is_zoomed(&self) -> bool {
+        self.zoom > 1.0
+    }
+
+    /// Whether some of the image is out of view, so there's somewhere to pan to.
+    pub fn can_pan(&self) -> bool {
+        self.zoom > 1.0 || self.extent.x < 1.0 || self.extent.y < 1.0
+    }
+
+    /// Sets how much of the image the fit mode shows before zooming in, as laid
+    /// out by `FitMode::layout`.
+    pub fn set_extent(&mut self, extent: Vec2) {
+        if extent != self.extent {
+            self.extent = extent.clamp(Vec2::splat(0.0001), Vec2::splat(1.0));
+            self.clamp();
+        }
+    }
+
+    /// The part of the turned image to draw.
+    fn uv(&self) -> Rect {
+        Rect::from_center_size(self.center, self.extent / self.zoom)
+    }
+
+    /// Zooms by `factor` around `pointer`, given as a fraction of the image's
+    /// width and height, keeping the point under it in place.
+    pub fn zoom_at(&mut self, factor: f32, pointer: Vec2) {
+        let uv = self.uv();
+        let point = uv.min + pointer * uv.size();
+        self.zoom = (self.zoom * factor).clamp(1.0, MAX_ZOOM);
+        let size = self.extent / self.zoom;
+        self.center = point - pointer * size + size / 2.0;
+        self.clamp();
+    }
+
+    /// Moves the image along with a drag of `delta` over an image drawn `size` large.
+    pub fn pan(&mut self, delta: Vec2, size: Vec2) {
+        self.center -= delta / size * self.uv().size();
+        self.clamp();
+    }
+
+    /// Draws the view of `texture` into `rect`.
+    pub fn paint(&self, painter: &Painter, rect: Rect, texture: TextureId) {
+        self.paint_faded(painter, rect, texture, 1.0);
+    }
+
+    /// Like `paint`, drawn see-through, from 0 (invisible) to 1 (solid).
+    pub fn paint_faded(&self, painter: &Painter, rect: Rect, texture: TextureId, opacity: f32) {
+        let color = Color32::WHITE.gamma_multiply(opacity.clamp(0.0, 1.0));
+        let uv = self.uv();
+        let mut mesh = Mesh::with_texture(texture);
+        let corners = [rect.left_top(), rect.right_top(), rect.right_bottom(), rect.left_bottom()];
+        let uvs = [uv.left_top(), uv.right_top(), uv.right_bottom(), uv.left_bottom()];
+        for (pos, uv) in corners.into_iter().zip(uvs) {
+            mesh.vertices.push(Vertex {
+                pos,
+                uv: self.texture_point(uv),
+                color,
+            });
+        }
+        mesh.add_triangle(0, 1, 2);
+        mesh.add_triangle(0, 2, 3);
+        painter.add(Shape::mesh(mesh));
+    }
+
+    /// Where a point of the image as shown comes from in the texture.
+    fn texture_point(&self, p: Pos2) -> Pos2 {
+        self.crop.min + self.unturned(p).to_vec2() * self.crop.size()
+    }
+
+    /// A point of the image as shown, turned and mirrored back.
+    fn unturned(&self, p: Pos2) -> Pos2 {
+        let p = pos2(
+            if self.flip_x { 1.0 - p.x } else { p.x },
+            if self.flip_y { 1.0 - p.y } else { p.y },
+        );
+        match self.quarter_turns {
+            1 => pos2(p.y, 1.0 - p.x),
+            2 => pos2(1.0 - p.x, 1.0 - p.y),
+            3 => pos2(1.0 - p.y, p.x),
+            _ => p,
+        }
+    }
+
+    /// Where a point of the texture, in 0..1, is drawn when the view fills `rect`.
+    pub fn screen_point(&self, rect: Rect, p: Pos2) -> Pos2 {
+        let p = ((p - self.crop.min) / self.crop.size()).to_pos2();
+        let p = match self.quarter_turns {
+            1 => pos2(1.0 - p.y, p.x),
+            2 => pos2(1.0 - p.x, 1.0 - p.y),
+            3 => pos2(p.y, 1.0 - p.x),
+            _ => p,
+        };
+        let p = pos2(
+            if self.flip_x { 1.0 - p.x } else { p.x },
+            if self.flip_y { 1.0 - p.y } else { p.y },
+        );
+        let uv = self.uv();
+        rect.min + (p - uv.min) / uv.size() * rect.size()
+    }
+
+    /// Where in the texture, in 0..1, the point `p` of the view filling `rect`
+    /// comes from. The reverse of `screen_point`.
+    pub fn texture_at(&self, rect: Rect, p: Pos2) -> Pos2 {
+        let uv = self.uv();
+        self.texture_point(uv.min + (p - rect.min) / rect.size() * uv.size())
+    }
+
+    /// Which way a step of `delta` across the image as shown goes in the
+    /// texture: one pixel right is one pixel up in an image turned clockwise.
+    pub fn texture_step(&self, delta: Vec2) -> Vec2 {
+        self.unturned(delta.to_pos2()) - self.unturned(Pos2::ZERO)
+    }
+
+    /// Keeps the view within the image.
+    fn clamp(&mut self) {
+        let half = self.extent / self.zoom / 2.0;
+        self.center.x = self.center.x.clamp(half.x, 1.0 - half.x);
+        self.center.y = self.center.y.clamp(half.y, 1.0 - half.y);
+    }
+}